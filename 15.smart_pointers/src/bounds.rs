@@ -0,0 +1,49 @@
+//! Functions that only compile for Send or Sync types, so the compiler
+//! checks the claims of `markers`. An Rc<List> can't be sent to another
+//! thread, its count isn't atomic:
+//!
+//! ```compile_fail,E0277
+//! use smart_pointers::bounds::require_send;
+//! use smart_pointers::list::List::{Cons, Nil};
+//! use std::rc::Rc;
+//!
+//! let list = Rc::new(Cons(5, Rc::new(Cons(10, Rc::new(Nil)))));
+//! require_send(&list);
+//! ```
+//!
+//! and neither can a thread be spawned with one:
+//!
+//! ```compile_fail,E0277
+//! use smart_pointers::list::List::{Cons, Nil};
+//! use std::rc::Rc;
+//! use std::thread;
+//!
+//! let list = Rc::new(Cons(5, Rc::new(Cons(10, Rc::new(Nil)))));
+//! thread::spawn(move || list.to_vec()).join().unwrap();
+//! ```
+//!
+//! A RefCell<i32> can't be shared between threads, even behind an Arc:
+//!
+//! ```compile_fail,E0277
+//! use smart_pointers::bounds::require_sync;
+//! use std::cell::RefCell;
+//!
+//! require_sync(&RefCell::new(0));
+//! ```
+//!
+//! while Arc<Mutex<T>> is both:
+//!
+//! ```
+//! use smart_pointers::bounds::{require_send, require_sync};
+//! use std::sync::{Arc, Mutex};
+//!
+//! let counter = Arc::new(Mutex::new(0));
+//! require_send(&counter);
+//! require_sync(&counter);
+//! ```
+
+/// Compiles only for Send types.
+pub fn require_send<T: Send>(_: &T) {}
+
+/// Compiles only for Sync types.
+pub fn require_sync<T: Sync>(_: &T) {}
@@ -0,0 +1,108 @@
+/// The multi-threaded counterpart of MutList: Arc<T> is the atomically
+/// reference counted Rc<T>, and Mutex<T> is the lock-based RefCell<T>.
+/// Rc<RefCell<T>> refuses to cross a thread boundary at compile time,
+/// Arc<Mutex<T>> is built to, in exactly the same Cons shape.
+use std::sync::{Arc, Mutex};
+use std::thread;
+use SharedList::SharedCons;
+
+#[derive(Debug)]
+pub enum SharedList {
+  SharedCons(Arc<Mutex<i32>>, Arc<SharedList>),
+  Nil
+}
+
+impl SharedList {
+  /// Locks one cell at a time, so a concurrent writer may land between
+  /// two cells, but never inside one.
+  pub fn sum(&self) -> i64 {
+    let mut total = 0;
+    let mut current = self;
+    while let SharedCons(cell, next) = current {
+      total += i64::from(*cell.lock().unwrap());
+      current = next;
+    }
+    total
+  }
+
+  pub fn values(&self) -> Vec<i32> {
+    let mut values = vec![];
+    let mut current = self;
+    while let SharedCons(cell, next) = current {
+      values.push(*cell.lock().unwrap());
+      current = next;
+    }
+    values
+  }
+}
+
+/// Spawns `threads` threads that each add `amount` to `cell`, and waits
+/// for all of them. Each thread gets its own Arc, moved into its closure.
+pub fn add_from_threads(cell: &Arc<Mutex<i32>>, threads: usize, amount: i32) {
+  let handles: Vec<_> = (0..threads)
+    .map(|_| {
+      let cell = Arc::clone(cell);
+      thread::spawn(move || {
+        *cell.lock().unwrap() += amount;
+      })
+    })
+    .collect();
+
+  for handle in handles {
+    handle.join().unwrap();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use SharedList::Nil;
+
+  fn cell(value: i32) -> Arc<Mutex<i32>> {
+    Arc::new(Mutex::new(value))
+  }
+
+  #[test]
+  fn threads_add_deterministically() {
+    for _ in 0..20 {
+      let value = cell(5);
+      add_from_threads(&value, 4, 10);
+      assert_eq!(*value.lock().unwrap(), 5 + 4 * 10);
+    }
+  }
+
+  #[test]
+  fn every_sharing_list_sees_the_result() {
+    let value = cell(5);
+    let a = Arc::new(SharedCons(Arc::clone(&value), Arc::new(Nil)));
+    let b = SharedCons(cell(3), Arc::clone(&a));
+    let c = SharedCons(cell(4), Arc::clone(&a));
+
+    add_from_threads(&value, 4, 10);
+    assert_eq!(a.values(), vec![45]);
+    assert_eq!(b.values(), vec![3, 45]);
+    assert_eq!(c.values(), vec![4, 45]);
+    assert_eq!(b.sum(), 48);
+    assert_eq!(Nil.sum(), 0);
+  }
+
+  #[test]
+  fn strong_counts_follow_the_rc_demo() {
+    let a = Arc::new(SharedCons(cell(5), Arc::new(SharedCons(cell(10), Arc::new(Nil)))));
+    assert_eq!(Arc::strong_count(&a), 1);
+    let _b = SharedCons(cell(3), Arc::clone(&a));
+    assert_eq!(Arc::strong_count(&a), 2);
+    {
+      let _c = SharedCons(cell(4), Arc::clone(&a));
+      assert_eq!(Arc::strong_count(&a), 3);
+    }
+    assert_eq!(Arc::strong_count(&a), 2);
+  }
+
+  #[test]
+  fn threads_release_their_handles() {
+    let value = cell(0);
+    add_from_threads(&value, 8, 1);
+    assert_eq!(Arc::strong_count(&value), 1);
+  }
+}
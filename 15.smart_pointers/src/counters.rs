@@ -0,0 +1,124 @@
+/// Two ways to share a counter between threads. A Mutex<u64> takes a
+/// lock around every update; an AtomicU64 updates in one CPU instruction
+/// and needs no lock at all, but only works for plain integers.
+use crate::pool::ThreadPool;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub trait Counter: Send + Sync {
+  fn add(&self, n: u64);
+  fn get(&self) -> u64;
+}
+
+#[derive(Debug, Default)]
+pub struct MutexCounter(Mutex<u64>);
+
+impl Counter for MutexCounter {
+  fn add(&self, n: u64) {
+    *self.0.lock().unwrap() += n;
+  }
+
+  fn get(&self) -> u64 {
+    *self.0.lock().unwrap()
+  }
+}
+
+#[derive(Debug, Default)]
+pub struct AtomicCounter(AtomicU64);
+
+impl Counter for AtomicCounter {
+  // no other memory is published through the counter, Relaxed is enough
+  fn add(&self, n: u64) {
+    self.0.fetch_add(n, Ordering::Relaxed);
+  }
+
+  fn get(&self) -> u64 {
+    self.0.load(Ordering::Relaxed)
+  }
+}
+
+#[derive(Debug)]
+pub struct BenchResult {
+  pub mutex_total: u64,
+  pub mutex_elapsed: Duration,
+  pub atomic_total: u64,
+  pub atomic_elapsed: Duration
+}
+
+/// `threads` threads each add 1 to `counter`, `iters` times.
+pub fn hammer(counter: Arc<dyn Counter>, threads: usize, iters: u64) -> (u64, Duration) {
+  let start = Instant::now();
+  let handles: Vec<_> = (0..threads)
+    .map(|_| {
+      let counter = Arc::clone(&counter);
+      thread::spawn(move || {
+        for _ in 0..iters {
+          counter.add(1);
+        }
+      })
+    })
+    .collect();
+  for handle in handles {
+    handle.join().unwrap();
+  }
+  (counter.get(), start.elapsed())
+}
+
+/// Times both counters on the same workload. Which one wins depends on
+/// the machine, the totals never do.
+pub fn bench_counters(threads: usize, iters: u64) -> BenchResult {
+  let (mutex_total, mutex_elapsed) = hammer(Arc::new(MutexCounter::default()), threads, iters);
+  let (atomic_total, atomic_elapsed) = hammer(Arc::new(AtomicCounter::default()), threads, iters);
+  BenchResult { mutex_total, mutex_elapsed, atomic_total, atomic_elapsed }
+}
+
+/// The thread-pool demo with the counter swapped out: `jobs` jobs each add 1.
+pub fn count_with_pool(counter: Arc<dyn Counter>, size: usize, jobs: usize) -> u64 {
+  let pool = ThreadPool::new(size).unwrap();
+  for _ in 0..jobs {
+    let counter = Arc::clone(&counter);
+    pool.execute(move || counter.add(1));
+  }
+  drop(pool);
+  counter.get()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn both_counters_are_exact() {
+    let result = bench_counters(4, 5_000);
+    assert_eq!(result.mutex_total, 20_000);
+    assert_eq!(result.atomic_total, 20_000);
+  }
+
+  #[test]
+  fn bench_measures_some_time() {
+    let result = bench_counters(2, 1_000);
+    assert!(result.mutex_elapsed > Duration::from_nanos(0));
+    assert!(result.atomic_elapsed > Duration::from_nanos(0));
+  }
+
+  #[test]
+  fn counters_swap_in_the_pool_demo() {
+    let counters: Vec<Arc<dyn Counter>> = vec![Arc::new(MutexCounter::default()), Arc::new(AtomicCounter::default())];
+    for counter in counters {
+      assert_eq!(count_with_pool(counter, 3, 100), 100);
+    }
+  }
+
+  #[test]
+  fn add_counts_by_n() {
+    let counter = AtomicCounter::default();
+    counter.add(5);
+    counter.add(7);
+    assert_eq!(counter.get(), 12);
+    let counter = MutexCounter::default();
+    counter.add(3);
+    assert_eq!(counter.get(), 3);
+  }
+}
@@ -0,0 +1,163 @@
+/// Two threads, two locks, opposite order: thread 1 holds a and waits for
+/// b while thread 2 holds b and waits for a, and neither ever lets go.
+/// `transfer` has exactly that bug. `transfer_safe` always locks the
+/// account at the lower address first, so every thread agrees on the order;
+/// `transfer_try` gives up instead of waiting forever.
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+use std::thread;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Account {
+  pub id: u32,
+  pub balance: i64
+}
+
+pub type SharedAccount = Arc<Mutex<Account>>;
+
+pub fn account(id: u32, balance: i64) -> SharedAccount {
+  Arc::new(Mutex::new(Account { id, balance }))
+}
+
+#[derive(Debug, PartialEq)]
+pub struct WouldBlock;
+
+fn move_money(from: &mut Account, to: &mut Account, amount: i64) {
+  from.balance -= amount;
+  to.balance += amount;
+}
+
+/// Locks `from`, then `to`. Fine on one thread; two threads transferring
+/// in opposite directions can each grab their first lock and wait forever.
+/// Transferring from an account to itself deadlocks even on one thread.
+pub fn transfer(from: &SharedAccount, to: &SharedAccount, amount: i64) {
+  let mut from = from.lock().unwrap();
+  let mut to = to.lock().unwrap();
+  move_money(&mut from, &mut to, amount);
+}
+
+/// Locks the account at the lower address first, whichever way the money
+/// goes. The address is fixed for as long as the Arc<T> lives and needs no
+/// lock to read, unlike anything inside the Mutex<T>, such as `id`.
+pub fn transfer_safe(from: &SharedAccount, to: &SharedAccount, amount: i64) {
+  if Arc::ptr_eq(from, to) {
+    return;
+  }
+  if Arc::as_ptr(from) < Arc::as_ptr(to) {
+    let mut from = from.lock().unwrap();
+    let mut to = to.lock().unwrap();
+    move_money(&mut from, &mut to, amount);
+  } else {
+    let mut to = to.lock().unwrap();
+    let mut from = from.lock().unwrap();
+    move_money(&mut from, &mut to, amount);
+  }
+}
+
+const RETRIES: usize = 100;
+
+fn try_lock(account: &SharedAccount) -> Option<MutexGuard<'_, Account>> {
+  match account.try_lock() {
+    Ok(guard) => Some(guard),
+    Err(TryLockError::WouldBlock) => None,
+    Err(TryLockError::Poisoned(err)) => panic!("account lock poisoned: {}", err)
+  }
+}
+
+/// Never waits on a lock: when either account is busy it drops whatever
+/// it holds, yields and tries again, up to a fixed number of attempts.
+pub fn transfer_try(from: &SharedAccount, to: &SharedAccount, amount: i64) -> Result<(), WouldBlock> {
+  if Arc::ptr_eq(from, to) {
+    return Ok(());
+  }
+  for _ in 0..RETRIES {
+    if let Some(mut from_guard) = try_lock(from) {
+      if let Some(mut to_guard) = try_lock(to) {
+        move_money(&mut from_guard, &mut to_guard, amount);
+        return Ok(());
+      }
+    }
+    thread::yield_now();
+  }
+  Err(WouldBlock)
+}
+
+/// Runs `iterations` transfers of 1 from a to b on one thread and from b
+/// to a on another, the script that can hang with the naive `transfer`.
+pub fn opposing_transfers(
+  a: &SharedAccount,
+  b: &SharedAccount,
+  iterations: usize,
+  transfer_fn: fn(&SharedAccount, &SharedAccount, i64)
+) {
+  let forward = {
+    let (a, b) = (Arc::clone(a), Arc::clone(b));
+    thread::spawn(move || (0..iterations).for_each(|_| transfer_fn(&a, &b, 1)))
+  };
+  let backward = {
+    let (a, b) = (Arc::clone(a), Arc::clone(b));
+    thread::spawn(move || (0..iterations).for_each(|_| transfer_fn(&b, &a, 1)))
+  };
+  forward.join().unwrap();
+  backward.join().unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn balances(a: &SharedAccount, b: &SharedAccount) -> (i64, i64) {
+    (a.lock().unwrap().balance, b.lock().unwrap().balance)
+  }
+
+  #[test]
+  fn safe_opposing_transfers_conserve_the_total() {
+    let (a, b) = (account(1, 500), account(2, 500));
+    opposing_transfers(&a, &b, 1000, transfer_safe);
+    assert_eq!(balances(&a, &b), (500, 500));
+
+    // the same with the ids the other way round
+    let (c, d) = (account(9, 100), account(3, 0));
+    opposing_transfers(&c, &d, 1000, transfer_safe);
+    let (c_balance, d_balance) = balances(&c, &d);
+    assert_eq!(c_balance + d_balance, 100);
+  }
+
+  #[test]
+  fn safe_opposing_transfers_with_equal_ids() {
+    let (a, b) = (account(7, 500), account(7, 500));
+    opposing_transfers(&a, &b, 1000, transfer_safe);
+    assert_eq!(balances(&a, &b), (500, 500));
+  }
+
+  #[test]
+  fn try_surfaces_contention() {
+    let (a, b) = (account(1, 100), account(2, 0));
+    let held = b.lock().unwrap();
+    assert_eq!(transfer_try(&a, &b, 10), Err(WouldBlock));
+    drop(held);
+
+    // nothing moved, and a was released again
+    assert_eq!(balances(&a, &b), (100, 0));
+    assert_eq!(transfer_try(&a, &b, 10), Ok(()));
+    assert_eq!(balances(&a, &b), (90, 10));
+  }
+
+  #[test]
+  fn single_threaded_transfers_agree() {
+    let (a, b) = (account(1, 100), account(2, 50));
+    transfer(&a, &b, 30);
+    assert_eq!(balances(&a, &b), (70, 80));
+    transfer_safe(&b, &a, 30);
+    assert_eq!(balances(&a, &b), (100, 50));
+    transfer_try(&a, &b, 30).unwrap();
+    assert_eq!(balances(&a, &b), (70, 80));
+  }
+
+  #[test]
+  fn safe_versions_allow_self_transfers() {
+    let a = account(1, 10);
+    transfer_safe(&a, &a, 5);
+    assert_eq!(transfer_try(&a, &a, 5), Ok(()));
+    assert_eq!(a.lock().unwrap().balance, 10);
+  }
+}
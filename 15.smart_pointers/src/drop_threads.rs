@@ -0,0 +1,109 @@
+/// Drop runs wherever the value's owner ends up. A value moved into a
+/// thread is dropped on that thread, unless the thread hands it back
+/// through its JoinHandle, and then it is dropped by whoever called join.
+/// A panicking thread still unwinds its stack, running Drop as it goes.
+use std::sync::{Arc, Mutex};
+use std::thread::{self, ThreadId};
+use crate::CustomSmartPointer;
+
+/// Shared between threads, so the pointers can log where they were dropped.
+#[derive(Debug, Clone, Default)]
+pub struct DropLog {
+  /// drops and the caller's own checkpoints, in the order they happened
+  pub events: Arc<Mutex<Vec<String>>>,
+  /// which thread dropped which pointer
+  pub droppers: Arc<Mutex<Vec<(String, ThreadId)>>>
+}
+
+impl DropLog {
+  pub fn note(&self, event: &str) {
+    self.events.lock().unwrap().push(event.to_string());
+  }
+
+  pub fn record_drop(&self, data: &str) {
+    self.note(&format!("dropped `{}`", data));
+    self.droppers.lock().unwrap().push((data.to_string(), thread::current().id()));
+  }
+
+  pub fn events(&self) -> Vec<String> {
+    self.events.lock().unwrap().clone()
+  }
+
+  pub fn dropper_of(&self, data: &str) -> Option<ThreadId> {
+    self.droppers.lock().unwrap().iter().find(|(dropped, _)| dropped == data).map(|&(_, id)| id)
+  }
+}
+
+pub fn logged(data: &str, log: &DropLog) -> CustomSmartPointer {
+  CustomSmartPointer { data: data.to_string(), log: Some(log.clone()) }
+}
+
+#[derive(Debug)]
+pub struct DropReport {
+  pub log: DropLog,
+  pub caller: ThreadId,
+  pub dropping_thread: ThreadId,
+  pub returning_thread: ThreadId
+}
+
+/// One thread drops its pointer before it finishes, another returns its
+/// pointer through the JoinHandle, which is then dropped here after join.
+pub fn drops_across_threads() -> DropReport {
+  let log = DropLog::default();
+
+  let inside = logged("dropped inside", &log);
+  let dropping = thread::spawn(move || {
+    drop(inside);
+    thread::current().id()
+  });
+  let dropping_thread = dropping.join().unwrap();
+  log.note("joined the dropping thread");
+
+  let returned = logged("returned", &log);
+  let returning = thread::spawn(move || (returned, thread::current().id()));
+  let (returned, returning_thread) = returning.join().unwrap();
+  log.note("joined the returning thread");
+  drop(returned);
+
+  DropReport { log, caller: thread::current().id(), dropping_thread, returning_thread }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn returned_pointer_drops_on_the_caller_after_join() {
+    let report = drops_across_threads();
+    let events = report.log.events();
+    let position = |event: &str| events.iter().position(|logged| logged == event).unwrap();
+    assert!(position("joined the returning thread") < position("dropped `returned`"));
+    assert_eq!(report.log.dropper_of("returned"), Some(report.caller));
+    assert_ne!(report.returning_thread, report.caller);
+  }
+
+  #[test]
+  fn in_thread_drop_happens_before_join_returns() {
+    let report = drops_across_threads();
+    assert_eq!(report.log.events()[..2], [
+      String::from("dropped `dropped inside`"),
+      String::from("joined the dropping thread")
+    ]);
+    assert_eq!(report.log.dropper_of("dropped inside"), Some(report.dropping_thread));
+  }
+
+  #[test]
+  fn panicking_thread_still_drops() {
+    let log = DropLog::default();
+    let owned = logged("owned by a panicking thread", &log);
+    let handle = thread::spawn(move || {
+      let _owned = owned;
+      panic!("the thread gives up");
+    });
+    let thread_id = handle.thread().id();
+
+    assert!(handle.join().is_err());
+    assert_eq!(log.events(), vec![String::from("dropped `owned by a panicking thread`")]);
+    assert_eq!(log.dropper_of("owned by a panicking thread"), Some(thread_id));
+  }
+}
@@ -0,0 +1,125 @@
+/// The cycle lesson generalized: a graph owns its nodes through Rc<T>,
+/// and edges between nodes are only Weak<T>. However the edges loop
+/// around, no node keeps another alive, so dropping the Graph frees
+/// everything, cycles included.
+use std::cell::RefCell;
+use std::collections::{HashSet, VecDeque};
+use std::rc::{Rc, Weak};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+#[derive(Debug)]
+pub struct GraphNode {
+  pub id: NodeId,
+  pub value: i32,
+  edges: Vec<Weak<RefCell<GraphNode>>>
+}
+
+#[derive(Debug, Default)]
+pub struct Graph {
+  nodes: Vec<Rc<RefCell<GraphNode>>>
+}
+
+impl Graph {
+  pub fn new() -> Graph {
+    Graph { nodes: vec![] }
+  }
+
+  pub fn add_node(&mut self, value: i32) -> NodeId {
+    let id = NodeId(self.nodes.len());
+    self.nodes.push(Rc::new(RefCell::new(GraphNode { id, value, edges: vec![] })));
+    id
+  }
+
+  /// Panics if either id doesn't belong to this graph.
+  pub fn add_edge(&mut self, from: NodeId, to: NodeId) {
+    let target = Rc::downgrade(&self.nodes[to.0]);
+    self.nodes[from.0].borrow_mut().edges.push(target);
+  }
+
+  pub fn node(&self, id: NodeId) -> Rc<RefCell<GraphNode>> {
+    Rc::clone(&self.nodes[id.0])
+  }
+
+  pub fn neighbors(&self, id: NodeId) -> Vec<NodeId> {
+    self.nodes[id.0]
+      .borrow()
+      .edges
+      .iter()
+      .filter_map(Weak::upgrade)
+      .map(|node| node.borrow().id)
+      .collect()
+  }
+
+  /// Breadth-first search. The visited set is what stops it from
+  /// looping forever around a cycle.
+  pub fn reachable(&self, from: NodeId, to: NodeId) -> bool {
+    let mut visited = HashSet::new();
+    let mut queue = VecDeque::new();
+    visited.insert(from);
+    queue.push_back(from);
+
+    while let Some(current) = queue.pop_front() {
+      if current == to {
+        return true;
+      }
+      for next in self.neighbors(current) {
+        if visited.insert(next) {
+          queue.push_back(next);
+        }
+      }
+    }
+    false
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // 0 -> 1 -> 2 -> 0 is a cycle, 3 -> 4 is a separate component
+  fn sample() -> (Graph, Vec<NodeId>) {
+    let mut graph = Graph::new();
+    let ids: Vec<NodeId> = (0..5).map(|value| graph.add_node(value * 10)).collect();
+    graph.add_edge(ids[0], ids[1]);
+    graph.add_edge(ids[1], ids[2]);
+    graph.add_edge(ids[2], ids[0]);
+    graph.add_edge(ids[3], ids[4]);
+    (graph, ids)
+  }
+
+  #[test]
+  fn neighbors_follow_edges() {
+    let (graph, ids) = sample();
+    assert_eq!(graph.neighbors(ids[1]), vec![ids[2]]);
+    assert_eq!(graph.neighbors(ids[4]), vec![]);
+    assert_eq!(graph.node(ids[2]).borrow().value, 20);
+  }
+
+  #[test]
+  fn reachability_terminates_on_cycles() {
+    let (graph, ids) = sample();
+    assert!(graph.reachable(ids[0], ids[2]));
+    assert!(graph.reachable(ids[2], ids[1]));
+    assert!(graph.reachable(ids[1], ids[1]));
+  }
+
+  #[test]
+  fn disconnected_components_are_unreachable() {
+    let (graph, ids) = sample();
+    assert!(graph.reachable(ids[3], ids[4]));
+    assert!(!graph.reachable(ids[4], ids[3]));
+    assert!(!graph.reachable(ids[0], ids[3]));
+  }
+
+  #[test]
+  fn dropping_graph_frees_cyclic_nodes() {
+    let (graph, ids) = sample();
+    let probes: Vec<_> = ids.iter().map(|&id| Rc::downgrade(&graph.node(id))).collect();
+    assert!(probes.iter().all(|probe| probe.upgrade().is_some()));
+
+    drop(graph);
+    assert!(probes.iter().all(|probe| probe.upgrade().is_none()));
+  }
+}
@@ -0,0 +1,117 @@
+/// An editable value with undo and redo, where every operation takes
+/// `&self`. Wrapped in an Rc<T>, several owners can edit the same value
+/// and share one history, just like Rc<RefCell<i32>> shares one cell.
+use std::cell::RefCell;
+
+pub struct EditableValue<T: Clone> {
+  value: RefCell<T>,
+  undo_stack: RefCell<Vec<T>>,
+  redo_stack: RefCell<Vec<T>>
+}
+
+impl<T: Clone> EditableValue<T> {
+  pub fn new(value: T) -> EditableValue<T> {
+    EditableValue {
+      value: RefCell::new(value),
+      undo_stack: RefCell::new(vec![]),
+      redo_stack: RefCell::new(vec![])
+    }
+  }
+
+  pub fn get(&self) -> T {
+    self.value.borrow().clone()
+  }
+
+  /// Records the current value for undo. A new edit forgets anything
+  /// that could have been redone.
+  pub fn set(&self, new: T) {
+    let old = self.value.replace(new);
+    self.undo_stack.borrow_mut().push(old);
+    self.redo_stack.borrow_mut().clear();
+  }
+
+  /// Returns false when there is nothing to undo.
+  pub fn undo(&self) -> bool {
+    let previous = self.undo_stack.borrow_mut().pop();
+    match previous {
+      Some(previous) => {
+        let current = self.value.replace(previous);
+        self.redo_stack.borrow_mut().push(current);
+        true
+      },
+      None => false
+    }
+  }
+
+  /// Returns false when there is nothing to redo.
+  pub fn redo(&self) -> bool {
+    let next = self.redo_stack.borrow_mut().pop();
+    match next {
+      Some(next) => {
+        let current = self.value.replace(next);
+        self.undo_stack.borrow_mut().push(current);
+        true
+      },
+      None => false
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::rc::Rc;
+
+  #[test]
+  fn linear_edit_undo_redo() {
+    let value = EditableValue::new(1);
+    value.set(2);
+    value.set(3);
+
+    assert!(value.undo());
+    assert_eq!(value.get(), 2);
+    assert!(value.undo());
+    assert_eq!(value.get(), 1);
+    assert!(value.redo());
+    assert_eq!(value.get(), 2);
+    assert!(value.redo());
+    assert_eq!(value.get(), 3);
+    assert!(!value.redo());
+  }
+
+  #[test]
+  fn set_after_undo_clears_redo() {
+    let value = EditableValue::new(String::from("a"));
+    value.set(String::from("b"));
+    assert!(value.undo());
+    value.set(String::from("c"));
+
+    assert!(!value.redo());
+    assert_eq!(value.get(), "c");
+    assert!(value.undo());
+    assert_eq!(value.get(), "a");
+  }
+
+  #[test]
+  fn empty_history() {
+    let value = EditableValue::new(5);
+    assert!(!value.undo());
+    assert!(!value.redo());
+    assert_eq!(value.get(), 5);
+  }
+
+  #[test]
+  fn rc_clones_share_history() {
+    let first = Rc::new(EditableValue::new(5));
+    let second = Rc::clone(&first);
+    first.set(15);
+    second.set(20);
+
+    assert!(first.undo());
+    assert_eq!(second.get(), 15);
+    assert!(second.undo());
+    assert_eq!(first.get(), 5);
+    assert!(first.redo());
+    assert_eq!(second.get(), 15);
+  }
+}
@@ -0,0 +1,142 @@
+/// The same tiny feature, a hit counter bumped through a shared
+/// immutable handle, implemented with the three interior mutability
+/// tools of the standard library.
+///
+/// a. Cell<T> moves values in and out, so it only fits Copy data and never hands out references.
+/// b. RefCell<T> hands out references and checks the borrowing rules at runtime.
+/// c. Mutex<T> does the same with a lock, which also makes it usable across threads.
+use std::cell::{BorrowMutError, Cell, RefCell};
+use std::rc::Rc;
+use std::sync::Mutex;
+
+fn hit_cell(counter: &Cell<u32>) {
+  counter.set(counter.get() + 1);
+}
+
+fn hit_refcell(counter: &RefCell<u32>) {
+  *counter.borrow_mut() += 1;
+}
+
+fn hit_mutex(counter: &Mutex<u32>) {
+  *counter.lock().unwrap() += 1;
+}
+
+pub fn count_with_cell(hits: u32) -> u32 {
+  let counter = Cell::new(0);
+  for _ in 0..hits {
+    hit_cell(&counter);
+  }
+  counter.get()
+}
+
+pub fn count_with_refcell(hits: u32) -> u32 {
+  let counter = RefCell::new(0);
+  for _ in 0..hits {
+    hit_refcell(&counter);
+  }
+  counter.into_inner()
+}
+
+// single-threaded use: the lock is never contended here
+pub fn count_with_mutex(hits: u32) -> u32 {
+  let counter = Mutex::new(0);
+  for _ in 0..hits {
+    hit_mutex(&counter);
+  }
+  counter.into_inner().unwrap()
+}
+
+/// Increments without panicking when the cell is already mutably borrowed,
+/// returning the new count on success.
+pub fn try_hit(counter: &RefCell<u32>) -> Result<u32, BorrowMutError> {
+  let mut count = counter.try_borrow_mut()?;
+  *count += 1;
+  Ok(*count)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CounterBackend {
+  Cell,
+  RefCell,
+  Mutex
+}
+
+impl CounterBackend {
+  pub const ALL: [CounterBackend; 3] = [
+    CounterBackend::Cell,
+    CounterBackend::RefCell,
+    CounterBackend::Mutex
+  ];
+
+  pub fn count(self, hits: u32) -> u32 {
+    match self {
+      CounterBackend::Cell => count_with_cell(hits),
+      CounterBackend::RefCell => count_with_refcell(hits),
+      CounterBackend::Mutex => count_with_mutex(hits)
+    }
+  }
+}
+
+/// Rc<Cell<T>> gives a Copy value several owners that can all update it,
+/// without the runtime borrow tracking RefCell<T> would add.
+/// Every handler owns its own clone of the Rc.
+pub fn make_handlers(counter: Rc<Cell<u32>>, n: usize) -> Vec<Box<dyn Fn()>> {
+  (0..n)
+    .map(|_| {
+      let counter = Rc::clone(&counter);
+      Box::new(move || counter.set(counter.get() + 1)) as Box<dyn Fn()>
+    })
+    .collect()
+}
+
+pub fn run_all(handlers: &[Box<dyn Fn()>]) {
+  for handler in handlers {
+    handler();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn backends_agree() {
+    for hits in [0, 1, 7, 100] {
+      let counts: Vec<u32> = CounterBackend::ALL
+        .iter()
+        .map(|backend| backend.count(hits))
+        .collect();
+      assert_eq!(counts, vec![hits; 3]);
+    }
+  }
+
+  #[test]
+  fn refcell_double_mutable_borrow_is_refused() {
+    let counter = RefCell::new(0);
+    assert_eq!(try_hit(&counter).unwrap(), 1);
+
+    {
+      let _guard = counter.borrow_mut();
+      assert!(try_hit(&counter).is_err());
+    }
+
+    // the refused hit didn't count, and the cell is usable again
+    assert_eq!(try_hit(&counter).unwrap(), 2);
+  }
+
+  #[test]
+  fn handlers_share_one_counter() {
+    let counter = Rc::new(Cell::new(0));
+    let handlers = make_handlers(Rc::clone(&counter), 3);
+    assert_eq!(Rc::strong_count(&counter), 4);
+
+    run_all(&handlers);
+    run_all(&handlers);
+    handlers[1]();
+    assert_eq!(counter.get(), 7);
+
+    drop(handlers);
+    assert_eq!(Rc::strong_count(&counter), 1);
+    assert_eq!(counter.get(), 7);
+  }
+}
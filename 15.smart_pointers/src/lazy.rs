@@ -0,0 +1,173 @@
+/// A value computed on first access and cached afterwards. `get` only
+/// takes `&self`, so the "not yet computed" to "computed" transition
+/// has to happen through interior mutability: an UnsafeCell<Option<T>>
+/// for the value (so we can hand out a plain `&T`) and Cell<T>s for the
+/// state flag and the pending init closure.
+use std::cell::{Cell, UnsafeCell};
+use std::fmt;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+  Uninit,
+  Initializing,
+  Ready,
+  /// the init closure panicked, and it can't be run again
+  Poisoned
+}
+
+#[derive(Debug, PartialEq)]
+pub enum InitError {
+  /// `get_or_init` was called while the value was still being computed,
+  /// i.e. the init closure tried to read its own MyLazy
+  Reentrant,
+  /// an earlier init panicked, so there is no value and no closure left
+  Poisoned
+}
+
+impl fmt::Display for InitError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      InitError::Reentrant => write!(f, "MyLazy was accessed from inside its own init closure"),
+      InitError::Poisoned => write!(f, "MyLazy's init closure panicked earlier")
+    }
+  }
+}
+
+/// Marks the MyLazy poisoned if the init closure unwinds before the
+/// state has moved on to Ready.
+struct PoisonOnUnwind<'a>(&'a Cell<State>);
+
+impl Drop for PoisonOnUnwind<'_> {
+  fn drop(&mut self) {
+    if self.0.get() == State::Initializing {
+      self.0.set(State::Poisoned);
+    }
+  }
+}
+
+pub struct MyLazy<T> {
+  value: UnsafeCell<Option<T>>,
+  state: Cell<State>,
+  init: Cell<Option<Box<dyn FnOnce() -> T>>>,
+  runs: Option<Rc<Cell<usize>>>
+}
+
+impl<T> MyLazy<T> {
+  pub fn new(init: impl FnOnce() -> T + 'static) -> MyLazy<T> {
+    MyLazy {
+      value: UnsafeCell::new(None),
+      state: Cell::new(State::Uninit),
+      init: Cell::new(Some(Box::new(init))),
+      runs: None
+    }
+  }
+
+  /// Like `new`, but bumps `runs` every time the init closure actually executes.
+  pub fn with_counter(init: impl FnOnce() -> T + 'static, runs: Rc<Cell<usize>>) -> MyLazy<T> {
+    MyLazy {
+      runs: Some(runs),
+      ..MyLazy::new(init)
+    }
+  }
+
+  pub fn is_initialized(&self) -> bool {
+    self.state.get() == State::Ready
+  }
+
+  /// Computes the value the first time, every later call returns the cached one.
+  pub fn get_or_init(&self) -> Result<&T, InitError> {
+    match self.state.get() {
+      State::Ready => {},
+      State::Initializing => return Err(InitError::Reentrant),
+      State::Poisoned => return Err(InitError::Poisoned),
+      State::Uninit => {
+        self.state.set(State::Initializing);
+        let _guard = PoisonOnUnwind(&self.state);
+        let init = self.init.take().expect("init closure is present until the first run");
+        if let Some(runs) = &self.runs {
+          runs.set(runs.get() + 1);
+        }
+        let value = init();
+        // SAFETY: no reference into `value` exists yet: it is only handed
+        // out in the Ready state, and re-entrant calls stop at Initializing.
+        unsafe { *self.value.get() = Some(value) };
+        self.state.set(State::Ready);
+      }
+    }
+
+    // SAFETY: in the Ready state the value is never written again,
+    // so shared references to it stay valid for as long as `&self`.
+    Ok(unsafe { (*self.value.get()).as_ref().unwrap() })
+  }
+
+  /// Panics if called re-entrantly from the init closure, or after the
+  /// init closure panicked.
+  pub fn get(&self) -> &T {
+    match self.get_or_init() {
+      Ok(value) => value,
+      Err(err) => panic!("{}", err)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::rc::Weak;
+
+  #[test]
+  fn init_runs_exactly_once() {
+    let runs = Rc::new(Cell::new(0));
+    let lazy = MyLazy::with_counter(|| vec![1, 2, 3], Rc::clone(&runs));
+    assert!(!lazy.is_initialized());
+    assert_eq!(runs.get(), 0);
+
+    for _ in 0..10 {
+      assert_eq!(lazy.get(), &vec![1, 2, 3]);
+    }
+    assert!(lazy.is_initialized());
+    assert_eq!(runs.get(), 1);
+  }
+
+  #[test]
+  fn get_or_init_is_idempotent() {
+    let runs = Rc::new(Cell::new(0));
+    let lazy = MyLazy::with_counter(|| String::from("ready"), Rc::clone(&runs));
+    let first = lazy.get_or_init().unwrap() as *const String;
+    let second = lazy.get_or_init().unwrap() as *const String;
+    assert_eq!(first, second);
+    assert_eq!(runs.get(), 1);
+  }
+
+  #[test]
+  fn reentrant_get_or_init_is_an_error() {
+    let lazy = Rc::new_cyclic(|me: &Weak<MyLazy<Option<InitError>>>| {
+      let me = me.clone();
+      MyLazy::new(move || me.upgrade().unwrap().get_or_init().err())
+    });
+    assert_eq!(lazy.get(), &Some(InitError::Reentrant));
+  }
+
+  #[test]
+  fn panicking_init_poisons() {
+    let runs = Rc::new(Cell::new(0));
+    let lazy: MyLazy<i32> = MyLazy::with_counter(|| panic!("init failed"), Rc::clone(&runs));
+    let caught = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| lazy.get_or_init().copied()));
+    assert!(caught.is_err());
+    assert_eq!(lazy.get_or_init(), Err(InitError::Poisoned));
+    assert_eq!(lazy.get_or_init(), Err(InitError::Poisoned));
+    assert!(!lazy.is_initialized());
+    assert_eq!(runs.get(), 1);
+  }
+
+  #[test]
+  #[should_panic(expected = "accessed from inside its own init closure")]
+  fn reentrant_get_panics() {
+    let lazy = Rc::new_cyclic(|me: &Weak<MyLazy<i32>>| {
+      let me = me.clone();
+      MyLazy::new(move || *me.upgrade().unwrap().get())
+    });
+    lazy.get();
+  }
+}
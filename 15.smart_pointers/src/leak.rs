@@ -0,0 +1,60 @@
+/// A small harness to prove that a structure is (or isn't) freed.
+/// It keeps a Weak<T> probe to every Rc<T> the builder hands back,
+/// drops the strong handles, and reports the probes that can still be
+/// upgraded: something else kept those values alive, so they leaked.
+use std::rc::{Rc, Weak};
+
+/// Returns the indices of the handed-back values that survived the drop.
+pub fn leak_check<T>(build: impl FnOnce() -> Vec<Rc<T>>) -> Vec<usize> {
+  let handles = build();
+  let probes: Vec<Weak<T>> = handles.iter().map(Rc::downgrade).collect();
+  drop(handles);
+
+  probes
+    .iter()
+    .enumerate()
+    .filter(|(_, probe)| probe.upgrade().is_some())
+    .map(|(index, _)| index)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::reference::cycle_reference::CycList::{Cons, Nil};
+  use std::cell::RefCell;
+
+  #[test]
+  fn freed_values_are_not_reported() {
+    assert!(leak_check(|| vec![Rc::new(1), Rc::new(2)]).is_empty());
+  }
+
+  #[test]
+  fn cyc_list_cycle_leaks() {
+    let leaked = leak_check(|| {
+      let a = Rc::new(Cons(5, RefCell::new(Rc::new(Nil))));
+      let b = Rc::new(Cons(10, RefCell::new(Rc::clone(&a))));
+      if let Some(link) = a.tail() {
+        *link.borrow_mut() = Rc::clone(&b);
+      }
+      vec![a, b]
+    });
+    assert_eq!(leaked, vec![0, 1]);
+  }
+
+  #[test]
+  fn weak_tail_cycle_does_not_leak() {
+    struct Node {
+      tail: RefCell<Weak<Node>>
+    }
+
+    let leaked = leak_check(|| {
+      let a = Rc::new(Node { tail: RefCell::new(Weak::new()) });
+      let b = Rc::new(Node { tail: RefCell::new(Rc::downgrade(&a)) });
+      *a.tail.borrow_mut() = Rc::downgrade(&b);
+      assert!(b.tail.borrow().upgrade().is_some());
+      vec![a, b]
+    });
+    assert!(leaked.is_empty());
+  }
+}
@@ -0,0 +1,7 @@
+// The chapter is a binary, and rustdoc only runs the examples in a
+// library's docs, so the list and the modules whose examples show what
+// can't cross threads live here; main uses them from `smart_pointers::`.
+pub mod bounds;
+pub mod list;
+pub mod mut_list;
+pub mod parallel;
@@ -0,0 +1,58 @@
+/// The immutable Cons list shared through Rc<T>. Its elements can only
+/// be read; MutList is the variant whose cells can change.
+use std::cell::RefCell;
+use std::rc::Rc;
+use crate::mut_list::MutList::{self, MutCons};
+use List::{Cons, Nil};
+
+#[derive(Debug)]
+pub enum List {
+  Cons(i32, Rc<List>), // Box<List>
+  Nil,
+}
+
+impl List {
+  /// Copies the list into a MutList where every element gets a fresh
+  /// Rc<RefCell<i32>>, so separately thawed copies never share cells.
+  pub fn thaw(&self) -> Rc<MutList<i32>> {
+    match self {
+      Cons(value, next) => Rc::new(MutCons(Rc::new(RefCell::new(*value)), next.thaw())),
+      Nil => Rc::new(MutList::Nil)
+    }
+  }
+
+  /// Copies the elements out, front to back.
+  pub fn to_vec(&self) -> Vec<i32> {
+    let mut values = vec![];
+    let mut current = self;
+    while let Cons(value, next) = current {
+      values.push(*value);
+      current = next;
+    }
+    values
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn thawed_copies_do_not_alias() {
+    let list = Cons(1, Rc::new(Cons(2, Rc::new(Nil))));
+    let first = list.thaw();
+    let second = list.thaw();
+    first.set(0, 10).unwrap();
+
+    assert_eq!(first.to_string(), "[10, 2]");
+    assert_eq!(second.to_string(), "[1, 2]");
+    assert_eq!(first.debug_verbose(), "[10 (rc=1), 2 (rc=1)]");
+  }
+
+  #[test]
+  fn to_vec_in_order() {
+    let list = Cons(1, Rc::new(Cons(2, Rc::new(Cons(3, Rc::new(Nil))))));
+    assert_eq!(list.to_vec(), vec![1, 2, 3]);
+    assert_eq!(Nil.to_vec(), vec![]);
+  }
+}
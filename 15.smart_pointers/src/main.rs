@@ -1,4 +1,4 @@
-/// when to us Box<T>
+// when to us Box<T>
 
 // 1. When you have a type whose size can’t be known at 
 // compile time and you want to use a value of that type 
@@ -12,11 +12,17 @@
 // it’s a type that implements a particular trait rather 
 // than being of a specific type
 
-/// At compile time, Rust needs to know how much space a type takes up
+// At compile time, Rust needs to know how much space a type takes up
 // -> recursive type
 
 use std::ops::Deref;
 mod mock;
+use mock::{
+    Clock, DynLimitTracker, FanoutMessenger, Level, LimitTracker, Messenger, MockMessenger, RateLimitedMessenger, SpyMessenger, WriterMessenger,
+};
+mod interior;
+use interior::{try_hit, CounterBackend};
+mod memo;
 
 // https://doc.rust-lang.org/rust-by-example/custom_types/enum/testcase_linked_list.html
 fn deref_use() {
@@ -63,25 +69,27 @@ impl<T> Deref for MyBox<T> {
 #[derive(Debug)]
 struct CustomSmartPointer {
     data: String,
+    // where drops are recorded when pointers cross threads
+    log: Option<drop_threads::DropLog>,
 }
 
 impl Drop for CustomSmartPointer {
     // destructor
     fn drop(&mut self) {
         println!("Dropping with data `{}`!", self.data);
+        if let Some(log) = &self.log {
+            log.record_drop(&self.data);
+        }
     }
 }
 
+mod drop_threads;
+
 // 4. The reference counted smart pointer
 // To enable multiple ownership, Rust has a type called Rc<T>
 // Note that Rc<T> is only for use in single-threaded scenarios
-#[derive(Debug)]
-enum List {
-    Cons(i32, Rc<List>), // Box<List>
-    Nil,
-}
-
-use crate::List::{Cons, Nil};
+use smart_pointers::list;
+use list::List::{Cons, Nil};
 use std::rc::Rc;
 
 // Via immutable references, Rc<T> allows you to share data 
@@ -108,16 +116,39 @@ fn use_rc_create_cons() {
     println!("b = {:?}\n", b);
 }
 
+// Rc<T> also shares unsized data such as str and slices
+mod shared_data;
+
+fn share_str_and_slices() {
+    let words = ["box", "rc", "box", "refcell", "rc"];
+    let interned = shared_data::intern(words.iter().map(|word| word.to_string()).collect());
+    for word in interned.iter() {
+        println!("{} count = {}", word, Rc::strong_count(word));
+    }
+
+    let (first, second) = shared_data::share_slice(vec![5, 10, 15]);
+    println!("same slice = {}, count = {}", Rc::ptr_eq(&first, &second), Rc::strong_count(&first));
+
+    // Rc::make_mut only clones when someone else still holds the data
+    let mut editor: shared_data::SharedConfig = Rc::new(vec![1, 2, 3]);
+    let viewer = Rc::clone(&editor);
+    let cloned = shared_data::update(&mut editor, |values| values.push(4));
+    println!("cloned = {}, editor = {:?}, viewer = {:?}", cloned, editor, viewer);
+    let cloned = shared_data::update(&mut editor, |values| values.push(5));
+    println!("cloned = {}, editor = {:?}", cloned, editor);
+    println!("\n");
+}
+
 // 5. `RefCell<T>` and interior mutability pattern
 /// Interior mutability is a design pattern in Rust 
 /// that allows you to mutate data even when there 
 /// are immutable references to that data
- 
+///
 // About Borrowing rules:
 /// a. At any given time, you can have either (but not both of) 
 /// one mutable reference or any number of immutable references.
 /// b. References must always be valid.
-
+///
 /// With references and Box<T>, the borrowing rules’ invariants 
 /// are enforced at compile time. With RefCell<T>, these invariants 
 /// are enforced at runtime. With references, if you break these 
@@ -126,7 +157,7 @@ fn use_rc_create_cons() {
 /// Similar to Rc<T>, RefCell<T> is only for use in single-threaded 
 /// scenarios and will give you a compile-time error if you try using 
 /// it in a multithreaded context.
-
+///
 /// Here is a recap of the reasons to choose Box<T>, Rc<T>, or RefCell<T>:
 /// a. Rc<T> enables multiple owners of the same data; Box<T> and RefCell<T> have single owners.
 /// b. Box<T> allows immutable or mutable borrows checked at compile time; 
@@ -134,29 +165,174 @@ fn use_rc_create_cons() {
 /// allows immutable or mutable borrows checked at runtime.
 /// c. Because RefCell<T> allows mutable borrows checked at runtime, 
 /// you can mutate the value inside the RefCell<T> even when the RefCell<T> is immutable.
- 
+///
 // Mutating the value inside an immutable value is the interior mutability pattern.
-#[allow(dead_code)] 
 fn interior_mutability() {
     let _x = 5;
     // !! cannot borrow immutable local variable `x` as mutable
     // let y = &mut x; 
 
+    // each backend counts hits through a shared `&` handle
+    for backend in CounterBackend::ALL.iter() {
+        println!("{:?} counted {} hits", backend, backend.count(3));
+    }
+
+    // RefCell<T> refuses a second mutable borrow at runtime
+    let counter = RefCell::new(0);
+    let guard = counter.borrow_mut();
+    println!("hit while borrowed: {:?}", try_hit(&counter).is_ok());
+    drop(guard);
+    println!("hit after release: {:?}", try_hit(&counter));
+
+    // several closures own the same Rc<Cell<u32>>
+    let hits = Rc::new(Cell::new(0));
+    let handlers = interior::make_handlers(Rc::clone(&hits), 3);
+    println!("hits count with handlers = {}", Rc::strong_count(&hits));
+    interior::run_all(&handlers);
+    drop(handlers);
+    println!("hits = {}, count after dropping handlers = {}", hits.get(), Rc::strong_count(&hits));
+    println!("\n");
 }
 
-/// Having multiple owners of mutable data by combining Rc<T> and RefCell<T>
-/// A common way to use RefCell<T> is in combination with Rc<T>. Recall that 
-/// Rc<T> lets you have multiple owners of some data, but it only gives 
-/// immutable access to that data. If you have an Rc<T> that holds a RefCell<T>,
-///  you can get a value that can have multiple owners and that you can mutate!
-#[derive(Debug)]
-enum MutList {
-    MutCons(Rc<RefCell<i32>>, Rc<MutList>),
-    Nil
+// The mock records every message through `&self`
+fn limit_tracking() {
+    let messenger = MockMessenger::new();
+    let mut tracker = LimitTracker::new(&messenger, 100);
+    for value in [50, 80, 95, 120].iter() {
+        tracker.set_value(*value);
+    }
+    println!("sent messages = {:?}", messenger.sent_messages.borrow());
+    println!("last message = {:?}", messenger.last_message());
+    println!("calls mentioning a warning = {}", messenger.messages_containing("warning"));
+    for call in messenger.calls.borrow().iter() {
+        println!("call #{} at value {:?}: {}", call.index, call.value, call.text);
+    }
+
+    // custom thresholds have to be sorted
+    let steps = vec![(0.5, Level::Info), (0.25, Level::Warning)];
+    if let Err(err) = LimitTracker::with_thresholds(&messenger, 100, steps) {
+        println!("rejected thresholds: {}", err);
+    }
+    let custom = MockMessenger::new();
+    let steps = vec![(0.5, Level::Info), (0.75, Level::Urgent), (1.0, Level::Exceeded)];
+    let mut tracker = LimitTracker::with_thresholds(&custom, 100, steps).unwrap();
+    for value in [60, 60, 80].iter() {
+        tracker.set_value(*value);
+    }
+    println!("custom sent messages = {:?}", custom.sent_messages.borrow());
+
+    // `add` only notifies when the level rises, bouncing around 90% stays quiet
+    let bouncing = MockMessenger::new();
+    let mut tracker = LimitTracker::new(&bouncing, 1000);
+    for delta in [901, 1, -1, 1, -100, 100].iter() {
+        tracker.add(*delta);
+    }
+    println!("value = {}, bouncing sent {} messages", tracker.value(), bouncing.sent_messages.borrow().len());
+    tracker.reset();
+    tracker.add(950);
+    println!("after reset = {:?}", bouncing.last_message());
+
+    // the same tracker prints straight to stdout, or to several messengers at once
+    let mut fanout = FanoutMessenger::new();
+    let recorded = Rc::new(MockMessenger::new());
+    fanout.push(Box::new(WriterMessenger::new(std::io::stdout())));
+    fanout.push(Box::new(Rc::clone(&recorded)));
+    LimitTracker::new(&fanout, 100).set_value(100);
+    println!("fanout recorded = {:?}, failures = {:?}", recorded.last_message(), fanout.failures());
+    let buffered = WriterMessenger::new(vec![]);
+    LimitTracker::new(&buffered, 100).set_value(95);
+    println!("buffered = {:?}", String::from_utf8(buffered.into_inner()));
+
+    // at most one message per 10 ticks, the clock is a closure over a Cell
+    let ticks = Cell::new(0);
+    let limited = RateLimitedMessenger::new(MockMessenger::new(), || ticks.get(), 1, 10);
+    let mut tracker = LimitTracker::new(&limited, 100);
+    tracker.set_value(80);
+    tracker.set_value(95);
+    ticks.set(10);
+    tracker.set_value(100);
+    println!(
+        "rate limited delivered = {:?}, dropped = {}, clock at {}",
+        limited.inner().sent_messages.borrow(),
+        limited.dropped_count(),
+        limited.clock().now()
+    );
+
+    // a spy keeps structured notifications and checks them for us
+    let spy = SpyMessenger::new();
+    let mut tracker = LimitTracker::new(&spy, 100);
+    tracker.set_value(50);
+    spy.assert_nothing_sent();
+    tracker.set_value(80);
+    tracker.set_value(100);
+    spy.assert_sent_in_order(&["75%", "over your quota"]);
+    for notification in spy.notifications() {
+        println!("spy saw {}", notification);
+    }
+    println!("\n");
+}
+
+// Static and dynamic dispatch side by side: same steps, same transcript
+fn static_and_dynamic_trackers() {
+    let generic_messenger = MockMessenger::new();
+    let dyn_messenger = MockMessenger::new();
+    let mut generic = LimitTracker::new(&generic_messenger, 100);
+    let mut dynamic = DynLimitTracker::from_generic(&dyn_messenger, 100);
+    for value in [80, 95, 60, 100].iter() {
+        generic.set_value(*value);
+        dynamic.set_value(*value);
+    }
+    generic.reset();
+    dynamic.reset();
+    generic.add(-5);
+    dynamic.add(-5);
+    println!(
+        "generic and dyn transcripts match = {}, values after reset = {} and {}",
+        *generic_messenger.calls.borrow() == *dyn_messenger.calls.borrow(),
+        generic.value(),
+        dynamic.value()
+    );
+
+    // a Box<dyn Messenger> drives the trait-object trackers
+    let inspected = Rc::new(MockMessenger::new());
+    let boxed: Box<dyn Messenger> = Box::new(Rc::clone(&inspected));
+    let steps = vec![(0.5, Level::Info)];
+    DynLimitTracker::with_thresholds(&*boxed, 10, steps).unwrap().set_value(5);
+    DynLimitTracker::new(&*boxed, 10).set_value(9);
+    LimitTracker::new(&*boxed, 10).set_value(10);
+    println!("boxed messenger got = {:?}", inspected.sent_messages.borrow());
+    println!("\n");
+}
+
+// A memo cache only needs `&self` to fill itself up
+fn memoization() {
+    let fib = memo::fib_memo();
+    println!("naive fib(25) = {}", memo::naive_fib(25));
+    println!("memo fib(25) = {}, computations = {}", fib.get(25), fib.computations());
+    println!("memo fib(25) again = {}, computations = {}", fib.get(25), fib.computations());
+    println!("\n");
 }
 
-use crate::MutList::{MutCons, Nil as Null};
-use std::cell::RefCell;
+// A lazy value defers an expensive construction to its first use
+mod lazy;
+
+fn lazy_list() {
+    let runs = Rc::new(Cell::new(0));
+    let list = lazy::MyLazy::with_counter(
+        || (1..=5).rev().fold(Rc::new(Nil), |tail, value| Rc::new(Cons(value, tail))),
+        Rc::clone(&runs),
+    );
+    println!("list built before use = {}, runs = {}", list.is_initialized(), runs.get());
+    println!("list = {:?}", list.get());
+    println!("list = {:?}", list.get());
+    println!("list built after use = {}, runs = {}", list.is_initialized(), runs.get());
+    println!("\n");
+}
+
+/// Having multiple owners of mutable data by combining Rc<T> and RefCell<T>
+use smart_pointers::mut_list;
+use mut_list::MutList::{MutCons, Nil as Null};
+use std::cell::{Cell, RefCell};
 
 fn multi_owners_mutable_data() {
     let value = Rc::new(RefCell::new(5));
@@ -165,43 +341,425 @@ fn multi_owners_mutable_data() {
     let b = MutCons(Rc::new(RefCell::new(6)), Rc::clone(&a));
     let c = MutCons(Rc::new(RefCell::new(7)), Rc::clone(&a));
 
+    let stats = |name, list: &mut_list::MutList<i32>| {
+        println!("{} = {}: sum = {}, max = {:?}, shared cells = {}", name, list, list.sum(), list.max(), list.count_shared());
+    };
+    stats("b before", &b);
+
     // The `borrow_mut` method returns a RefMut<T> smart pointer, 
     // and we use the dereference operator on it and change the inner value.
     *value.borrow_mut() += 10;
 
-    println!("a after = {:?}", a);
-    println!("b after = {:?}", b);
-    println!("c after = {:?}", c);
+    stats("a after", &a);
+    stats("b after", &b);
+    stats("c after", &c);
+    println!("b cells = {}", b.debug_verbose());
+
+    // guarded references fail while a cell is being written
+    {
+        let _writing = value.borrow_mut();
+        println!("b refs while value is borrowed mutably: {:?}", b.try_iter_refs().map(|refs| refs.len()));
+    }
+    println!("b refs afterwards: {:?}", b.try_iter_refs().map(|refs| refs.len()));
+
+    // incrementing every cell of a also shows up in b and c
+    a.increment_all();
+    println!("b after a.increment_all() = {}", b);
+    println!("c after a.increment_all() = {}", c);
+
+    // b's own head cell belongs to b alone
+    println!("b[0] = 60 replaced {:?}", b.set(0, 60));
+    println!("a after b[0] = 60: {}", a);
+    // b[1] is the cell shared with a and c
+    println!("b[1] = 20 replaced {:?}", b.set(1, 20));
+    println!("a after b[1] = 20: {}", a);
+    println!("c[1] = {:?}", c.get(1));
+    if let Err(err) = b.set(5, 0) {
+        println!("b[5] = 0: {}", err);
+    }
+
+    // a transaction applies all of its writes or none of them
+    let failed = b.transaction(|txn| {
+        txn.set(0, 100)?;
+        txn.set(5, 500)
+    });
+    println!("failed transaction: {:?}, b = {}", failed.map_err(|err| err.to_string()), b);
+    let aborted = b.transaction(|txn| {
+        txn.set(1, 0)?;
+        match txn.get(1) {
+            Some(0) => Err(mut_list::TxnError::Aborted(String::from("b[1] must not be zero"))),
+            _ => Ok(()),
+        }
+    });
+    println!("aborted transaction: {:?}, b = {}", aborted.map_err(|err| err.to_string()), b);
+    if let Ok(applied) = b.transaction_with_undo(|txn| {
+        txn.set(0, 100)?;
+        txn.set(1, txn.get(1).unwrap_or(0) * 10)
+    }) {
+        println!("after transaction b = {}, a = {}", b, a);
+        applied.rollback();
+        println!("after rollback b = {}, a = {}", b, a);
+    }
     println!("\n")
 }
 
+// thaw a shared List into mutable cells, freeze a snapshot back out of it
+fn freeze_and_thaw() {
+    let shared = Rc::new(Cons(1, Rc::new(Cons(2, Rc::new(Nil)))));
+    let cells = shared.thaw();
+    cells.increment_all();
+    let snapshot = cells.freeze();
+    cells.increment_all();
+    println!("shared = {:?}", shared);
+    println!("snapshot = {:?}", snapshot);
+    println!("cells = {}", cells);
+    println!("\n");
+}
+
+// The same sharing style, with an undo/redo history behind the Rc<T>
+mod history;
+
+fn shared_history() {
+    let value = Rc::new(history::EditableValue::new(5));
+    let owner_a = Rc::clone(&value);
+    let owner_b = Rc::clone(&value);
+
+    owner_a.set(owner_a.get() + 10);
+    owner_b.set(owner_b.get() * 2);
+    println!("value after both edits = {}", value.get());
+
+    owner_a.undo();
+    println!("value after a undoes = {}", owner_b.get());
+    owner_b.redo();
+    println!("value after b redoes = {}", owner_a.get());
+    println!("\n");
+}
+
 /// 6. Reference cycles can leak memory
 mod reference;
-use reference::cycle_reference::CycList::{ Cons as CycleCons, Nil as CycleNil };
+mod leak;
+use reference::cycle_reference::{
+    build_a, checkpoint, close_cycle, has_cycle, link_b_to_a, run_cycle_demo, run_weak_cycle_demo, shape, to_dot,
+};
 
 fn cycle_reference() {
-    let strong_count = |a| Rc::strong_count(a);
+    // the same steps and labels the reference tests pin down
+    let print = |(label, count): (String, usize)| println!("{} = {}", label, count);
 
-    let a = Rc::new(CycleCons(5, RefCell::new(Rc::new(CycleNil))));
-    println!("a initial rc count = {}", strong_count(&a));
+    let a = build_a();
+    print(checkpoint("a initial rc count", &a));
     println!("a next item: {:?}", a.tail());
 
-    let b = Rc::new(CycleCons(10, RefCell::new(Rc::clone(&a))));
-    println!("a rc count after b creation = {}", strong_count(&a));
-    println!("b initial rc count = {}", strong_count(&b));
+    let b = link_b_to_a(&a);
+    print(checkpoint("a rc count after b creation", &a));
+    print(checkpoint("b initial rc count", &b));
     println!("b next item = {:?}", b.tail());
 
+    println!("cycle before changing a = {}", has_cycle(&a));
+    println!("shape before: {}", shape(&b));
+    println!("{}", to_dot(&[("a", &a), ("b", &b)]));
+
+    // the checked setter refuses, the unchecked one reaches inside like before
+    if let Err(err) = a.set_tail(Rc::clone(&b)) {
+        println!("set_tail refused: {}", err);
+    }
+    close_cycle(&a, &b);
+
+    println!("cycle after changing a = {}", has_cycle(&a));
+    println!("shape after: {}", shape(&a));
+    println!("{}", to_dot(&[("a", &a), ("b", &b)]));
+    print(checkpoint("b rc count after changing a", &b));
+    print(checkpoint("a rc count after changing a", &a));
+
+    // with the derived Debug this overflowed the stack:
+    // !!thread 'main' has overflowed its stack
+    // CycList's Debug now stops where the cycle closes
+    println!("a next item = {:?}", a.tail());
+    let mut values = a.iter();
+    let collected: Vec<i32> = values.by_ref().collect();
+    println!("a values = {:?}, stopped on cycle = {}", collected, values.stopped_on_cycle());
+    println!("b sum = {}", b.iter().sum::<i32>());
+    println!("a first 5 links = {:?}", a.take_limited(5));
+
+    // rerun the same steps with probes: both nodes survive dropping our handles
+    println!("report = {:?}", run_cycle_demo());
+    println!("\n");
+}
+
+// the same a/b shape with Weak<T> tails: same checkpoints, different counts
+use reference::cycle_reference::WeakCycList;
+use std::rc::Weak;
+
+fn weak_cycle_reference() {
+    let counts = |a: &Rc<WeakCycList>| (Rc::strong_count(a), Rc::weak_count(a));
+
+    let a = Rc::new(WeakCycList::Cons(5, RefCell::new(Weak::new())));
+    println!("a initial rc count (strong, weak) = {:?}", counts(&a));
+
+    let b = Rc::new(WeakCycList::Cons(10, RefCell::new(Rc::downgrade(&a))));
+    println!("a rc count after b creation = {:?}", counts(&a));
+    println!("b initial rc count = {:?}", counts(&b));
+    println!("b next item = {:?}", b.tail().map(|link| link.borrow().upgrade()));
+
     if let Some(link) = a.tail() {
-        *link.borrow_mut() = Rc::clone(&b);
+        *link.borrow_mut() = Rc::downgrade(&b);
     }
 
-    println!("b rc count after changing a = {}", strong_count(&b));
-    println!("a rc count after changing a = {}", strong_count(&a));
+    println!("b rc count after changing a = {:?}", counts(&b));
+    println!("a rc count after changing a = {:?}", counts(&a));
+
+    let (probe_a, probe_b) = (Rc::downgrade(&a), Rc::downgrade(&b));
+    drop(a);
+    drop(b);
+    println!("a freed = {}, b freed = {}", probe_a.upgrade().is_none(), probe_b.upgrade().is_none());
+    println!("report = {:?}", run_weak_cycle_demo());
     println!("\n");
+}
 
-    // trigger panic due to cycle reference
-    // !!thread 'main' has overflowed its stack
-    // println!("a next item = {:?}", a.tail());
+/// 7. Weak<T> doesn't keep values alive
+mod observer;
+use observer::{RecordingObserver, Subject};
+
+fn weak_observers() {
+    let subject = Subject::new();
+    let logger = Rc::new(RefCell::new(RecordingObserver::new()));
+    let auditor = Rc::new(RefCell::new(RecordingObserver::new()));
+    subject.subscribe(&logger);
+    subject.subscribe(&auditor);
+
+    subject.notify(&"login");
+    println!("observers after subscribing = {}", subject.observer_count());
+
+    // no unsubscribe: dropping the only Rc is enough
+    drop(auditor);
+    subject.notify(&"logout");
+    println!("observers after dropping auditor = {}", subject.observer_count());
+    println!("logger events = {:?}", logger.borrow().events());
+    println!("\n");
+}
+
+// a cache of Weak<T> handles never keeps its values alive
+mod weak_cache;
+
+fn weak_caching() {
+    let cache = weak_cache::WeakCache::new();
+    let config = cache.get_or_insert("config", || String::from("loaded config"));
+    let again = cache.get_or_insert("config", || String::from("reloaded config"));
+    println!("same value = {}, count = {}", Rc::ptr_eq(&config, &again), Rc::strong_count(&config));
+
+    drop(config);
+    drop(again);
+    println!("live entries after drop = {}", cache.live_len());
+    cache.purge();
+    println!("entries after purge = {}, empty = {}", cache.len(), cache.is_empty());
+    println!("\n");
+}
+
+// children are owned through Rc<T>, the parent is only referenced by Weak<T>
+mod tree;
+use tree::{add_child, Node};
+
+fn tree_counts() {
+    let counts = |node: &Rc<Node>| (Rc::strong_count(node), Rc::weak_count(node));
+
+    let leaf = Node::new(3);
+    println!("leaf parent = {:?}", leaf.parent().map(|parent| parent.value));
+    println!("leaf strong, weak = {:?}", counts(&leaf));
+
+    {
+        let branch = Node::new(5);
+        add_child(&branch, Rc::clone(&leaf));
+        println!("leaf parent = {:?}", leaf.parent().map(|parent| parent.value));
+        println!("branch strong, weak = {:?}", counts(&branch));
+        println!("leaf strong, weak = {:?}", counts(&leaf));
+        println!("branch values = {:?}, leaf depth = {}", branch.values(), leaf.depth());
+        println!("leaf root = {}", leaf.root().value);
+    }
+
+    // the branch subtree is gone, only our own handle keeps the leaf
+    println!("leaf parent = {:?}", leaf.parent().map(|parent| parent.value));
+    println!("leaf strong, weak = {:?}", counts(&leaf));
+
+    // the same walkthrough, recorded by the reference module
+    for (label, strong, weak, parent) in reference::parent_child::leaf_branch_checkpoints() {
+        println!("{} strong = {}, weak = {}, parent = {:?}", label, strong, weak, parent);
+    }
+
+    // detaching clears both directions
+    let branch = Node::new(5);
+    reference::parent_child::attach(&branch, Rc::clone(&leaf));
+    reference::parent_child::detach(&leaf);
+    println!("after detach: leaf parent = {:?}, branch values = {:?}", leaf.parent().map(|parent| parent.value), branch.values());
+    println!("leaf strong, weak = {:?}", counts(&leaf));
+    println!("\n");
+}
+
+// the cycle lesson generalized: Weak<T> edges between Rc<T> nodes
+mod graph;
+
+fn weak_graph() {
+    let leaked = leak::leak_check(|| {
+        let mut graph = graph::Graph::new();
+        let a = graph.add_node(5);
+        let b = graph.add_node(10);
+        let c = graph.add_node(15);
+        graph.add_edge(a, b);
+        graph.add_edge(b, a);
+        graph.add_edge(c, a);
+        let values: Vec<i32> = graph.neighbors(c).iter().map(|&id| graph.node(id).borrow().value).collect();
+        println!("c neighbor values = {:?}", values);
+        println!("a reaches b = {}, a reaches c = {}", graph.reachable(a, b), graph.reachable(a, c));
+        vec![graph.node(a), graph.node(b), graph.node(c)]
+    });
+    println!("leaked nodes after dropping the graph = {:?}", leaked);
+    println!("\n");
+}
+
+/// 8. Arc<T> and Mutex<T>: the thread-safe Rc<T> and RefCell<T>
+mod concurrent;
+use concurrent::SharedList::{SharedCons, Nil as SharedNil};
+use std::sync::{Arc, Mutex};
+
+fn shared_list_across_threads() {
+    let value = Arc::new(Mutex::new(5));
+    let a = Arc::new(SharedCons(Arc::clone(&value), Arc::new(SharedNil)));
+    let b = SharedCons(Arc::new(Mutex::new(3)), Arc::clone(&a));
+    let c = SharedCons(Arc::new(Mutex::new(4)), Arc::clone(&a));
+    println!("a arc count = {}, value arc count = {}", Arc::strong_count(&a), Arc::strong_count(&value));
+
+    // four threads each add 10 to the head cell of a
+    concurrent::add_from_threads(&value, 4, 10);
+
+    println!("a after = {:?}, sum = {}", a.values(), a.sum());
+    println!("b after = {:?}, sum = {}", b.values(), b.sum());
+    println!("c after = {:?}, sum = {}", c.values(), c.sum());
+    println!("\n");
+}
+
+// Send and Sync decide which of these may cross threads
+mod markers;
+
+fn send_and_sync() {
+    for (name, send, sync) in markers::marker_table() {
+        println!("{:<20} Send = {:<5} Sync = {}", name, send, sync);
+    }
+    let counter = Arc::new(Mutex::new(0));
+    markers::require_send(&counter);
+    markers::require_sync(&counter);
+    // !! `Rc<List>` cannot be sent between threads safely
+    // markers::require_send(&Rc::new(Nil));
+    println!("\n");
+}
+
+// a thread pool: workers share one receiver through Arc<Mutex<Receiver>>
+mod pool;
+
+fn thread_pool() {
+    let counter = Arc::new(Mutex::new(0u64));
+    let worker_ids = Arc::new(Mutex::new(vec![]));
+    match pool::ThreadPool::new(0) {
+        Ok(_) => println!("a pool without threads?"),
+        Err(err) => println!("pool of 0: {}", err)
+    }
+
+    let pool = pool::ThreadPool::new(4).unwrap();
+    for _ in 0..100 {
+        let counter = Arc::clone(&counter);
+        let worker_ids = Arc::clone(&worker_ids);
+        pool.execute(move || {
+            *counter.lock().unwrap() += 1;
+            worker_ids.lock().unwrap().push(pool::current_worker_id().unwrap());
+        });
+    }
+    let size = pool.size();
+    // dropping the pool waits for all 100 jobs; jobs this small often
+    // finish before a second worker wakes up
+    drop(pool);
+
+    let mut ids = worker_ids.lock().unwrap().clone();
+    ids.sort();
+    ids.dedup();
+    println!("counter = {}, workers used = {:?} of {}", counter.lock().unwrap(), ids, size);
+    println!("\n");
+}
+
+// a Mutex<u64> and an AtomicU64 behind the same Counter trait
+mod counters;
+
+fn mutex_vs_atomic() {
+    let result = counters::bench_counters(4, 100_000);
+    println!("{:<8} {:>8} {:>12}", "counter", "total", "elapsed");
+    println!("{:<8} {:>8} {:>12?}", "Mutex", result.mutex_total, result.mutex_elapsed);
+    println!("{:<8} {:>8} {:>12?}", "Atomic", result.atomic_total, result.atomic_elapsed);
+
+    let atomic: Arc<dyn counters::Counter> = Arc::new(counters::AtomicCounter::default());
+    println!("thread pool with an atomic counter = {}", counters::count_with_pool(atomic, 4, 100));
+    println!("\n");
+}
+
+// scoped threads borrow a snapshot of the Rc list, never the list itself
+use smart_pointers::parallel;
+
+fn scoped_parallel_sum() {
+    let list = Rc::new(Cons(5, Rc::new(Cons(10, Rc::new(Cons(15, Rc::new(Nil)))))));
+    // !! `Rc<List>` cannot be shared between threads safely
+    // std::thread::scope(|s| { s.spawn(|| list.to_vec()); });
+    println!("list snapshot = {:?}, parallel sum = {}", list.to_vec(), parallel::list_parallel_sum(&list));
+    let values: Vec<i32> = (1..=1000).collect();
+    println!("1..=1000 in 8 chunks = {}", parallel::parallel_sum(&values, 8));
+    println!("\n");
+}
+
+// the Weak<T> tree again, with Arc<T> and RwLock<T> so threads can insert
+mod sync_tree;
+
+fn concurrent_tree() {
+    let root = sync_tree::SyncNode::new(0);
+    sync_tree::parallel_populate(&root, (1..=20).collect(), 4);
+    let first = root.children.read().unwrap()[0].clone();
+    println!("root children = {}, values sum = {}", root.child_count(), root.values().iter().sum::<i32>());
+    println!("first child {} has parent {:?}", first.value, first.parent().map(|parent| parent.value));
+
+    let leaf = sync_tree::SyncNode::new(99);
+    sync_tree::add_child(&first, Arc::clone(&leaf));
+    println!("leaf parent = {:?}, weak count of first = {}", leaf.parent().map(|parent| parent.value), Arc::weak_count(&first));
+    println!("\n");
+}
+
+// lock ordering: the same two accounts locked in opposite orders can deadlock
+mod deadlock;
+
+fn deadlock_and_remedies() {
+    let a = deadlock::account(1, 500);
+    let b = deadlock::account(2, 500);
+    // !! with deadlock::transfer this can hang forever, each thread holding one lock
+    // deadlock::opposing_transfers(&a, &b, 1000, deadlock::transfer);
+    deadlock::opposing_transfers(&a, &b, 1000, deadlock::transfer_safe);
+    deadlock::transfer(&a, &b, 100);
+    println!("after opposing transfers: a = {:?}, b = {:?}", a.lock().unwrap(), b.lock().unwrap());
+
+    let held = b.lock().unwrap();
+    println!("transfer_try while b is locked = {:?}", deadlock::transfer_try(&a, &b, 10));
+    drop(held);
+    println!("transfer_try after release = {:?}", deadlock::transfer_try(&a, &b, 10));
+    println!("\n");
+}
+
+// Drop runs on whichever thread owns the value last
+fn drops_across_threads() {
+    let report = drop_threads::drops_across_threads();
+    for event in report.log.events() {
+        println!("{}", event);
+    }
+    println!(
+        "`returned` dropped by the caller = {}",
+        report.log.dropper_of("returned") == Some(report.caller)
+    );
+    println!(
+        "threads: caller {:?}, dropping {:?}, returning {:?}",
+        report.caller, report.dropping_thread, report.returning_thread
+    );
+    println!("\n");
 }
 
 fn main() {
@@ -209,8 +767,8 @@ fn main() {
     deref_use();
 
     // 3. drop trait
-    let a = CustomSmartPointer{ data: String::from("my stuff") };
-    let b = CustomSmartPointer{ data: String::from("other stuff") };
+    let a = CustomSmartPointer{ data: String::from("my stuff"), log: None };
+    let b = CustomSmartPointer{ data: String::from("other stuff"), log: None };
 
     // the ownership system that makes sure references are always 
     // valid also ensures that drop gets called only once when the
@@ -222,12 +780,44 @@ fn main() {
     // 4. Rc<T>
     println!("## Rc<T> multiple reference");
     use_rc_create_cons();
+    share_str_and_slices();
+
+    // 5. interior mutability
+    println!("## interior mutability");
+    interior_mutability();
+    limit_tracking();
+    static_and_dynamic_trackers();
+    memoization();
+    lazy_list();
 
-    // 5. RefCell<T> and Rc<T>
+    // RefCell<T> and Rc<T>
     println!("## multi owners mutable data");
     multi_owners_mutable_data();
+    freeze_and_thaw();
+    shared_history();
 
     // 6. Cycle reference
     println!("## cycle reference");
     cycle_reference();
+    weak_cycle_reference();
+
+    // 7. Weak<T>
+    println!("## weak observers");
+    weak_observers();
+    weak_caching();
+    println!("## tree");
+    tree_counts();
+    println!("## graph");
+    weak_graph();
+
+    // 8. Arc<T> and Mutex<T>
+    println!("## threads");
+    shared_list_across_threads();
+    send_and_sync();
+    thread_pool();
+    mutex_vs_atomic();
+    scoped_parallel_sum();
+    concurrent_tree();
+    deadlock_and_remedies();
+    drops_across_threads();
 }
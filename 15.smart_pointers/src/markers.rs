@@ -0,0 +1,146 @@
+/// Send and Sync are the marker traits behind "Rc<T> is only for use in
+/// single-threaded scenarios". A type is Send when its values can move to
+/// another thread, and Sync when `&T` can be shared between threads.
+/// The compiler implements both automatically from a type's fields, so
+/// Rc<T> (a non-atomic count) and RefCell<T> (a non-atomic borrow flag)
+/// opt out, while Arc<T> and Mutex<T> opt back in.
+///
+/// `smart_pointers::bounds` shows the compiler refusing an Rc<List> and
+/// a RefCell<i32> in its doc examples; this module asks the same question
+/// at run time, to print a table.
+use std::marker::PhantomData;
+
+pub use smart_pointers::bounds::{require_send, require_sync};
+
+// Autoref specialization: for a concrete T, method lookup tries the impl
+// on `Probe<T>` first and only falls back to the one on `&Probe<T>` when
+// the `T: Send` (or `T: Sync`) bound doesn't hold.
+pub struct Probe<T: ?Sized>(PhantomData<T>);
+
+impl<T: ?Sized> Probe<T> {
+  pub fn new() -> Probe<T> {
+    Probe(PhantomData)
+  }
+}
+
+pub trait IsSend {
+  fn is_send(&self) -> bool {
+    true
+  }
+}
+
+impl<T: ?Sized + Send> IsSend for Probe<T> {}
+
+pub trait NotSend {
+  fn is_send(&self) -> bool {
+    false
+  }
+}
+
+impl<T: ?Sized> NotSend for &Probe<T> {}
+
+pub trait IsSync {
+  fn is_sync(&self) -> bool {
+    true
+  }
+}
+
+impl<T: ?Sized + Sync> IsSync for Probe<T> {}
+
+pub trait NotSync {
+  fn is_sync(&self) -> bool {
+    false
+  }
+}
+
+impl<T: ?Sized> NotSync for &Probe<T> {}
+
+/// `(name, send, sync)` for a concrete type. Only works on concrete
+/// types: inside a generic function the bound is unknown and the
+/// fallback always wins.
+macro_rules! marker_row {
+  ($t:ty) => {
+    (stringify!($t), (&Probe::<$t>::new()).is_send(), (&Probe::<$t>::new()).is_sync())
+  };
+}
+
+pub type MarkerRow = (&'static str, bool, bool);
+
+/// The chapter's smart pointers and which markers they carry.
+pub fn marker_table() -> Vec<MarkerRow> {
+  use crate::MyBox;
+  use smart_pointers::list::List;
+  use std::cell::RefCell;
+  use std::rc::Rc;
+  use std::sync::{Arc, Mutex};
+
+  vec![
+    marker_row!(Rc<List>),
+    marker_row!(Arc<Mutex<i32>>),
+    marker_row!(Arc<RefCell<i32>>),
+    marker_row!(RefCell<i32>),
+    marker_row!(Mutex<i32>),
+    marker_row!(MyBox<i32>),
+    marker_row!(MyBox<Rc<i32>>)
+  ]
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::cell::RefCell;
+  use std::rc::Rc;
+  use std::sync::{Arc, Mutex};
+  use std::thread;
+
+  fn row(name: &str) -> MarkerRow {
+    *marker_table().iter().find(|row| row.0 == name).unwrap()
+  }
+
+  #[test]
+  fn single_threaded_pointers_are_neither() {
+    assert_eq!(row("Rc<List>"), ("Rc<List>", false, false));
+    assert_eq!(row("RefCell<i32>"), ("RefCell<i32>", true, false));
+    assert_eq!(row("Arc<RefCell<i32>>"), ("Arc<RefCell<i32>>", false, false));
+  }
+
+  #[test]
+  fn arc_mutex_crosses_threads() {
+    assert_eq!(row("Arc<Mutex<i32>>"), ("Arc<Mutex<i32>>", true, true));
+    let counter = Arc::new(Mutex::new(0));
+    require_send(&counter);
+    require_sync(&counter);
+
+    let moved = Arc::clone(&counter);
+    thread::spawn(move || *moved.lock().unwrap() += 1).join().unwrap();
+    assert_eq!(*counter.lock().unwrap(), 1);
+  }
+
+  #[test]
+  fn mutex_moves_into_a_thread() {
+    assert_eq!(row("Mutex<i32>"), ("Mutex<i32>", true, true));
+    let mutex = Mutex::new(41);
+    let back = thread::spawn(move || {
+      *mutex.lock().unwrap() += 1;
+      mutex
+    })
+    .join()
+    .unwrap();
+    assert_eq!(back.into_inner().unwrap(), 42);
+  }
+
+  #[test]
+  fn my_box_follows_its_contents() {
+    assert_eq!(row("MyBox<i32>"), ("MyBox<i32>", true, true));
+    assert_eq!(row("MyBox<Rc<i32>>"), ("MyBox<Rc<i32>>", false, false));
+  }
+
+  #[test]
+  fn probes_agree_with_the_bounds() {
+    // these would fail to compile if the rows above were wrong the other way
+    require_send(&RefCell::new(0));
+    require_sync(&Mutex::new(0));
+    let (send, sync) = ((&Probe::<Rc<i32>>::new()).is_send(), (&Probe::<Rc<i32>>::new()).is_sync());
+    assert!(!send && !sync);
+  }
+}
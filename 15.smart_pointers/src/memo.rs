@@ -0,0 +1,105 @@
+/// A memoizing cache: `get` only takes `&self`, yet it fills the cache
+/// behind the scenes. The HashMap lives inside a RefCell<T> and the
+/// computation counter inside a Cell<T>, so callers can share one Memo
+/// through plain immutable references.
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+type Compute<K, V> = Box<dyn Fn(&Memo<K, V>, K) -> V>;
+
+pub struct Memo<K, V> {
+  cache: RefCell<HashMap<K, V>>,
+  compute: Compute<K, V>,
+  computations: Cell<usize>
+}
+
+impl<K, V> Memo<K, V> where
+  K: Eq + Hash + Clone,
+  V: Clone
+{
+  /// The closure receives the memo itself so that recursive
+  /// definitions (like fibonacci) can call back into `get`.
+  pub fn new(compute: impl Fn(&Memo<K, V>, K) -> V + 'static) -> Memo<K, V> {
+    Memo {
+      cache: RefCell::new(HashMap::new()),
+      compute: Box::new(compute),
+      computations: Cell::new(0)
+    }
+  }
+
+  /// Returns the cached value for `key`, computing and storing it first if needed.
+  ///
+  /// Borrow scoping matters here: the lookup's `Ref` must be gone before
+  /// `compute` runs, because a recursive compute calls `get` again and
+  /// eventually `borrow_mut`s the cache. Writing the lookup as
+  /// `if let Some(v) = self.cache.borrow().get(&key)` would keep the `Ref`
+  /// alive for the whole `if let` and make the nested `borrow_mut` panic
+  /// with `BorrowMutError` (RefCell<T> panics where a lock would deadlock).
+  pub fn get(&self, key: K) -> V {
+    let cached = self.cache.borrow().get(&key).cloned();
+    if let Some(value) = cached {
+      return value;
+    }
+
+    self.computations.set(self.computations.get() + 1);
+    let value = (self.compute)(self, key.clone());
+    // the RefMut only lives for this statement
+    self.cache.borrow_mut().insert(key, value.clone());
+    value
+  }
+
+  /// How many times the computation actually ran.
+  pub fn computations(&self) -> usize {
+    self.computations.get()
+  }
+}
+
+/// The naive, exponential fibonacci the memo is meant to rescue.
+pub fn naive_fib(n: u64) -> u64 {
+  if n < 2 { n } else { naive_fib(n - 1) + naive_fib(n - 2) }
+}
+
+/// Fibonacci whose recursive calls go back through the memo.
+pub fn fib_memo() -> Memo<u64, u64> {
+  Memo::new(|memo, n| {
+    if n < 2 { n } else { memo.get(n - 1) + memo.get(n - 2) }
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn computes_once_per_distinct_key() {
+    let squares = Memo::new(|_, n: u32| n * n);
+    for key in [3, 4, 3, 5, 4, 3] {
+      squares.get(key);
+    }
+    assert_eq!(squares.computations(), 3);
+  }
+
+  #[test]
+  fn repeated_queries_hit_the_cache() {
+    let squares = Memo::new(|_, n: u32| n * n);
+    assert_eq!(squares.get(9), 81);
+    assert_eq!(squares.computations(), 1);
+
+    for _ in 0..10 {
+      assert_eq!(squares.get(9), 81);
+    }
+    assert_eq!(squares.computations(), 1);
+  }
+
+  #[test]
+  fn recursive_use_does_not_hold_the_borrow() {
+    let fib = fib_memo();
+    assert_eq!(fib.get(50), 12_586_269_025);
+    // keys 0..=50, each computed exactly once
+    assert_eq!(fib.computations(), 51);
+
+    assert_eq!(fib.get(20), naive_fib(20));
+    assert_eq!(fib.computations(), 51);
+  }
+}
@@ -1,39 +1,592 @@
-/// A test double is the general programming concept for a type 
-/// used in place of another type during testing. Mock objects 
-/// are specific types of test doubles that record what happens 
+/// A test double is the general programming concept for a type
+/// used in place of another type during testing. Mock objects
+/// are specific types of test doubles that record what happens
 /// during a test so you can assert that the correct actions took place.
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::io::{self, Write};
+use std::rc::Rc;
 
-#[allow(dead_code)]
-pub trait Messager {
+pub trait Messenger {
   fn send(&self, msg: &str);
+
+  /// Like `send`, with the value that triggered the message. Messengers
+  /// that don't care about the value get this forwarded to `send`.
+  fn send_with_value(&self, msg: &str, _value: usize) {
+    self.send(msg);
+  }
+
+  /// Like `send`, reporting whether delivery worked. Messengers that can't
+  /// fail don't need to override it.
+  fn try_send(&self, msg: &str) -> io::Result<()> {
+    self.send(msg);
+    Ok(())
+  }
+
+  /// `send_with_value` reporting whether delivery worked, the way
+  /// `try_send` does for `send`.
+  fn try_send_with_value(&self, msg: &str, value: usize) -> io::Result<()> {
+    self.send_with_value(msg, value);
+    Ok(())
+  }
+}
+
+/// A shared messenger is still a messenger, so one can sit inside a
+/// FanoutMessenger while the caller keeps a handle to inspect it.
+impl<M: Messenger + ?Sized> Messenger for Rc<M> {
+  fn send(&self, msg: &str) {
+    (**self).send(msg);
+  }
+
+  fn send_with_value(&self, msg: &str, value: usize) {
+    (**self).send_with_value(msg, value);
+  }
+
+  fn try_send(&self, msg: &str) -> io::Result<()> {
+    (**self).try_send(msg)
+  }
+
+  fn try_send_with_value(&self, msg: &str, value: usize) -> io::Result<()> {
+    (**self).try_send_with_value(msg, value)
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+  Info,
+  Warning,
+  Urgent,
+  Exceeded
+}
+
+impl Level {
+  // `fraction` is the threshold that was crossed, e.g. 0.75. Most
+  // fractions aren't exact in binary, 0.57 * 100.0 is 56.99999999999999,
+  // so the percent is rounded to a whole number
+  fn message(self, fraction: f64) -> String {
+    let percent = (fraction * 100.0).round();
+    match self {
+      Level::Info => format!("Info: You've used up over {}% of your quota!", percent),
+      Level::Warning => format!("Warning: You've used up over {}% of your quota!", percent),
+      Level::Urgent => format!("Urgent warning: You've used up over {}% of your quota!", percent),
+      Level::Exceeded => String::from("Error: You are over your quota!")
+    }
+  }
 }
 
-pub struct LimitTracker<'a, T: 'a + Messager> {
-  messager: &'a T,
+impl Level {
+  /// Reads the level back out of a message `LimitTracker` sent.
+  pub fn from_message(text: &str) -> Option<Level> {
+    if text.starts_with("Info:") {
+      Some(Level::Info)
+    } else if text.starts_with("Warning:") {
+      Some(Level::Warning)
+    } else if text.starts_with("Urgent warning:") {
+      Some(Level::Urgent)
+    } else if text.starts_with("Error:") {
+      Some(Level::Exceeded)
+    } else {
+      None
+    }
+  }
+}
+
+/// Why a threshold list was rejected.
+#[derive(Debug, PartialEq)]
+pub enum ThresholdError {
+  /// each fraction has to be larger than the one before it
+  Unsorted,
+  /// fractions have to be finite and above zero
+  OutOfRange(f64)
+}
+
+impl fmt::Display for ThresholdError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ThresholdError::Unsorted => write!(f, "thresholds must be in ascending order"),
+      ThresholdError::OutOfRange(fraction) => write!(f, "threshold {} is not above zero", fraction)
+    }
+  }
+}
+
+/// Fractions of `max` in ascending order, each with the level it warns at.
+/// Fractions above 1.0 are allowed, to warn again well past the quota.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Thresholds {
+  steps: Vec<(f64, Level)>
+}
+
+impl Thresholds {
+  pub fn new(steps: Vec<(f64, Level)>) -> Result<Thresholds, ThresholdError> {
+    for (i, &(fraction, _)) in steps.iter().enumerate() {
+      if !fraction.is_finite() || fraction <= 0.0 {
+        return Err(ThresholdError::OutOfRange(fraction));
+      }
+      if i > 0 && steps[i - 1].0 >= fraction {
+        return Err(ThresholdError::Unsorted);
+      }
+    }
+    Ok(Thresholds { steps })
+  }
+
+  /// The highest threshold `fraction` has reached, if any.
+  pub fn crossed(&self, fraction: f64) -> Option<(f64, Level)> {
+    self.steps.iter().rev().find(|(step, _)| fraction >= *step).copied()
+  }
+}
+
+/// The Book's 75%, 90% and 100% warnings.
+impl Default for Thresholds {
+  fn default() -> Thresholds {
+    Thresholds {
+      steps: vec![(0.75, Level::Warning), (0.9, Level::Urgent), (1.0, Level::Exceeded)]
+    }
+  }
+}
+
+// The threshold bookkeeping both trackers share: each method sends the
+// message, if any, through the messenger of the tracker calling it.
+struct Quota {
   value: usize,
-  max: usize
+  max: usize,
+  thresholds: Thresholds,
+  // the level of the newest value, so `add` only speaks up when it rises
+  last_level: Option<Level>
+}
+
+impl Quota {
+  fn new(max: usize, thresholds: Thresholds) -> Quota {
+    Quota { value: 0, max, thresholds, last_level: None }
+  }
+
+  fn set_value<M: Messenger + ?Sized>(&mut self, messenger: &M, value: usize) {
+    if value == self.value {
+      return;
+    }
+    if let Some((fraction, level)) = self.update(value) {
+      messenger.send_with_value(&level.message(fraction), value);
+    }
+  }
+
+  fn add<M: Messenger + ?Sized>(&mut self, messenger: &M, delta: i64) {
+    let value = (self.value as i64).saturating_add(delta).max(0) as usize;
+    let previous = self.last_level;
+    match self.update(value) {
+      Some((fraction, level)) if Some(level) > previous => messenger.send_with_value(&level.message(fraction), value),
+      _ => {}
+    }
+  }
+
+  fn reset(&mut self) {
+    self.value = 0;
+    self.last_level = None;
+  }
+
+  // stores the value and its level, returning the threshold it reached
+  fn update(&mut self, value: usize) -> Option<(f64, Level)> {
+    self.value = value;
+    let percentage_of_max = self.value as f64 / self.max as f64;
+    let crossed = self.thresholds.crossed(percentage_of_max);
+    self.last_level = crossed.map(|(_, level)| level);
+    crossed
+  }
+}
+
+/// Static dispatch: the messenger type is part of the tracker's type and
+/// every `send` call is resolved at compile time. `T` may also be
+/// `dyn Messenger`, so `new` takes DynLimitTracker's messengers too.
+pub struct LimitTracker<'a, T: 'a + Messenger + ?Sized> {
+  messenger: &'a T,
+  quota: Quota
 }
 
 impl <'a, T> LimitTracker<'a, T> where
-  T: Messager
+  T: Messenger + ?Sized
 {
-  pub fn new(messager: &T, max: usize) -> LimitTracker<T> {
+  pub fn new(messenger: &T, max: usize) -> LimitTracker<'_, T> {
     LimitTracker {
-      messager,
-      value: 0,
-      max
+      messenger,
+      quota: Quota::new(max, Thresholds::default())
     }
   }
 
+  pub fn with_thresholds(
+    messenger: &T,
+    max: usize,
+    thresholds: Vec<(f64, Level)>
+  ) -> Result<LimitTracker<'_, T>, ThresholdError> {
+    Ok(LimitTracker {
+      messenger,
+      quota: Quota::new(max, Thresholds::new(thresholds)?)
+    })
+  }
+
+  /// Sends one message for the highest threshold the new value reached.
+  /// Setting the value it already has sends nothing.
   pub fn set_value(&mut self, value: usize) {
-    self.value = value;
-    let percentrage_of_max = self.value as f64 / self.max as f64;
-    if percentrage_of_max > 0.75 && percentrage_of_max < 0.9 {
-      self.messager.send("Warning: You've used up over 75% of your quota!");
-    } else if percentrage_of_max >= 0.9 && percentrage_of_max < 1.0 {
-      self.messager.send("Urgent warning: You've used up over 90% of your quota!");
-    } else if percentrage_of_max >= 1.0 {
-      self.messager.send("Error: You are over your quota!");
+    self.quota.set_value(self.messenger, value);
+  }
+
+  /// Moves the value up or down, never below zero. With hysteresis:
+  /// a message goes out only when the value climbs into a higher level
+  /// than the previous value was in, so wobbling inside one level is quiet
+  /// while leaving a level and coming back notifies again.
+  pub fn add(&mut self, delta: i64) {
+    self.quota.add(self.messenger, delta);
+  }
+
+  /// Back to zero, forgetting which levels were already reported.
+  pub fn reset(&mut self) {
+    self.quota.reset();
+  }
+
+  pub fn value(&self) -> usize {
+    self.quota.value
+  }
+}
+
+/// Dynamic dispatch: any messenger behind a `&dyn Messenger`, looked up
+/// through the vtable on every call. Behaves exactly like LimitTracker.
+pub struct DynLimitTracker<'a> {
+  messenger: &'a dyn Messenger,
+  quota: Quota
+}
+
+impl<'a> DynLimitTracker<'a> {
+  pub fn new(messenger: &'a dyn Messenger, max: usize) -> DynLimitTracker<'a> {
+    DynLimitTracker {
+      messenger,
+      quota: Quota::new(max, Thresholds::default())
+    }
+  }
+
+  /// Takes the same `&T` LimitTracker::new does and erases its type.
+  pub fn from_generic<T: Messenger>(messenger: &'a T, max: usize) -> DynLimitTracker<'a> {
+    DynLimitTracker::new(messenger, max)
+  }
+
+  pub fn with_thresholds(
+    messenger: &'a dyn Messenger,
+    max: usize,
+    thresholds: Vec<(f64, Level)>
+  ) -> Result<DynLimitTracker<'a>, ThresholdError> {
+    Ok(DynLimitTracker {
+      messenger,
+      quota: Quota::new(max, Thresholds::new(thresholds)?)
+    })
+  }
+
+  pub fn set_value(&mut self, value: usize) {
+    self.quota.set_value(self.messenger, value);
+  }
+
+  pub fn add(&mut self, delta: i64) {
+    self.quota.add(self.messenger, delta);
+  }
+
+  pub fn reset(&mut self) {
+    self.quota.reset();
+  }
+
+  pub fn value(&self) -> usize {
+    self.quota.value
+  }
+}
+
+/// One recorded call: its position among all calls, the text and
+/// the value that triggered it (None when it came through plain `send`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Call {
+  pub index: usize,
+  pub text: String,
+  pub value: Option<usize>
+}
+
+/// `send` only gets `&self`, so the log sits in a RefCell<T>: the
+/// borrow rules are checked when `borrow_mut` runs instead of at compile time.
+pub struct MockMessenger {
+  pub sent_messages: RefCell<Vec<String>>,
+  pub calls: RefCell<Vec<Call>>
+}
+
+impl MockMessenger {
+  pub fn new() -> MockMessenger {
+    MockMessenger {
+      sent_messages: RefCell::new(vec![]),
+      calls: RefCell::new(vec![])
+    }
+  }
+
+  pub fn messages_containing(&self, needle: &str) -> usize {
+    self.sent_messages.borrow().iter().filter(|message| message.contains(needle)).count()
+  }
+
+  pub fn last_message(&self) -> Option<String> {
+    self.sent_messages.borrow().last().cloned()
+  }
+
+  // both logs are borrowed mutably at once, they are separate RefCells
+  fn record(&self, message: &str, value: Option<usize>) {
+    let mut calls = self.calls.borrow_mut();
+    let mut sent_messages = self.sent_messages.borrow_mut();
+    let index = calls.len();
+    calls.push(Call { index, text: String::from(message), value });
+    sent_messages.push(String::from(message));
+  }
+}
+
+impl Default for MockMessenger {
+  fn default() -> MockMessenger {
+    MockMessenger::new()
+  }
+}
+
+impl Messenger for MockMessenger {
+  fn send(&self, message: &str) {
+    self.record(message, None);
+  }
+
+  fn send_with_value(&self, message: &str, value: usize) {
+    self.record(message, Some(value));
+  }
+}
+
+/// Writes every message as one line to any `Write` sink. Writing needs
+/// `&mut W` while `send` only has `&self`, so the sink lives in a RefCell<T>.
+pub struct WriterMessenger<W: Write> {
+  sink: RefCell<W>
+}
+
+impl<W: Write> WriterMessenger<W> {
+  pub fn new(sink: W) -> WriterMessenger<W> {
+    WriterMessenger { sink: RefCell::new(sink) }
+  }
+
+  pub fn into_inner(self) -> W {
+    self.sink.into_inner()
+  }
+}
+
+impl<W: Write> Messenger for WriterMessenger<W> {
+  /// Write errors are dropped here, `try_send` reports them.
+  fn send(&self, msg: &str) {
+    self.try_send(msg).ok();
+  }
+
+  fn try_send(&self, msg: &str) -> io::Result<()> {
+    writeln!(self.sink.borrow_mut(), "{}", msg)
+  }
+
+  /// The value isn't written, only the message.
+  fn try_send_with_value(&self, msg: &str, _value: usize) -> io::Result<()> {
+    self.try_send(msg)
+  }
+}
+
+/// Forwards every message to all of its children. A child that fails
+/// doesn't stop the others, the failure is recorded by child index.
+#[derive(Default)]
+pub struct FanoutMessenger {
+  children: Vec<Box<dyn Messenger>>,
+  failures: RefCell<Vec<(usize, String)>>
+}
+
+impl FanoutMessenger {
+  pub fn new() -> FanoutMessenger {
+    FanoutMessenger::default()
+  }
+
+  pub fn push(&mut self, child: Box<dyn Messenger>) {
+    self.children.push(child);
+  }
+
+  pub fn failures(&self) -> Vec<(usize, String)> {
+    self.failures.borrow().clone()
+  }
+
+  // hands the message to every child, recording the ones that failed
+  fn deliver(&self, send: impl Fn(&dyn Messenger) -> io::Result<()>) {
+    for (index, child) in self.children.iter().enumerate() {
+      if let Err(err) = send(child.as_ref()) {
+        self.failures.borrow_mut().push((index, err.to_string()));
+      }
+    }
+  }
+}
+
+impl Messenger for FanoutMessenger {
+  fn send(&self, msg: &str) {
+    self.deliver(|child| child.try_send(msg));
+  }
+
+  fn send_with_value(&self, msg: &str, value: usize) {
+    self.deliver(|child| child.try_send_with_value(msg, value));
+  }
+}
+
+/// One structured entry in a SpyMessenger's transcript. `level` is parsed
+/// from the text, so messages that didn't come from a tracker have none.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+  pub seq: usize,
+  pub text: String,
+  pub level: Option<Level>
+}
+
+impl fmt::Display for Notification {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self.level {
+      Some(level) => write!(f, "#{} [{:?}] {}", self.seq, level, self.text),
+      None => write!(f, "#{} {}", self.seq, self.text)
+    }
+  }
+}
+
+/// A MockMessenger with assertions built in: a failing assertion panics
+/// with the whole transcript, so the test output shows what was sent.
+#[derive(Default)]
+pub struct SpyMessenger {
+  notifications: RefCell<Vec<Notification>>
+}
+
+impl SpyMessenger {
+  pub fn new() -> SpyMessenger {
+    SpyMessenger::default()
+  }
+
+  pub fn notifications(&self) -> Vec<Notification> {
+    self.notifications.borrow().clone()
+  }
+
+  /// Passes when message i contains needles[i], for every message sent.
+  pub fn assert_sent_in_order(&self, needles: &[&str]) {
+    let notifications = self.notifications.borrow();
+    let matches = notifications.len() == needles.len()
+      && notifications.iter().zip(needles).all(|(notification, needle)| notification.text.contains(needle));
+    if !matches {
+      panic!("expected messages containing {:?} in this order, sent:\n{}", needles, self.transcript());
+    }
+  }
+
+  pub fn assert_nothing_sent(&self) {
+    if !self.notifications.borrow().is_empty() {
+      panic!("expected no messages, sent:\n{}", self.transcript());
+    }
+  }
+
+  fn transcript(&self) -> String {
+    let notifications = self.notifications.borrow();
+    if notifications.is_empty() {
+      return String::from("  (nothing)");
+    }
+    notifications.iter().map(|notification| format!("  {}", notification)).collect::<Vec<_>>().join("\n")
+  }
+}
+
+impl Messenger for SpyMessenger {
+  fn send(&self, msg: &str) {
+    let mut notifications = self.notifications.borrow_mut();
+    let seq = notifications.len();
+    notifications.push(Notification { seq, text: String::from(msg), level: Level::from_message(msg) });
+  }
+}
+
+/// A source of time in ticks, so tests can move it by hand.
+pub trait Clock {
+  fn now(&self) -> u64;
+}
+
+/// Any `Fn() -> u64` works as a clock.
+impl<F: Fn() -> u64> Clock for F {
+  fn now(&self) -> u64 {
+    self()
+  }
+}
+
+/// Lets at most `limit` messages through per window of `window` ticks
+/// and drops the rest. `send` only has `&self`, so the window bookkeeping
+/// is kept in Cell<T>s: plain Copy values that are swapped, never borrowed.
+pub struct RateLimitedMessenger<M: Messenger, C: Clock> {
+  inner: M,
+  clock: C,
+  limit: usize,
+  window: u64,
+  current_window: Cell<Option<u64>>,
+  sent_in_window: Cell<usize>,
+  dropped: Cell<usize>
+}
+
+impl<M: Messenger, C: Clock> RateLimitedMessenger<M, C> {
+  /// Panics if `window` is zero.
+  pub fn new(inner: M, clock: C, limit: usize, window: u64) -> RateLimitedMessenger<M, C> {
+    assert!(window > 0, "the window has to be at least one tick long");
+    RateLimitedMessenger {
+      inner,
+      clock,
+      limit,
+      window,
+      current_window: Cell::new(None),
+      sent_in_window: Cell::new(0),
+      dropped: Cell::new(0)
+    }
+  }
+
+  pub fn dropped_count(&self) -> usize {
+    self.dropped.get()
+  }
+
+  pub fn inner(&self) -> &M {
+    &self.inner
+  }
+
+  pub fn clock(&self) -> &C {
+    &self.clock
+  }
+
+  // counts the message against the window it falls in, or drops it
+  fn allow(&self) -> bool {
+    let window = self.clock.now() / self.window;
+    if self.current_window.get() != Some(window) {
+      self.current_window.set(Some(window));
+      self.sent_in_window.set(0);
+    }
+    if self.sent_in_window.get() < self.limit {
+      self.sent_in_window.set(self.sent_in_window.get() + 1);
+      true
+    } else {
+      self.dropped.set(self.dropped.get() + 1);
+      false
+    }
+  }
+}
+
+impl<M: Messenger, C: Clock> Messenger for RateLimitedMessenger<M, C> {
+  fn send(&self, msg: &str) {
+    if self.allow() {
+      self.inner.send(msg);
+    }
+  }
+
+  fn send_with_value(&self, msg: &str, value: usize) {
+    if self.allow() {
+      self.inner.send_with_value(msg, value);
+    }
+  }
+
+  /// A dropped message isn't a failure, it was never meant to go out.
+  fn try_send(&self, msg: &str) -> io::Result<()> {
+    if self.allow() {
+      self.inner.try_send(msg)
+    } else {
+      Ok(())
+    }
+  }
+
+  fn try_send_with_value(&self, msg: &str, value: usize) -> io::Result<()> {
+    if self.allow() {
+      self.inner.try_send_with_value(msg, value)
+    } else {
+      Ok(())
     }
   }
 }
@@ -41,32 +594,422 @@ impl <'a, T> LimitTracker<'a, T> where
 #[cfg(test)]
 mod tests {
   use super::*;
-  use std::cell::RefCell;
 
-  struct MockMessager {
-    sent_messages: RefCell<Vec<String>>
+  const WARNING: &str = "Warning: You've used up over 75% of your quota!";
+  const URGENT: &str = "Urgent warning: You've used up over 90% of your quota!";
+  const OVER: &str = "Error: You are over your quota!";
+
+  fn sent_after(value: usize) -> Vec<String> {
+    let mock_messenger = MockMessenger::new();
+    let mut limit_tracker = LimitTracker::new(&mock_messenger, 100);
+    limit_tracker.set_value(value);
+    mock_messenger.sent_messages.into_inner()
   }
 
-  impl MockMessager {
-    fn new() -> MockMessager {
-      MockMessager {
-        sent_messages: RefCell::new(vec![])
-      }
+  #[test]
+  fn it_sends_an_over_75_percent_warning() {
+    let mock_messenger = MockMessenger::new();
+    let mut limit_tracker = LimitTracker::new(&mock_messenger, 100);
+
+    limit_tracker.set_value(80);
+    assert_eq!(mock_messenger.sent_messages.borrow().len(), 1);
+  }
+
+  #[test]
+  fn the_75_percent_boundary() {
+    assert!(sent_after(74).is_empty());
+    assert_eq!(sent_after(75), vec![WARNING]);
+    assert_eq!(sent_after(76), vec![WARNING]);
+  }
+
+  #[test]
+  fn the_90_percent_boundary() {
+    assert_eq!(sent_after(89), vec![WARNING]);
+    assert_eq!(sent_after(90), vec![URGENT]);
+  }
+
+  #[test]
+  fn the_100_percent_boundary() {
+    assert_eq!(sent_after(99), vec![URGENT]);
+    assert_eq!(sent_after(100), vec![OVER]);
+  }
+
+  #[test]
+  fn over_quota_keeps_sending_errors() {
+    assert_eq!(sent_after(150), vec![OVER]);
+
+    let spy = SpyMessenger::new();
+    let mut limit_tracker = LimitTracker::new(&spy, 100);
+    limit_tracker.set_value(101);
+    limit_tracker.set_value(120);
+    spy.assert_sent_in_order(&["over your quota", "over your quota"]);
+  }
+
+  #[test]
+  fn custom_thresholds() {
+    let mock_messenger = MockMessenger::new();
+    let steps = vec![(0.5, Level::Info), (0.8, Level::Urgent), (1.5, Level::Exceeded)];
+    let mut limit_tracker = LimitTracker::with_thresholds(&mock_messenger, 10, steps).unwrap();
+
+    limit_tracker.set_value(4);
+    limit_tracker.set_value(5);
+    limit_tracker.set_value(12);
+    limit_tracker.set_value(15);
+    assert_eq!(*mock_messenger.sent_messages.borrow(), vec![
+      "Info: You've used up over 50% of your quota!",
+      "Urgent warning: You've used up over 80% of your quota!",
+      OVER
+    ]);
+  }
+
+  #[test]
+  fn inexact_thresholds_print_whole_percents() {
+    let mock_messenger = MockMessenger::new();
+    let steps = vec![(0.07, Level::Info), (0.29, Level::Warning), (0.57, Level::Urgent)];
+    let mut limit_tracker = LimitTracker::with_thresholds(&mock_messenger, 100, steps).unwrap();
+
+    limit_tracker.set_value(7);
+    limit_tracker.set_value(29);
+    limit_tracker.set_value(57);
+    assert_eq!(*mock_messenger.sent_messages.borrow(), vec![
+      "Info: You've used up over 7% of your quota!",
+      "Warning: You've used up over 29% of your quota!",
+      "Urgent warning: You've used up over 57% of your quota!"
+    ]);
+  }
+
+  #[test]
+  fn invalid_thresholds_are_rejected() {
+    let mock_messenger = MockMessenger::new();
+    let unsorted = vec![(0.9, Level::Urgent), (0.75, Level::Warning)];
+    let rejected = LimitTracker::with_thresholds(&mock_messenger, 100, unsorted);
+    assert_eq!(rejected.err(), Some(ThresholdError::Unsorted));
+    let duplicated = vec![(0.5, Level::Info), (0.5, Level::Warning)];
+    assert_eq!(Thresholds::new(duplicated), Err(ThresholdError::Unsorted));
+    assert_eq!(Thresholds::new(vec![(0.0, Level::Info)]), Err(ThresholdError::OutOfRange(0.0)));
+    assert_eq!(Thresholds::new(vec![(-0.5, Level::Info)]), Err(ThresholdError::OutOfRange(-0.5)));
+  }
+
+  #[test]
+  fn only_the_highest_crossed_level_is_sent() {
+    // jumping from 0 to 95 crosses 75% and 90%, only the urgent warning goes out
+    assert_eq!(sent_after(95), vec![URGENT]);
+    assert_eq!(Thresholds::default().crossed(1.2), Some((1.0, Level::Exceeded)));
+    assert_eq!(Thresholds::default().crossed(0.5), None);
+  }
+
+  #[test]
+  fn same_value_is_not_sent_twice() {
+    let spy = SpyMessenger::new();
+    let mut limit_tracker = LimitTracker::new(&spy, 100);
+    limit_tracker.set_value(80);
+    limit_tracker.set_value(80);
+    spy.assert_sent_in_order(&["75%"]);
+  }
+
+  #[test]
+  fn rising_then_falling_transcript() {
+    let mock_messenger = MockMessenger::new();
+    let mut limit_tracker = LimitTracker::new(&mock_messenger, 100);
+    for &value in [50, 80, 95, 60, 100].iter() {
+      limit_tracker.set_value(value);
+    }
+
+    let call = |index, text: &str, value| Call { index, text: String::from(text), value: Some(value) };
+    assert_eq!(*mock_messenger.calls.borrow(), vec![
+      call(0, WARNING, 80),
+      call(1, URGENT, 95),
+      call(2, OVER, 100)
+    ]);
+    assert_eq!(mock_messenger.last_message(), Some(String::from(OVER)));
+    assert_eq!(mock_messenger.messages_containing("quota"), 3);
+    assert_eq!(mock_messenger.messages_containing("warning"), 1);
+  }
+
+  #[test]
+  fn plain_send_records_no_value() {
+    let mock_messenger = MockMessenger::new();
+    assert_eq!(mock_messenger.last_message(), None);
+    mock_messenger.send("hello");
+    mock_messenger.send_with_value("again", 7);
+
+    let calls = mock_messenger.calls.borrow();
+    assert_eq!(calls[0], Call { index: 0, text: String::from("hello"), value: None });
+    assert_eq!(calls[1].index, 1);
+    assert_eq!(calls[1].value, Some(7));
+  }
+
+  #[test]
+  fn sawtooth_only_notifies_on_rising_transitions() {
+    let mock_messenger = MockMessenger::new();
+    let mut limit_tracker = LimitTracker::new(&mock_messenger, 1000);
+    limit_tracker.add(901); // 90.1%, urgent
+    limit_tracker.add(1); // 90.2%, still urgent
+    limit_tracker.add(-1);
+    limit_tracker.add(1);
+    limit_tracker.add(-100); // 80.2%, down to the warning level
+    limit_tracker.add(100); // back over 90%
+    limit_tracker.add(200); // over quota
+    limit_tracker.add(-2000); // clamps at zero
+    assert_eq!(limit_tracker.value(), 0);
+
+    let values: Vec<Option<usize>> = mock_messenger.calls.borrow().iter().map(|call| call.value).collect();
+    assert_eq!(values, vec![Some(901), Some(902), Some(1102)]);
+    assert_eq!(*mock_messenger.sent_messages.borrow(), vec![URGENT, URGENT, OVER]);
+  }
+
+  #[test]
+  fn set_value_keeps_notifying_on_every_change() {
+    let mock_messenger = MockMessenger::new();
+    let mut limit_tracker = LimitTracker::new(&mock_messenger, 1000);
+    limit_tracker.set_value(901);
+    limit_tracker.set_value(902);
+    assert_eq!(*mock_messenger.sent_messages.borrow(), vec![URGENT, URGENT]);
+    // add picks up the level set_value left behind
+    limit_tracker.add(1);
+    assert_eq!(mock_messenger.sent_messages.borrow().len(), 2);
+  }
+
+  #[test]
+  fn reset_clears_the_reported_level() {
+    let spy = SpyMessenger::new();
+    let mut limit_tracker = LimitTracker::new(&spy, 100);
+    limit_tracker.add(70);
+    spy.assert_nothing_sent();
+    limit_tracker.add(10);
+    limit_tracker.reset();
+    assert_eq!(limit_tracker.value(), 0);
+    limit_tracker.add(80);
+    spy.assert_sent_in_order(&[WARNING, WARNING]);
+  }
+
+  struct BrokenSink;
+
+  impl Write for BrokenSink {
+    fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+      Err(io::Error::other("sink is broken"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+      Ok(())
+    }
+  }
+
+  #[test]
+  fn writer_messenger_writes_lines() {
+    let writer = WriterMessenger::new(vec![]);
+    let mut limit_tracker = LimitTracker::new(&writer, 100);
+    limit_tracker.set_value(95);
+    limit_tracker.set_value(100);
+    drop(limit_tracker);
+
+    let expected = format!("{}\n{}\n", URGENT, OVER);
+    assert_eq!(writer.into_inner(), expected.into_bytes());
+  }
+
+  #[test]
+  fn fanout_delivers_to_every_child() {
+    let first = Rc::new(MockMessenger::new());
+    let second = Rc::new(MockMessenger::new());
+    let mut fanout = FanoutMessenger::new();
+    fanout.push(Box::new(Rc::clone(&first)));
+    fanout.push(Box::new(WriterMessenger::new(BrokenSink)));
+    fanout.push(Box::new(Rc::clone(&second)));
+
+    let mut limit_tracker = LimitTracker::new(&fanout, 100);
+    limit_tracker.set_value(80);
+    assert_eq!(*first.sent_messages.borrow(), vec![WARNING]);
+    assert_eq!(*second.sent_messages.borrow(), vec![WARNING]);
+    assert_eq!(fanout.failures(), vec![(1, String::from("sink is broken"))]);
+  }
+
+  #[test]
+  fn fanout_forwards_values() {
+    let first = Rc::new(MockMessenger::new());
+    let second = Rc::new(MockMessenger::new());
+    let mut fanout = FanoutMessenger::new();
+    fanout.push(Box::new(Rc::clone(&first)));
+    fanout.push(Box::new(WriterMessenger::new(BrokenSink)));
+    fanout.push(Box::new(Rc::clone(&second)));
+
+    fanout.send_with_value("hello", 42);
+    fanout.send("plain");
+    for child in [&first, &second].iter() {
+      let values: Vec<Option<usize>> = child.calls.borrow().iter().map(|call| call.value).collect();
+      assert_eq!(values, vec![Some(42), None]);
     }
+    assert_eq!(fanout.failures(), vec![(1, String::from("sink is broken")), (1, String::from("sink is broken"))]);
+  }
+
+  #[test]
+  fn empty_fanout_is_a_no_op() {
+    let fanout = FanoutMessenger::new();
+    let mut limit_tracker = LimitTracker::new(&fanout, 100);
+    limit_tracker.set_value(100);
+    assert!(fanout.failures().is_empty());
   }
 
-  impl Messager for MockMessager {
-    fn send(&self, message: &str) {
-      self.sent_messages.borrow_mut().push(String::from(message));
+  // the same steps through either tracker
+  const STEPS: [i64; 7] = [50, 30, 15, -20, 30, 1, 10];
+
+  #[test]
+  fn static_and_dynamic_trackers_agree() {
+    let generic_messenger = MockMessenger::new();
+    let dyn_messenger = MockMessenger::new();
+    let mut generic = LimitTracker::new(&generic_messenger, 100);
+    let mut dynamic = DynLimitTracker::from_generic(&dyn_messenger, 100);
+    for &delta in STEPS.iter() {
+      generic.add(delta);
+      dynamic.add(delta);
+      generic.set_value(generic.value() + 1);
+      dynamic.set_value(dynamic.value() + 1);
+    }
+
+    let generic_calls = generic_messenger.calls.borrow();
+    let dyn_calls = dyn_messenger.calls.borrow();
+    assert!(!generic_calls.is_empty());
+    assert_eq!(generic_calls.len(), dyn_calls.len());
+    for (generic_call, dyn_call) in generic_calls.iter().zip(dyn_calls.iter()) {
+      assert_eq!(generic_call, dyn_call);
     }
   }
 
   #[test]
-  fn it_sends_an_over_75_percent_warning() {
-    let mock_messager = MockMessager::new();
-    let mut limit_tracker = LimitTracker::new(&mock_messager, 100);
+  fn generic_tracker_over_a_trait_object() {
+    let mock_messenger = MockMessenger::new();
+    let mut limit_tracker = LimitTracker::<dyn Messenger>::new(&mock_messenger, 100);
+    limit_tracker.set_value(90);
+    assert_eq!(mock_messenger.last_message(), Some(String::from(URGENT)));
+  }
+
+  #[test]
+  fn dyn_tracker_with_a_boxed_messenger() {
+    let mock_messenger = Rc::new(MockMessenger::new());
+    let boxed: Box<dyn Messenger> = Box::new(Rc::clone(&mock_messenger));
+    let mut limit_tracker = DynLimitTracker::new(&*boxed, 100);
+    limit_tracker.add(80);
+    limit_tracker.add(5);
+    limit_tracker.reset();
+    limit_tracker.add(100);
+    assert_eq!(*mock_messenger.sent_messages.borrow(), vec![WARNING, OVER]);
+
+    let steps = vec![(0.5, Level::Info)];
+    assert!(DynLimitTracker::with_thresholds(&*boxed, 10, steps).is_ok());
+  }
 
+  struct ManualClock {
+    now: Cell<u64>
+  }
+
+  impl ManualClock {
+    fn new() -> ManualClock {
+      ManualClock { now: Cell::new(0) }
+    }
+
+    fn advance(&self, ticks: u64) {
+      self.now.set(self.now.get() + ticks);
+    }
+  }
+
+  impl Clock for ManualClock {
+    fn now(&self) -> u64 {
+      self.now.get()
+    }
+  }
+
+  fn rate_limited(limit: usize) -> RateLimitedMessenger<MockMessenger, ManualClock> {
+    RateLimitedMessenger::new(MockMessenger::new(), ManualClock::new(), limit, 10)
+  }
+
+  #[test]
+  fn first_messages_in_a_window_pass() {
+    let messenger = rate_limited(2);
+    messenger.send("one");
+    messenger.send_with_value("two", 2);
+    assert_eq!(*messenger.inner().sent_messages.borrow(), vec!["one", "two"]);
+    assert_eq!(messenger.inner().calls.borrow()[1].value, Some(2));
+    assert_eq!(messenger.dropped_count(), 0);
+  }
+
+  #[test]
+  fn messages_over_the_limit_are_dropped() {
+    let messenger = rate_limited(2);
+    for message in ["one", "two", "three", "four"].iter() {
+      messenger.send(message);
+    }
+    messenger.clock().advance(9); // still the same window
+    assert!(messenger.try_send("five").is_ok());
+    assert_eq!(*messenger.inner().sent_messages.borrow(), vec!["one", "two"]);
+    assert_eq!(messenger.dropped_count(), 3);
+  }
+
+  #[test]
+  fn a_new_window_lets_messages_through_again() {
+    let messenger = rate_limited(1);
+    messenger.send("one");
+    messenger.send("dropped");
+    messenger.clock().advance(10);
+    messenger.send("two");
+    messenger.send("dropped too");
+    messenger.clock().advance(25);
+    messenger.send("three");
+    assert_eq!(*messenger.inner().sent_messages.borrow(), vec!["one", "two", "three"]);
+    assert_eq!(messenger.dropped_count(), 2);
+  }
+
+  #[test]
+  fn limit_tracker_through_a_rate_limit() {
+    let messenger = rate_limited(1);
+    let mut limit_tracker = LimitTracker::new(&messenger, 100);
+    limit_tracker.set_value(80);
+    limit_tracker.set_value(95);
+    assert_eq!(*messenger.inner().sent_messages.borrow(), vec![WARNING]);
+    assert_eq!(messenger.dropped_count(), 1);
+  }
+
+  #[test]
+  fn spy_parses_levels() {
+    let spy = SpyMessenger::new();
+    let mut limit_tracker = LimitTracker::new(&spy, 100);
     limit_tracker.set_value(80);
-    assert_eq!(mock_messager.sent_messages.borrow().len(), 1);
+    limit_tracker.set_value(100);
+    spy.send("hello");
+
+    let levels: Vec<Option<Level>> = spy.notifications().iter().map(|notification| notification.level).collect();
+    assert_eq!(levels, vec![Some(Level::Warning), Some(Level::Exceeded), None]);
+    assert_eq!(spy.notifications()[2].seq, 2);
+    spy.assert_sent_in_order(&["Warning", "Error", "hello"]);
+  }
+
+  #[test]
+  #[should_panic(expected = "#0 [Urgent] Urgent warning")]
+  fn spy_out_of_order_lists_the_transcript() {
+    let spy = SpyMessenger::new();
+    let mut limit_tracker = LimitTracker::new(&spy, 100);
+    limit_tracker.set_value(90);
+    limit_tracker.set_value(100);
+    spy.assert_sent_in_order(&["over your quota", "90%"]);
   }
-}
\ No newline at end of file
+
+  #[test]
+  #[should_panic(expected = "expected no messages, sent:\n  #0 hi")]
+  fn spy_nothing_sent_fails_with_the_transcript() {
+    let spy = SpyMessenger::new();
+    spy.send("hi");
+    spy.assert_nothing_sent();
+  }
+
+  #[test]
+  fn spy_empty_transcript() {
+    let spy = SpyMessenger::new();
+    spy.assert_nothing_sent();
+    spy.assert_sent_in_order(&[]);
+    assert_eq!(spy.transcript(), "  (nothing)");
+  }
+
+  #[test]
+  #[should_panic(expected = "sent:\n  (nothing)")]
+  fn spy_expecting_messages_from_an_empty_transcript() {
+    SpyMessenger::new().assert_sent_in_order(&["Warning"]);
+  }
+}
@@ -0,0 +1,522 @@
+/// Having multiple owners of mutable data by combining Rc<T> and RefCell<T>
+/// A common way to use RefCell<T> is in combination with Rc<T>. Recall that
+/// Rc<T> lets you have multiple owners of some data, but it only gives
+/// immutable access to that data. If you have an Rc<T> that holds a RefCell<T>,
+///  you can get a value that can have multiple owners and that you can mutate!
+use std::cell::{BorrowError, Ref, RefCell};
+use std::fmt;
+use std::ops::AddAssign;
+use std::rc::Rc;
+use crate::list::List;
+use MutList::{MutCons, Nil};
+
+#[derive(Debug)]
+pub enum MutList<T> {
+  MutCons(Rc<RefCell<T>>, Rc<MutList<T>>),
+  Nil
+}
+
+#[derive(Debug, PartialEq)]
+pub struct IndexError {
+  pub index: usize,
+  pub len: usize
+}
+
+impl fmt::Display for IndexError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "index {} is out of range for a list of length {}", self.index, self.len)
+  }
+}
+
+impl<T> MutList<T> {
+  pub fn len(&self) -> usize {
+    match self {
+      MutCons(_, next) => 1 + next.len(),
+      Nil => 0
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    matches!(self, Nil)
+  }
+
+  // walks the Cons chain to the nth cell
+  fn cell(&self, index: usize) -> Option<&Rc<RefCell<T>>> {
+    let mut current = self;
+    let mut remaining = index;
+    while let MutCons(cell, next) = current {
+      if remaining == 0 {
+        return Some(cell);
+      }
+      remaining -= 1;
+      current = next;
+    }
+    None
+  }
+
+  pub fn get(&self, index: usize) -> Option<T> where T: Clone {
+    self.cell(index).map(|cell| cell.borrow().clone())
+  }
+
+  /// Replaces the value at `index` and returns the previous one. A cell
+  /// shared with another list changes for both of them.
+  pub fn set(&self, index: usize, value: T) -> Result<T, IndexError> {
+    match self.cell(index) {
+      Some(cell) => Ok(cell.replace(value)),
+      None => Err(IndexError { index, len: self.len() })
+    }
+  }
+
+  /// Applies `f` to every cell in place through `borrow_mut`. The closure
+  /// runs each time the traversal reaches a cell: a cell shared with
+  /// another list is changed again when that list is traversed, and a
+  /// cell that appears twice in this list is changed twice.
+  pub fn for_each_mut(&self, mut f: impl FnMut(&mut T)) {
+    let mut current = self;
+    while let MutCons(cell, next) = current {
+      f(&mut cell.borrow_mut());
+      current = next;
+    }
+  }
+}
+
+impl<T: Clone> MutList<T> {
+  /// Iterates over copies of the values. The iterator is lazy: each cell
+  /// is borrowed only while `next` clones its value out, so the value you
+  /// get is the one the cell holds at that moment. A cell may be mutably
+  /// borrowed while the iterator exists, as long as the `RefMut` is dropped
+  /// before `next` reaches it; reaching a cell that is still mutably
+  /// borrowed panics, just like `RefCell::borrow`.
+  pub fn iter_values(&self) -> Values<'_, T> {
+    Values { current: self }
+  }
+}
+
+impl<T> MutList<T> {
+  /// Borrows every cell up front and keeps the guards, so the values can't
+  /// change while the caller holds them. Fails instead of panicking when
+  /// one of the cells is already mutably borrowed.
+  pub fn try_iter_refs(&self) -> Result<Vec<Ref<'_, T>>, BorrowError> {
+    let mut refs = vec![];
+    let mut current = self;
+    while let MutCons(cell, next) = current {
+      refs.push(cell.try_borrow()?);
+      current = next;
+    }
+    Ok(refs)
+  }
+}
+
+pub struct Values<'a, T> {
+  current: &'a MutList<T>
+}
+
+impl<'a, T: Clone> Iterator for Values<'a, T> {
+  type Item = T;
+
+  fn next(&mut self) -> Option<T> {
+    match self.current {
+      MutCons(cell, next) => {
+        self.current = next;
+        Some(cell.borrow().clone())
+      },
+      Nil => None
+    }
+  }
+}
+
+/// Why a transaction was not applied.
+#[derive(Debug, PartialEq)]
+pub enum TxnError {
+  Index(IndexError),
+  Aborted(String)
+}
+
+impl fmt::Display for TxnError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      TxnError::Index(err) => write!(f, "transaction failed: {}", err),
+      TxnError::Aborted(reason) => write!(f, "transaction aborted: {}", reason)
+    }
+  }
+}
+
+/// What a transaction closure sees: writes are only recorded here and
+/// reach the cells once the closure returns Ok.
+pub struct TxnView<'a, T> {
+  list: &'a MutList<T>,
+  writes: RefCell<Vec<(usize, T)>>
+}
+
+impl<'a, T: Clone> TxnView<'a, T> {
+  pub fn set(&self, index: usize, value: T) -> Result<(), TxnError> {
+    let len = self.list.len();
+    if index >= len {
+      return Err(TxnError::Index(IndexError { index, len }));
+    }
+    self.writes.borrow_mut().push((index, value));
+    Ok(())
+  }
+
+  /// The value the list will hold at `index` if the transaction commits now.
+  pub fn get(&self, index: usize) -> Option<T> {
+    let pending = self.writes
+      .borrow()
+      .iter()
+      .rev()
+      .find(|(written, _)| *written == index)
+      .map(|(_, value)| value.clone());
+    pending.or_else(|| self.list.get(index))
+  }
+}
+
+/// The old values of an applied transaction, kept per cell so that
+/// rolling back also restores cells shared with other lists.
+pub struct Applied<T> {
+  previous: Vec<(Rc<RefCell<T>>, T)>
+}
+
+impl<T> Applied<T> {
+  pub fn rollback(self) {
+    for (cell, old) in self.previous.into_iter().rev() {
+      *cell.borrow_mut() = old;
+    }
+  }
+}
+
+impl<T: Clone> MutList<T> {
+  /// Runs `f` against a view of the list. On Ok every recorded write is
+  /// applied through `borrow_mut`; on Err nothing is.
+  pub fn transaction(&self, f: impl FnOnce(&TxnView<T>) -> Result<(), TxnError>) -> Result<(), TxnError> {
+    self.transaction_with_undo(f).map(|_| ())
+  }
+
+  /// Like `transaction`, but keeps the overwritten values for a later rollback.
+  pub fn transaction_with_undo(&self, f: impl FnOnce(&TxnView<T>) -> Result<(), TxnError>) -> Result<Applied<T>, TxnError> {
+    let view = TxnView { list: self, writes: RefCell::new(vec![]) };
+    f(&view)?;
+
+    let previous = view.writes
+      .into_inner()
+      .into_iter()
+      .map(|(index, value)| {
+        let cell = self.cell(index).expect("writes are checked against the length");
+        let old = cell.replace(value);
+        (Rc::clone(cell), old)
+      })
+      .collect();
+    Ok(Applied { previous })
+  }
+}
+
+/// Prints the values only, `[6, 15]`, instead of the derived
+/// `MutCons(RefCell { value: 6 }, ...)` noise.
+impl<T: fmt::Display> fmt::Display for MutList<T> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "[")?;
+    let mut current = self;
+    while let MutCons(cell, next) = current {
+      if !std::ptr::eq(current, self) {
+        write!(f, ", ")?;
+      }
+      write!(f, "{}", cell.borrow())?;
+      current = next;
+    }
+    write!(f, "]")
+  }
+}
+
+impl<T: fmt::Display> MutList<T> {
+  /// Like Display, but annotates every cell with its `Rc::strong_count`
+  /// so shared cells stand out: `[6 (rc=1), 15 (rc=2)]`.
+  pub fn debug_verbose(&self) -> String {
+    let mut cells = vec![];
+    let mut current = self;
+    while let MutCons(cell, next) = current {
+      cells.push(format!("{} (rc={})", cell.borrow(), Rc::strong_count(cell)));
+      current = next;
+    }
+    format!("[{}]", cells.join(", "))
+  }
+}
+
+impl MutList<i32> {
+  pub fn sum(&self) -> i64 {
+    self.iter_values().map(i64::from).sum()
+  }
+
+  pub fn max(&self) -> Option<i32> {
+    self.iter_values().max()
+  }
+
+  /// Cells with more than one owner, whether another list or an outside handle.
+  pub fn count_shared(&self) -> usize {
+    let mut shared = 0;
+    let mut current = self;
+    while let MutCons(cell, next) = current {
+      if Rc::strong_count(cell) > 1 {
+        shared += 1;
+      }
+      current = next;
+    }
+    shared
+  }
+
+  /// Snapshots the current values into an immutable List.
+  /// Later changes to the cells don't reach the snapshot.
+  pub fn freeze(&self) -> Rc<List> {
+    match self {
+      MutCons(cell, next) => Rc::new(List::Cons(*cell.borrow(), next.freeze())),
+      Nil => Rc::new(List::Nil)
+    }
+  }
+}
+
+impl<T: AddAssign + From<u8>> MutList<T> {
+  /// Adds one to every element.
+  pub fn increment_all(&self) {
+    self.for_each_mut(|value| *value += T::from(1));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn cell<T>(value: T) -> Rc<RefCell<T>> {
+    Rc::new(RefCell::new(value))
+  }
+
+  #[test]
+  fn get_and_set_in_range() {
+    let list = MutCons(cell(1), Rc::new(MutCons(cell(2), Rc::new(Nil))));
+    assert_eq!(list.get(0), Some(1));
+    assert_eq!(list.get(1), Some(2));
+    assert_eq!(list.set(1, 20), Ok(2));
+    assert_eq!(list.get(1), Some(20));
+  }
+
+  #[test]
+  fn out_of_range() {
+    let list = MutCons(cell(1), Rc::new(Nil));
+    assert_eq!(list.get(1), None);
+    assert_eq!(list.set(3, 0), Err(IndexError { index: 3, len: 1 }));
+    assert_eq!(Nil::<i32>.get(0), None);
+  }
+
+  #[test]
+  fn setting_a_shared_cell_is_seen_by_both_lists() {
+    let shared = Rc::new(MutCons(cell(5), Rc::new(Nil)));
+    let b = MutCons(cell(6), Rc::clone(&shared));
+    let c = MutCons(cell(7), Rc::clone(&shared));
+
+    assert_eq!(b.set(1, 50), Ok(5));
+    assert_eq!(c.get(1), Some(50));
+    assert_eq!(shared.get(0), Some(50));
+    // the lists' own heads are untouched
+    assert_eq!((b.get(0), c.get(0)), (Some(6), Some(7)));
+  }
+
+  #[test]
+  fn for_each_mut_doubles_in_place() {
+    let list = MutCons(cell(1), Rc::new(MutCons(cell(2), Rc::new(Nil))));
+    list.for_each_mut(|value| *value *= 2);
+    assert_eq!((list.get(0), list.get(1)), (Some(2), Some(4)));
+
+    list.increment_all();
+    assert_eq!((list.get(0), list.get(1)), (Some(3), Some(5)));
+  }
+
+  #[test]
+  fn for_each_mut_on_strings() {
+    let list = MutCons(cell(String::from("Rc")), Rc::new(MutCons(cell(String::from("RefCell")), Rc::new(Nil))));
+    list.for_each_mut(|value| value.push_str("<T>"));
+    assert_eq!(list.get(0).unwrap(), "Rc<T>");
+    assert_eq!(list.get(1).unwrap(), "RefCell<T>");
+  }
+
+  #[test]
+  fn shared_cell_is_changed_by_every_traversal_reaching_it() {
+    let shared = cell(5);
+    let tail = Rc::new(MutCons(Rc::clone(&shared), Rc::new(Nil)));
+    let b = MutCons(cell(6), Rc::clone(&tail));
+    let c = MutCons(cell(7), Rc::clone(&tail));
+
+    b.for_each_mut(|value| *value *= 2);
+    c.for_each_mut(|value| *value *= 2);
+    assert_eq!(*shared.borrow(), 20);
+    assert_eq!((b.get(0), c.get(0)), (Some(12), Some(14)));
+
+    // the same cell twice in one list is doubled twice in one traversal
+    let twice = MutCons(Rc::clone(&shared), Rc::new(MutCons(Rc::clone(&shared), Rc::new(Nil))));
+    twice.for_each_mut(|value| *value *= 2);
+    assert_eq!(*shared.borrow(), 80);
+  }
+
+  fn three() -> (Rc<RefCell<i32>>, MutList<i32>) {
+    let middle = cell(2);
+    let list = MutCons(cell(1), Rc::new(MutCons(Rc::clone(&middle), Rc::new(MutCons(cell(3), Rc::new(Nil))))));
+    (middle, list)
+  }
+
+  #[test]
+  fn iter_values_copies_in_order() {
+    let (_, list) = three();
+    assert_eq!(list.iter_values().collect::<Vec<_>>(), vec![1, 2, 3]);
+    assert_eq!(list.iter_values().sum::<i32>(), 6);
+  }
+
+  #[test]
+  fn iter_values_reads_each_cell_when_it_gets_there() {
+    let (middle, list) = three();
+    let mut guard = middle.borrow_mut();
+    let mut values = list.iter_values();
+    // the first cell isn't the borrowed one
+    assert_eq!(values.next(), Some(1));
+
+    *guard = 20;
+    drop(guard);
+    assert_eq!(values.next(), Some(20));
+    assert_eq!(values.next(), Some(3));
+    assert_eq!(values.next(), None);
+  }
+
+  #[test]
+  #[should_panic(expected = "already mutably borrowed")]
+  fn iter_values_panics_on_a_cell_still_borrowed() {
+    let (middle, list) = three();
+    let _guard = middle.borrow_mut();
+    list.iter_values().for_each(drop);
+  }
+
+  #[test]
+  fn try_iter_refs_guards_every_cell() {
+    let (middle, list) = three();
+    {
+      let refs = list.try_iter_refs().unwrap();
+      let values: Vec<i32> = refs.iter().map(|value| **value).collect();
+      assert_eq!(values, vec![1, 2, 3]);
+      // held guards block writers
+      assert!(middle.try_borrow_mut().is_err());
+    }
+
+    let _guard = middle.borrow_mut();
+    assert!(list.try_iter_refs().is_err());
+  }
+
+  #[test]
+  fn failing_transaction_changes_nothing() {
+    let (_, list) = three();
+    let result = list.transaction(|txn| {
+      txn.set(0, 10)?;
+      txn.set(1, 20)?;
+      Err(TxnError::Aborted(String::from("changed my mind")))
+    });
+    assert_eq!(result, Err(TxnError::Aborted(String::from("changed my mind"))));
+    assert_eq!(list.iter_values().collect::<Vec<_>>(), vec![1, 2, 3]);
+
+    let result = list.transaction(|txn| {
+      txn.set(0, 10)?;
+      txn.set(7, 70)
+    });
+    assert_eq!(result, Err(TxnError::Index(IndexError { index: 7, len: 3 })));
+    assert_eq!(list.get(0), Some(1));
+  }
+
+  #[test]
+  fn succeeding_transaction_applies_every_write_at_the_end() {
+    let (_, list) = three();
+    let result = list.transaction(|txn| {
+      txn.set(0, 10)?;
+      txn.set(2, 30)?;
+      // nothing has reached the cells yet
+      assert_eq!(list.get(0), Some(1));
+      assert_eq!(txn.get(0), Some(10));
+      assert_eq!(txn.get(1), Some(2));
+      Ok(())
+    });
+    assert_eq!(result, Ok(()));
+    assert_eq!(list.iter_values().collect::<Vec<_>>(), vec![10, 2, 30]);
+  }
+
+  #[test]
+  fn rollback_restores_shared_cells() {
+    let (middle, list) = three();
+    let other = MutCons(cell(9), Rc::new(MutCons(Rc::clone(&middle), Rc::new(Nil))));
+
+    let applied = list.transaction_with_undo(|txn| {
+      txn.set(1, 20)?;
+      txn.set(1, 200)?;
+      txn.set(0, 10)
+    }).unwrap();
+    assert_eq!(other.get(1), Some(200));
+
+    applied.rollback();
+    assert_eq!(list.iter_values().collect::<Vec<_>>(), vec![1, 2, 3]);
+    assert_eq!(other.get(1), Some(2));
+  }
+
+  #[test]
+  fn display_hides_the_cells() {
+    let value = cell(5);
+    let a = Rc::new(MutCons(Rc::clone(&value), Rc::new(Nil)));
+    let b = MutCons(cell(6), Rc::clone(&a));
+    assert_eq!(b.to_string(), "[6, 5]");
+
+    *value.borrow_mut() += 10;
+    assert_eq!(a.to_string(), "[15]");
+    assert_eq!(b.to_string(), "[6, 15]");
+    assert_eq!(Nil::<i32>.to_string(), "[]");
+  }
+
+  #[test]
+  fn debug_verbose_shows_sharing() {
+    let value = cell(5);
+    let a = Rc::new(MutCons(Rc::clone(&value), Rc::new(Nil)));
+    let b = MutCons(cell(6), Rc::clone(&a));
+    // `value` plus a's own handle on the cell
+    assert_eq!(b.debug_verbose(), "[6 (rc=1), 5 (rc=2)]");
+  }
+
+  #[test]
+  fn freeze_snapshots_current_values_in_order() {
+    let (middle, list) = three();
+    let snapshot = list.freeze();
+    *middle.borrow_mut() = 20;
+    list.set(0, 10).unwrap();
+
+    assert_eq!(format!("{:?}", snapshot), "Cons(1, Cons(2, Cons(3, Nil)))");
+    // thawing the snapshot gets the old values back
+    assert_eq!(snapshot.thaw().to_string(), "[1, 2, 3]");
+    assert_eq!(list.to_string(), "[10, 20, 3]");
+  }
+
+  #[test]
+  fn aggregations_follow_the_shared_cell() {
+    let value = cell(5);
+    let a = Rc::new(MutCons(Rc::clone(&value), Rc::new(Nil)));
+    let b = MutCons(cell(6), Rc::clone(&a));
+    let c = MutCons(cell(7), Rc::clone(&a));
+    assert_eq!((b.sum(), b.max()), (11, Some(6)));
+    assert_eq!((c.sum(), c.max()), (12, Some(7)));
+
+    *value.borrow_mut() += 10;
+    assert_eq!((b.sum(), b.max()), (21, Some(15)));
+    assert_eq!((c.sum(), c.max()), (22, Some(15)));
+    assert_eq!(b.count_shared(), 1);
+    assert_eq!(c.count_shared(), 1);
+  }
+
+  #[test]
+  fn standalone_list_shares_nothing() {
+    let (middle, list) = three();
+    assert_eq!(list.count_shared(), 1);
+    drop(middle);
+    assert_eq!(list.count_shared(), 0);
+    let standalone = MutCons(cell(i32::MAX), Rc::new(MutCons(cell(i32::MAX), Rc::new(Nil))));
+    assert_eq!(standalone.count_shared(), 0);
+    // the sum is widened, so it doesn't overflow
+    assert_eq!(standalone.sum(), 2 * i64::from(i32::MAX));
+    assert_eq!(Nil.max(), None);
+  }
+}
@@ -0,0 +1,133 @@
+/// The observer pattern is a realistic home for Weak<T>: a subject
+/// shouldn't keep its subscribers alive. Each subscriber is held as a
+/// Weak<T>, so when the last Rc<T> to an observer is dropped the
+/// subject simply stops delivering to it, no explicit unsubscribe needed.
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+pub trait Observer<T> {
+  fn notify(&mut self, event: &T);
+}
+
+pub struct Subject<T> {
+  observers: RefCell<Vec<Weak<RefCell<dyn Observer<T>>>>>
+}
+
+impl<T> Subject<T> {
+  pub fn new() -> Subject<T> {
+    Subject {
+      observers: RefCell::new(vec![])
+    }
+  }
+
+  pub fn subscribe<O: Observer<T> + 'static>(&self, observer: &Rc<RefCell<O>>) {
+    let observer: Rc<RefCell<dyn Observer<T>>> = observer.clone();
+    self.observers.borrow_mut().push(Rc::downgrade(&observer));
+  }
+
+  /// Delivers `event` to every live observer and prunes the dead ones.
+  /// Observers must not subscribe from inside `notify`, the list is
+  /// mutably borrowed for the whole delivery.
+  pub fn notify(&self, event: &T) {
+    self.observers.borrow_mut().retain(|observer| match observer.upgrade() {
+      Some(observer) => {
+        observer.borrow_mut().notify(event);
+        true
+      },
+      None => false
+    });
+  }
+
+  /// Number of subscriptions held, including dead ones not yet pruned.
+  pub fn observer_count(&self) -> usize {
+    self.observers.borrow().len()
+  }
+}
+
+impl<T> Default for Subject<T> {
+  fn default() -> Subject<T> {
+    Subject::new()
+  }
+}
+
+#[derive(Debug)]
+pub struct RecordingObserver<T> {
+  events: Vec<T>
+}
+
+impl<T> RecordingObserver<T> {
+  pub fn new() -> RecordingObserver<T> {
+    RecordingObserver { events: vec![] }
+  }
+
+  pub fn events(&self) -> &[T] {
+    &self.events
+  }
+}
+
+impl<T> Default for RecordingObserver<T> {
+  fn default() -> RecordingObserver<T> {
+    RecordingObserver::new()
+  }
+}
+
+impl<T: Clone> Observer<T> for RecordingObserver<T> {
+  fn notify(&mut self, event: &T) {
+    self.events.push(event.clone());
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn every_observer_receives_events() {
+    let subject = Subject::new();
+    let first = Rc::new(RefCell::new(RecordingObserver::new()));
+    let second = Rc::new(RefCell::new(RecordingObserver::new()));
+    subject.subscribe(&first);
+    subject.subscribe(&second);
+
+    subject.notify(&1);
+    subject.notify(&2);
+
+    assert_eq!(first.borrow().events(), &[1, 2]);
+    assert_eq!(second.borrow().events(), &[1, 2]);
+  }
+
+  #[test]
+  fn dropped_observer_stops_receiving() {
+    let subject = Subject::new();
+    let first = Rc::new(RefCell::new(RecordingObserver::new()));
+    let second = Rc::new(RefCell::new(RecordingObserver::new()));
+    subject.subscribe(&first);
+    subject.subscribe(&second);
+    subject.notify(&"before");
+
+    // the subject only held a Weak, so this frees the observer
+    let probe = Rc::downgrade(&first);
+    drop(first);
+    assert!(probe.upgrade().is_none());
+
+    subject.notify(&"after");
+    assert_eq!(second.borrow().events(), &["before", "after"]);
+  }
+
+  #[test]
+  fn notify_prunes_dead_subscriptions() {
+    let subject = Subject::new();
+    let first = Rc::new(RefCell::new(RecordingObserver::new()));
+    let second = Rc::new(RefCell::new(RecordingObserver::new()));
+    subject.subscribe(&first);
+    subject.subscribe(&second);
+    assert_eq!(subject.observer_count(), 2);
+
+    drop(first);
+    // the dead entry stays until the next delivery walks the list
+    assert_eq!(subject.observer_count(), 2);
+    subject.notify(&0);
+    assert_eq!(subject.observer_count(), 1);
+    assert_eq!(Rc::strong_count(&second), 1);
+  }
+}
@@ -0,0 +1,91 @@
+//! Scoped threads may borrow from the stack: `thread::scope` joins every
+//! thread it spawned before returning, so a plain `&[i32]` outlives them
+//! all and needs no Arc<T>. What still can't cross is an Rc<T>, the list
+//! itself stays on its thread:
+//!
+//! ```compile_fail,E0277
+//! use smart_pointers::list::List::{Cons, Nil};
+//! use std::rc::Rc;
+//! use std::thread;
+//!
+//! let list = Rc::new(Cons(1, Rc::new(Cons(2, Rc::new(Nil)))));
+//! thread::scope(|s| {
+//!   s.spawn(|| list.to_vec());
+//! });
+//! ```
+use crate::list::List;
+use std::rc::Rc;
+use std::thread;
+
+/// Splits `values` into `chunks` nearly equal slices and sums each on
+/// its own thread. Asking for more chunks than values spawns fewer threads.
+pub fn parallel_sum(values: &[i32], chunks: usize) -> i64 {
+  if values.is_empty() {
+    return 0;
+  }
+  let chunk_len = values.len().div_ceil(chunks.max(1));
+  thread::scope(|s| {
+    let handles: Vec<_> = values
+      .chunks(chunk_len)
+      .map(|chunk| s.spawn(move || chunk.iter().map(|&value| i64::from(value)).sum::<i64>()))
+      .collect();
+    handles.into_iter().map(|handle| handle.join().unwrap()).sum()
+  })
+}
+
+/// Snapshots the list into a Vec<i32>, which is Send, and sums that.
+pub fn list_parallel_sum(list: &Rc<List>) -> i64 {
+  parallel_sum(&list.to_vec(), 4)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::list::List::{Cons, Nil};
+
+  // a small LCG, enough to vary the data without a rand dependency
+  fn pseudo_random(len: usize, seed: u64) -> Vec<i32> {
+    let mut state = seed;
+    (0..len)
+      .map(|_| {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (state >> 33) as i32
+      })
+      .collect()
+  }
+
+  fn sequential_sum(values: &[i32]) -> i64 {
+    values.iter().map(|&value| i64::from(value)).sum()
+  }
+
+  #[test]
+  fn matches_the_sequential_sum() {
+    for seed in 1..6 {
+      let values = pseudo_random(1_001, seed);
+      for chunks in [1usize, 2, 3, 7, 16].iter() {
+        assert_eq!(parallel_sum(&values, *chunks), sequential_sum(&values));
+      }
+    }
+  }
+
+  #[test]
+  fn more_chunks_than_values() {
+    let values = [5, -3, 10];
+    assert_eq!(parallel_sum(&values, 50), 12);
+    assert_eq!(parallel_sum(&values, 0), 12);
+  }
+
+  #[test]
+  fn empty_inputs() {
+    assert_eq!(parallel_sum(&[], 4), 0);
+    assert_eq!(list_parallel_sum(&Rc::new(Nil)), 0);
+  }
+
+  #[test]
+  fn sums_a_shared_list() {
+    let tail = Rc::new(Cons(10, Rc::new(Cons(15, Rc::new(Nil)))));
+    let list = Rc::new(Cons(5, Rc::clone(&tail)));
+    assert_eq!(list_parallel_sum(&list), 30);
+    assert_eq!(list_parallel_sum(&tail), 25);
+  }
+}
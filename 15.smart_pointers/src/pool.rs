@@ -0,0 +1,141 @@
+/// A fixed set of worker threads pulling jobs off one channel. There is
+/// only one Receiver, so the workers share it as Arc<Mutex<Receiver<Job>>>:
+/// Arc<T> gives every worker an owner of the receiver, Mutex<T> makes sure
+/// only one of them waits on it at a time.
+use std::cell::Cell;
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+thread_local! {
+  static WORKER_ID: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// The id of the pool worker running the current job, None outside a pool.
+pub fn current_worker_id() -> Option<usize> {
+  WORKER_ID.with(Cell::get)
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PoolCreationError;
+
+impl fmt::Display for PoolCreationError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "a thread pool needs at least one thread")
+  }
+}
+
+struct Worker {
+  thread: Option<JoinHandle<()>>
+}
+
+impl Worker {
+  fn new(id: usize, receiver: Arc<Mutex<Receiver<Job>>>) -> Worker {
+    let thread = thread::spawn(move || {
+      WORKER_ID.with(|worker_id| worker_id.set(Some(id)));
+      loop {
+        // the guard is a temporary, so the lock is released before the job runs
+        let message = receiver.lock().unwrap().recv();
+        match message {
+          Ok(job) => job(),
+          // the pool dropped its Sender: no more jobs will come
+          Err(_) => break
+        }
+      }
+    });
+    Worker { thread: Some(thread) }
+  }
+}
+
+pub struct ThreadPool {
+  workers: Vec<Worker>,
+  sender: Option<Sender<Job>>
+}
+
+impl ThreadPool {
+  pub fn new(size: usize) -> Result<ThreadPool, PoolCreationError> {
+    if size == 0 {
+      return Err(PoolCreationError);
+    }
+    let (sender, receiver) = mpsc::channel();
+    let receiver = Arc::new(Mutex::new(receiver));
+    let workers = (0..size).map(|id| Worker::new(id, Arc::clone(&receiver))).collect();
+    Ok(ThreadPool { workers, sender: Some(sender) })
+  }
+
+  pub fn size(&self) -> usize {
+    self.workers.len()
+  }
+
+  pub fn execute(&self, f: impl FnOnce() + Send + 'static) {
+    if let Some(sender) = &self.sender {
+      sender.send(Box::new(f)).unwrap();
+    }
+  }
+}
+
+/// Closes the channel, then waits for every worker to finish the jobs
+/// already queued and leave its loop.
+impl Drop for ThreadPool {
+  fn drop(&mut self) {
+    drop(self.sender.take());
+    for worker in &mut self.workers {
+      if let Some(thread) = worker.thread.take() {
+        thread.join().unwrap();
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::time::Duration;
+
+  #[test]
+  fn every_job_runs_before_drop_returns() {
+    let counter = Arc::new(Mutex::new(0u64));
+    let pool = ThreadPool::new(4).unwrap();
+    for _ in 0..100 {
+      let counter = Arc::clone(&counter);
+      pool.execute(move || *counter.lock().unwrap() += 1);
+    }
+    drop(pool);
+    assert_eq!(*counter.lock().unwrap(), 100);
+    assert_eq!(Arc::strong_count(&counter), 1);
+  }
+
+  #[test]
+  fn jobs_spread_over_workers() {
+    let ids = Arc::new(Mutex::new(vec![]));
+    let pool = ThreadPool::new(2).unwrap();
+    for _ in 0..20 {
+      let ids = Arc::clone(&ids);
+      pool.execute(move || {
+        thread::sleep(Duration::from_millis(2));
+        ids.lock().unwrap().push(current_worker_id().unwrap());
+      });
+    }
+    drop(pool);
+
+    let mut ids = ids.lock().unwrap().clone();
+    assert_eq!(ids.len(), 20);
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids, vec![0, 1]);
+  }
+
+  #[test]
+  fn zero_threads_is_an_error() {
+    assert_eq!(ThreadPool::new(0).err(), Some(PoolCreationError));
+    assert_eq!(ThreadPool::new(3).unwrap().size(), 3);
+  }
+
+  #[test]
+  fn no_worker_id_outside_the_pool() {
+    assert_eq!(current_worker_id(), None);
+  }
+}
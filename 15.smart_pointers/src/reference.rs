@@ -6,11 +6,12 @@ pub mod cycle_reference {
   /// where items refer to each other in a cycle. This creates memory leaks 
   /// because the reference count of each item in the cycle will never reach 0, 
   /// and the values will never be dropped.
-  use std::rc::Rc;
+  use std::rc::{Rc, Weak};
   use std::cell::RefCell;
+  use std::collections::{HashMap, HashSet};
+  use std::fmt;
   use CycList::{Cons, Nil};
   
-  #[derive(Debug)]
   pub enum CycList {
     Cons(i32, RefCell<Rc<CycList>>),
     Nil
@@ -23,5 +24,792 @@ pub mod cycle_reference {
         Nil => None
       }
     }
+
+    /// Prints the list without recursing forever on a cycle, e.g.
+    /// `5 -> 10 -> (cycle back to node #0)`. Nodes are numbered in walk
+    /// order and recognized by address, so values may repeat freely.
+    pub fn display_safe(&self) -> String {
+      let mut visited: Vec<*const CycList> = vec![];
+      let mut parts = vec![];
+      let mut owned: Option<Rc<CycList>> = None;
+      loop {
+        let node: &CycList = match &owned {
+          Some(node) => node,
+          None => self
+        };
+        let address = node as *const CycList;
+        if let Some(index) = visited.iter().position(|&seen| seen == address) {
+          parts.push(format!("(cycle back to node #{})", index));
+          break;
+        }
+        visited.push(address);
+
+        match node {
+          Cons(value, tail) => {
+            parts.push(value.to_string());
+            let next = Rc::clone(&tail.borrow());
+            owned = Some(next);
+          },
+          Nil => {
+            parts.push(String::from("Nil"));
+            break;
+          }
+        }
+      }
+      parts.join(" -> ")
+    }
+  }
+
+  /// Iterates over the values, remembering every node it has visited by
+  /// address and stopping as soon as it comes back to one, so it ends
+  /// even on a cyclic list. Only the next node is held (as an Rc<T>
+  /// clone), counts are back to normal once the iterator is dropped.
+  pub struct CycIter<'a> {
+    start: Option<&'a CycList>,
+    next: Option<Rc<CycList>>,
+    visited: HashSet<*const CycList>,
+    stopped_on_cycle: bool
+  }
+
+  impl<'a> CycIter<'a> {
+    /// True once iteration ended on an already visited node rather than on Nil.
+    pub fn stopped_on_cycle(&self) -> bool {
+      self.stopped_on_cycle
+    }
+
+    fn visit(&mut self, node: &CycList) -> Option<(i32, Rc<CycList>)> {
+      if !self.visited.insert(node as *const CycList) {
+        self.stopped_on_cycle = true;
+        return None;
+      }
+      match node {
+        Cons(value, tail) => Some((*value, Rc::clone(&tail.borrow()))),
+        Nil => None
+      }
+    }
+  }
+
+  impl<'a> Iterator for CycIter<'a> {
+    type Item = i32;
+
+    fn next(&mut self) -> Option<i32> {
+      let step = match self.start.take() {
+        Some(node) => self.visit(node),
+        None => match self.next.take() {
+          Some(node) => self.visit(&node),
+          None => None
+        }
+      };
+      let (value, tail) = step?;
+      self.next = Some(tail);
+      Some(value)
+    }
+  }
+
+  impl CycList {
+    pub fn iter(&self) -> CycIter<'_> {
+      CycIter {
+        start: Some(self),
+        next: None,
+        visited: HashSet::new(),
+        stopped_on_cycle: false
+      }
+    }
+
+    /// Follows the tails blindly, at most `n` values: on a cycle the
+    /// values repeat, but the walk is still bounded.
+    pub fn take_limited(&self, n: usize) -> Vec<i32> {
+      let mut values = vec![];
+      let mut current = match self {
+        Cons(value, tail) if n > 0 => {
+          values.push(*value);
+          Rc::clone(&tail.borrow())
+        },
+        _ => return values
+      };
+      while values.len() < n {
+        current = match &*current {
+          Cons(value, tail) => {
+            values.push(*value);
+            Rc::clone(&tail.borrow())
+          },
+          Nil => break
+        };
+      }
+      values
+    }
+  }
+
+  /// Renders the lists as a Graphviz digraph: every node once (keyed by
+  /// address) labelled with its value and strong count, one edge per
+  /// tail, and `color=red` on the edge that closes a cycle, i.e. points
+  /// back to a node already on the path being walked.
+  pub fn to_dot(roots: &[(&str, &Rc<CycList>)]) -> String {
+    let mut ids: HashMap<*const CycList, usize> = HashMap::new();
+    let mut lines = vec![String::from("digraph CycList {")];
+
+    for (name, root) in roots {
+      lines.push(format!("  \"{}\" [shape=plaintext];", name));
+      let mut path = HashSet::new();
+      let mut current = Rc::clone(root);
+      let mut from = format!("\"{}\"", name);
+      loop {
+        let address = Rc::as_ptr(&current);
+        let next_id = ids.len();
+        let seen = ids.contains_key(&address);
+        let id = *ids.entry(address).or_insert(next_id);
+        if path.contains(&address) {
+          lines.push(format!("  {} -> n{} [color=red];", from, id));
+          break;
+        }
+        lines.push(format!("  {} -> n{};", from, id));
+        if seen {
+          break;
+        }
+        path.insert(address);
+
+        // `current` is our own extra clone, leave it out of the label
+        let strong = Rc::strong_count(&current) - 1;
+        let next = match &*current {
+          Cons(value, tail) => {
+            lines.push(format!("  n{} [label=\"{} (rc={})\"];", id, value, strong));
+            Rc::clone(&tail.borrow())
+          },
+          Nil => {
+            lines.push(format!("  n{} [label=\"Nil (rc={})\"];", id, strong));
+            break;
+          }
+        };
+        from = format!("n{}", id);
+        current = next;
+      }
+    }
+
+    lines.push(String::from("}"));
+    lines.join("\n")
+  }
+
+  /// The shape of a list: a straight line ending in Nil, or a "rho" (ρ),
+  /// a straight prefix leading into a loop. The demo's a/b cycle is a
+  /// rho without a prefix.
+  #[derive(Debug, PartialEq)]
+  pub enum ListShape {
+    Acyclic { len: usize },
+    Cyclic { prefix_len: usize, cycle_len: usize }
+  }
+
+  impl fmt::Display for ListShape {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+      match self {
+        ListShape::Acyclic { len } => write!(f, "Acyclic{{{}}}", len),
+        ListShape::Cyclic { prefix_len, cycle_len } => write!(f, "Cyclic{{{}, {}}}", prefix_len, cycle_len)
+      }
+    }
+  }
+
+  /// Numbers the Cons nodes by address while walking: the first node seen
+  /// twice is where the loop starts, its number is the prefix length and
+  /// the distance back to it is the cycle length. O(n) time and space.
+  pub fn shape(list: &Rc<CycList>) -> ListShape {
+    let mut positions: HashMap<*const CycList, usize> = HashMap::new();
+    let mut current = Rc::clone(list);
+    loop {
+      let address = Rc::as_ptr(&current);
+      let index = positions.len();
+      if let Some(&start) = positions.get(&address) {
+        return ListShape::Cyclic { prefix_len: start, cycle_len: index - start };
+      }
+      current = match advance(&current) {
+        Some(next) => next,
+        None => return ListShape::Acyclic { len: index }
+      };
+      positions.insert(address, index);
+    }
+  }
+
+  /// Why `set_tail` refused to install a tail.
+  #[derive(Debug, PartialEq)]
+  pub enum CycleError {
+    /// the list itself is reachable from the new tail
+    ClosesCycle,
+    /// Nil has no tail to replace
+    NoTail
+  }
+
+  impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+      match self {
+        CycleError::ClosesCycle => write!(f, "the new tail leads back to this node"),
+        CycleError::NoTail => write!(f, "Nil has no tail")
+      }
+    }
+  }
+
+  // walks from `from` (cycles included) looking for the node at `target`
+  fn reaches(from: &Rc<CycList>, target: *const CycList) -> bool {
+    let mut visited = HashSet::new();
+    let mut current = Some(Rc::clone(from));
+    while let Some(node) = current {
+      if Rc::as_ptr(&node) == target {
+        return true;
+      }
+      if !visited.insert(Rc::as_ptr(&node)) {
+        return false;
+      }
+      current = advance(&node);
+    }
+    false
+  }
+
+  impl CycList {
+    /// Replaces the tail, unless that would close a cycle through `self`.
+    pub fn set_tail(self: &Rc<Self>, new_tail: Rc<CycList>) -> Result<(), CycleError> {
+      if reaches(&new_tail, Rc::as_ptr(self)) {
+        return Err(CycleError::ClosesCycle);
+      }
+      self.force_set_tail(new_tail)
+    }
+
+    /// Replaces the tail without any check: this is how the leaking
+    /// cycle in the demo gets built.
+    pub fn force_set_tail(&self, new_tail: Rc<CycList>) -> Result<(), CycleError> {
+      match self.tail() {
+        Some(link) => {
+          *link.borrow_mut() = new_tail;
+          Ok(())
+        },
+        None => Err(CycleError::NoTail)
+      }
+    }
+  }
+
+  /// The demo's steps, one function each so the counts can be asserted.
+  /// `a` starts as `5 -> Nil`.
+  pub fn build_a() -> Rc<CycList> {
+    Rc::new(Cons(5, RefCell::new(Rc::new(Nil))))
+  }
+
+  /// `b` is `10 -> a`, sharing `a` rather than copying it.
+  pub fn link_b_to_a(a: &Rc<CycList>) -> Rc<CycList> {
+    Rc::new(Cons(10, RefCell::new(Rc::clone(a))))
+  }
+
+  /// Points `a` back at `b`. From here on neither count can reach zero.
+  /// Panics if `a` is Nil, which `build_a` never returns.
+  pub fn close_cycle(a: &Rc<CycList>, b: &Rc<CycList>) {
+    a.force_set_tail(Rc::clone(b)).unwrap();
+  }
+
+  /// The strong count of `list`, labelled like the demo prints it.
+  pub fn checkpoint(label: &str, list: &Rc<CycList>) -> (String, usize) {
+    (label.to_string(), Rc::strong_count(list))
+  }
+
+  /// What the a/b cycle demo leaves behind once our own handles are gone.
+  #[derive(Debug, PartialEq)]
+  pub struct CycleDemoReport {
+    /// (a, b) strong counts once the cycle is closed
+    pub strong_before_drop: (usize, usize),
+    /// (a, b) strong counts, read through Weak probes, after dropping a and b
+    pub strong_after_drop: (usize, usize),
+    pub a_leaked: bool,
+    pub b_leaked: bool
+  }
+
+  /// Builds the a/b cycle exactly like the demo, then drops both handles.
+  pub fn run_cycle_demo() -> CycleDemoReport {
+    let a = build_a();
+    let b = link_b_to_a(&a);
+    close_cycle(&a, &b);
+    let strong_before_drop = (Rc::strong_count(&a), Rc::strong_count(&b));
+
+    let (probe_a, probe_b) = (Rc::downgrade(&a), Rc::downgrade(&b));
+    drop(a);
+    drop(b);
+    CycleDemoReport {
+      strong_before_drop,
+      strong_after_drop: (probe_a.strong_count(), probe_b.strong_count()),
+      a_leaked: probe_a.upgrade().is_some(),
+      b_leaked: probe_b.upgrade().is_some()
+    }
+  }
+
+  /// The same steps with WeakCycList.
+  pub fn run_weak_cycle_demo() -> CycleDemoReport {
+    let a = Rc::new(WeakCycList::Cons(5, RefCell::new(Weak::new())));
+    let b = Rc::new(WeakCycList::Cons(10, RefCell::new(Rc::downgrade(&a))));
+    *a.tail().unwrap().borrow_mut() = Rc::downgrade(&b);
+    let strong_before_drop = (Rc::strong_count(&a), Rc::strong_count(&b));
+
+    let (probe_a, probe_b) = (Rc::downgrade(&a), Rc::downgrade(&b));
+    drop(a);
+    drop(b);
+    CycleDemoReport {
+      strong_before_drop,
+      strong_after_drop: (probe_a.strong_count(), probe_b.strong_count()),
+      a_leaked: probe_a.upgrade().is_some(),
+      b_leaked: probe_b.upgrade().is_some()
+    }
+  }
+
+  /// The derived Debug would follow the tail forever once a cycle
+  /// exists and overflow the stack, this one is always safe.
+  impl fmt::Debug for CycList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+      write!(f, "{}", self.display_safe())
+    }
+  }
+
+  /// The fix: the same shape, but the tail is a Weak<T>. A Weak<T> doesn't
+  /// count towards ownership, so a cycle of them no longer keeps its
+  /// nodes alive and both are freed once the outside Rc<T>s are dropped.
+  /// The price is that `upgrade()` may find the next node gone.
+  #[allow(dead_code)]
+  #[derive(Debug)]
+  pub enum WeakCycList {
+    Cons(i32, RefCell<Weak<WeakCycList>>),
+    Nil
+  }
+
+  impl WeakCycList {
+    pub fn tail(&self) -> Option<&RefCell<Weak<WeakCycList>>> {
+      match self {
+        WeakCycList::Cons(_, item) => Some(item),
+        WeakCycList::Nil => None
+      }
+    }
+  }
+
+  // the node after this one, Nil has none
+  fn advance(node: &Rc<CycList>) -> Option<Rc<CycList>> {
+    node.tail().map(|tail| Rc::clone(&tail.borrow()))
+  }
+
+  /// Floyd's tortoise and hare: the hare moves two nodes per step, the
+  /// tortoise one. If the hare runs off the end there is no cycle,
+  /// otherwise the two end up on the same node. Nodes are compared by
+  /// address, runs in O(n) time with O(1) extra space and never writes
+  /// to the list.
+  pub fn has_cycle(list: &Rc<CycList>) -> bool {
+    let mut tortoise = Rc::clone(list);
+    let mut hare = Rc::clone(list);
+    loop {
+      hare = match advance(&hare).and_then(|next| advance(&next)) {
+        Some(node) => node,
+        None => return false
+      };
+      tortoise = advance(&tortoise).expect("the tortoise is behind the hare");
+      if Rc::ptr_eq(&tortoise, &hare) {
+        return true;
+      }
+    }
+  }
+
+  #[cfg(test)]
+  mod tests {
+    use super::*;
+
+    fn cons(value: i32, tail: &Rc<CycList>) -> Rc<CycList> {
+      Rc::new(Cons(value, RefCell::new(Rc::clone(tail))))
+    }
+
+    fn link(from: &Rc<CycList>, to: &Rc<CycList>) {
+      *from.tail().unwrap().borrow_mut() = Rc::clone(to);
+    }
+
+    #[test]
+    fn display_acyclic_list() {
+      let a = cons(5, &Rc::new(Nil));
+      let b = cons(10, &a);
+      assert_eq!(b.display_safe(), "10 -> 5 -> Nil");
+      assert_eq!(format!("{:?}", Nil), "Nil");
+    }
+
+    #[test]
+    fn display_full_cycle() {
+      let a = cons(5, &Rc::new(Nil));
+      let b = cons(10, &a);
+      link(&a, &b);
+      assert_eq!(a.display_safe(), "5 -> 10 -> (cycle back to node #0)");
+      assert_eq!(format!("{:?}", b), "10 -> 5 -> (cycle back to node #0)");
+      // break the cycle so the test doesn't leak
+      link(&a, &Rc::new(Nil));
+    }
+
+    #[test]
+    fn display_rho_shape() {
+      let loop_end = cons(3, &Rc::new(Nil));
+      let loop_start = cons(2, &loop_end);
+      let head = cons(1, &loop_start);
+      link(&loop_end, &loop_start);
+      assert_eq!(head.display_safe(), "1 -> 2 -> 3 -> (cycle back to node #1)");
+      link(&loop_end, &Rc::new(Nil));
+    }
+
+    #[test]
+    fn no_cycle_in_the_initial_chain() {
+      let a = cons(5, &Rc::new(Nil));
+      let b = cons(10, &a);
+      assert!(!has_cycle(&a));
+      assert!(!has_cycle(&b));
+      assert!(!has_cycle(&Rc::new(Nil)));
+    }
+
+    #[test]
+    fn cycle_detected_after_linking_back() {
+      let a = cons(5, &Rc::new(Nil));
+      let b = cons(10, &a);
+      link(&a, &b);
+      assert!(has_cycle(&a));
+      assert!(has_cycle(&b));
+      link(&a, &Rc::new(Nil));
+      assert!(!has_cycle(&b));
+    }
+
+    #[test]
+    fn long_acyclic_list() {
+      let mut head = Rc::new(Nil);
+      for value in 0..10_000 {
+        head = cons(value, &head);
+      }
+      assert!(!has_cycle(&head));
+
+      // unlink iteratively, dropping 10_000 nested Rcs recursively
+      // could overflow the test thread's stack
+      while let Some(next) = advance(&head) {
+        link(&head, &Rc::new(Nil));
+        head = next;
+      }
+    }
+
+    #[test]
+    fn cycle_that_skips_the_head() {
+      let loop_end = cons(3, &Rc::new(Nil));
+      let loop_start = cons(2, &loop_end);
+      let head = cons(1, &loop_start);
+      link(&loop_end, &loop_start);
+      assert!(has_cycle(&head));
+      link(&loop_end, &Rc::new(Nil));
+    }
+
+    #[test]
+    fn weak_tail_keeps_strong_counts_at_one() {
+      let a = cons(5, &Rc::new(Nil));
+      let b = cons(10, &a);
+      link(&a, &b);
+
+      let weak_a = Rc::new(WeakCycList::Cons(5, RefCell::new(Weak::new())));
+      let weak_b = Rc::new(WeakCycList::Cons(10, RefCell::new(Rc::downgrade(&weak_a))));
+      *weak_a.tail().unwrap().borrow_mut() = Rc::downgrade(&weak_b);
+
+      assert_eq!((Rc::strong_count(&a), Rc::strong_count(&b)), (2, 2));
+      assert_eq!((Rc::strong_count(&weak_a), Rc::strong_count(&weak_b)), (1, 1));
+      assert_eq!((Rc::weak_count(&weak_a), Rc::weak_count(&weak_b)), (1, 1));
+      link(&a, &Rc::new(Nil));
+    }
+
+    #[test]
+    fn weak_tail_cycle_is_freed() {
+      let a = Rc::new(WeakCycList::Cons(5, RefCell::new(Weak::new())));
+      let b = Rc::new(WeakCycList::Cons(10, RefCell::new(Rc::downgrade(&a))));
+      *a.tail().unwrap().borrow_mut() = Rc::downgrade(&b);
+      let (probe_a, probe_b) = (Rc::downgrade(&a), Rc::downgrade(&b));
+
+      drop(a);
+      // b's tail now points at a freed node
+      assert!(b.tail().unwrap().borrow().upgrade().is_none());
+      drop(b);
+      assert!(probe_a.upgrade().is_none());
+      assert!(probe_b.upgrade().is_none());
+    }
+
+    #[test]
+    fn set_tail_refuses_to_close_a_cycle() {
+      let a = cons(5, &Rc::new(Nil));
+      let b = cons(10, &a);
+      assert_eq!(a.set_tail(Rc::clone(&b)), Err(CycleError::ClosesCycle));
+      assert_eq!(a.set_tail(Rc::clone(&a)), Err(CycleError::ClosesCycle));
+      assert!(!has_cycle(&b));
+      assert_eq!(Rc::strong_count(&b), 1);
+    }
+
+    #[test]
+    fn set_tail_relinks_acyclic_lists() {
+      let a = cons(5, &Rc::new(Nil));
+      let b = cons(10, &a);
+      let c = cons(15, &Rc::new(Nil));
+      assert_eq!(b.set_tail(Rc::clone(&c)), Ok(()));
+      assert_eq!(b.display_safe(), "10 -> 15 -> Nil");
+      assert_eq!(Rc::strong_count(&a), 1);
+      assert_eq!(Rc::new(Nil).set_tail(a), Err(CycleError::NoTail));
+    }
+
+    #[test]
+    fn force_set_tail_still_leaks() {
+      let leaked = crate::leak::leak_check(|| {
+        let a = cons(5, &Rc::new(Nil));
+        let b = cons(10, &a);
+        a.force_set_tail(Rc::clone(&b)).unwrap();
+        assert!(has_cycle(&a));
+        vec![a, b]
+      });
+      assert_eq!(leaked, vec![0, 1]);
+    }
+
+    // cycle_reference's steps, with the count at each point it prints one.
+    // The cycle is left in place, so the two nodes leak like in the demo
+    fn count_checkpoints() -> Vec<(String, usize)> {
+      let a = build_a();
+      let mut checkpoints = vec![checkpoint("a initial rc count", &a)];
+      let b = link_b_to_a(&a);
+      checkpoints.push(checkpoint("a rc count after b creation", &a));
+      checkpoints.push(checkpoint("b initial rc count", &b));
+      close_cycle(&a, &b);
+      checkpoints.push(checkpoint("b rc count after changing a", &b));
+      checkpoints.push(checkpoint("a rc count after changing a", &a));
+      checkpoints
+    }
+
+    #[test]
+    fn count_checkpoints_follow_the_demo() {
+      let counts: Vec<usize> = count_checkpoints().into_iter().map(|(_, count)| count).collect();
+      assert_eq!(counts, vec![1, 2, 1, 2, 2]);
+    }
+
+    #[test]
+    fn count_checkpoints_are_labelled_like_the_output() {
+      let checkpoints = count_checkpoints();
+      assert_eq!(checkpoints[0], ("a initial rc count".to_string(), 1));
+      assert_eq!(checkpoints[3], ("b rc count after changing a".to_string(), 2));
+    }
+
+    #[test]
+    fn demo_steps_build_the_cycle() {
+      let a = build_a();
+      assert_eq!(a.display_safe(), "5 -> Nil");
+      let b = link_b_to_a(&a);
+      assert_eq!(b.display_safe(), "10 -> 5 -> Nil");
+      close_cycle(&a, &b);
+      assert_eq!(shape(&a), ListShape::Cyclic { prefix_len: 0, cycle_len: 2 });
+      link(&a, &Rc::new(Nil));
+    }
+
+    #[test]
+    fn cycle_demo_report_proves_the_leak() {
+      assert_eq!(run_cycle_demo(), CycleDemoReport {
+        strong_before_drop: (2, 2),
+        strong_after_drop: (1, 1),
+        a_leaked: true,
+        b_leaked: true
+      });
+    }
+
+    #[test]
+    fn weak_cycle_demo_report_shows_deallocation() {
+      assert_eq!(run_weak_cycle_demo(), CycleDemoReport {
+        strong_before_drop: (1, 1),
+        strong_after_drop: (0, 0),
+        a_leaked: false,
+        b_leaked: false
+      });
+    }
+
+    #[test]
+    fn iter_acyclic_list_in_order() {
+      let c = cons(15, &Rc::new(Nil));
+      let b = cons(10, &c);
+      let a = cons(5, &b);
+      let mut values = a.iter();
+      assert_eq!(values.by_ref().collect::<Vec<_>>(), vec![5, 10, 15]);
+      assert!(!values.stopped_on_cycle());
+      assert_eq!(a.take_limited(2), vec![5, 10]);
+      assert_eq!(a.take_limited(10), vec![5, 10, 15]);
+    }
+
+    #[test]
+    fn iter_stops_on_the_demo_cycle() {
+      let a = cons(5, &Rc::new(Nil));
+      let b = cons(10, &a);
+      link(&a, &b);
+
+      let mut values = a.iter();
+      assert_eq!(values.by_ref().collect::<Vec<_>>(), vec![5, 10]);
+      assert!(values.stopped_on_cycle());
+      assert_eq!(values.next(), None);
+      assert_eq!(b.iter().sum::<i32>(), 15);
+      assert_eq!(a.take_limited(5), vec![5, 10, 5, 10, 5]);
+      link(&a, &Rc::new(Nil));
+    }
+
+    #[test]
+    fn iter_leaves_counts_unchanged() {
+      let a = cons(5, &Rc::new(Nil));
+      let b = cons(10, &a);
+      link(&a, &b);
+      let counts = (Rc::strong_count(&a), Rc::strong_count(&b));
+
+      let mut values = b.iter();
+      values.next();
+      // the iterator holds the next node while it's alive
+      assert_eq!(Rc::strong_count(&a), counts.0 + 1);
+      drop(values);
+      b.iter().for_each(drop);
+      assert_eq!((Rc::strong_count(&a), Rc::strong_count(&b)), counts);
+      link(&a, &Rc::new(Nil));
+    }
+
+    fn count(dot: &str, needle: &str) -> usize {
+      dot.lines().filter(|line| line.contains(needle)).count()
+    }
+
+    #[test]
+    fn dot_before_the_cycle() {
+      let a = cons(5, &Rc::new(Nil));
+      let b = cons(10, &a);
+      let dot = to_dot(&[("a", &a), ("b", &b)]);
+      assert_eq!(count(&dot, "[label="), 3);
+      assert_eq!(count(&dot, "color=red"), 0);
+      assert!(dot.contains("[label=\"5 (rc=2)\"]"));
+      assert!(dot.contains("[label=\"10 (rc=1)\"]"));
+    }
+
+    #[test]
+    fn dot_after_the_cycle() {
+      let a = cons(5, &Rc::new(Nil));
+      let b = cons(10, &a);
+      link(&a, &b);
+      let dot = to_dot(&[("a", &a), ("b", &b)]);
+      assert_eq!(count(&dot, "[label="), 2);
+      assert_eq!(count(&dot, "color=red"), 1);
+      assert!(dot.contains("[label=\"10 (rc=2)\"]"));
+      // the edge from b back to a closes the cycle
+      assert!(dot.contains("n1 -> n0 [color=red];"));
+      link(&a, &Rc::new(Nil));
+    }
+
+    #[test]
+    fn shape_of_acyclic_lists() {
+      let a = cons(5, &Rc::new(Nil));
+      let b = cons(10, &a);
+      assert_eq!(shape(&b), ListShape::Acyclic { len: 2 });
+      assert_eq!(shape(&Rc::new(Nil)), ListShape::Acyclic { len: 0 });
+      assert_eq!(shape(&b).to_string(), "Acyclic{2}");
+    }
+
+    #[test]
+    fn shape_of_the_demo_cycle() {
+      let a = cons(5, &Rc::new(Nil));
+      let b = cons(10, &a);
+      link(&a, &b);
+      assert_eq!(shape(&a), ListShape::Cyclic { prefix_len: 0, cycle_len: 2 });
+      assert_eq!(shape(&a).to_string(), "Cyclic{0, 2}");
+      link(&a, &Rc::new(Nil));
+    }
+
+    #[test]
+    fn shape_of_a_rho() {
+      // 1 -> 2 -> 3 -> (4 -> 5 -> 6 -> 7 -> back to 4)
+      let loop_end = cons(7, &Rc::new(Nil));
+      let mut node = Rc::clone(&loop_end);
+      for value in (1..=6).rev() {
+        node = cons(value, &node);
+      }
+      let loop_start = advance(&advance(&advance(&node).unwrap()).unwrap()).unwrap();
+      link(&loop_end, &loop_start);
+
+      assert_eq!(shape(&node), ListShape::Cyclic { prefix_len: 3, cycle_len: 4 });
+      assert_eq!(shape(&loop_start), ListShape::Cyclic { prefix_len: 0, cycle_len: 4 });
+      link(&loop_end, &Rc::new(Nil));
+    }
+  }
+}
+
+pub mod parent_child {
+  /// The fix for trees: parents own their children through Rc<T>, while
+  /// children refer to their parent through Weak<T>. Dropping a branch
+  /// frees its children, but a leaf never keeps its branch alive.
+  /// The Node type is the one from the `tree` module.
+  use std::rc::{Rc, Weak};
+  pub use crate::tree::Node;
+
+  pub fn attach(parent: &Rc<Node>, child: Rc<Node>) {
+    crate::tree::add_child(parent, child);
+  }
+
+  /// Clears both directions: the parent forgets the child and the child
+  /// forgets its parent, so the child lives only as long as its own Rc<T>s.
+  pub fn detach(child: &Rc<Node>) {
+    if let Some(parent) = child.parent() {
+      parent.children.borrow_mut().retain(|sibling| !Rc::ptr_eq(sibling, child));
+    }
+    *child.parent.borrow_mut() = Weak::new();
+  }
+
+  /// (label, strong, weak, parent value) of the node inspected at each stage.
+  pub type Checkpoint = (&'static str, usize, usize, Option<i32>);
+
+  fn checkpoint(label: &'static str, node: &Rc<Node>) -> Checkpoint {
+    (label, Rc::strong_count(node), Rc::weak_count(node), node.parent().map(|parent| parent.value))
+  }
+
+  /// The Book's leaf/branch walkthrough, with the counts recorded.
+  pub fn leaf_branch_checkpoints() -> Vec<Checkpoint> {
+    let mut checkpoints = vec![];
+    let leaf = Node::new(3);
+    checkpoints.push(checkpoint("leaf", &leaf));
+
+    {
+      let branch = Node::new(5);
+      attach(&branch, Rc::clone(&leaf));
+      checkpoints.push(checkpoint("branch", &branch));
+      checkpoints.push(checkpoint("leaf in branch", &leaf));
+    }
+
+    // the branch is gone, its Weak<T> back pointer can't be upgraded
+    checkpoints.push(checkpoint("leaf after branch", &leaf));
+    checkpoints
+  }
+
+  #[cfg(test)]
+  mod tests {
+    use super::*;
+
+    #[test]
+    fn checkpoints_match_the_book() {
+      assert_eq!(leaf_branch_checkpoints(), vec![
+        ("leaf", 1, 0, None),
+        ("branch", 1, 1, None),
+        ("leaf in branch", 2, 0, Some(5)),
+        ("leaf after branch", 1, 0, None)
+      ]);
+    }
+
+    #[test]
+    fn parent_is_gone_after_branch_drops() {
+      let leaf = Node::new(3);
+      let branch = Node::new(5);
+      attach(&branch, Rc::clone(&leaf));
+      assert_eq!(leaf.parent().map(|parent| parent.value), Some(5));
+      drop(branch);
+      assert!(leaf.parent().is_none());
+    }
+
+    #[test]
+    fn detach_clears_both_directions() {
+      let branch = Node::new(5);
+      let leaf = Node::new(3);
+      attach(&branch, Rc::clone(&leaf));
+      attach(&branch, Node::new(4));
+
+      detach(&leaf);
+      assert!(leaf.parent().is_none());
+      assert_eq!(branch.values(), vec![5, 4]);
+      assert_eq!(Rc::weak_count(&branch), 1);
+
+      // nothing but our handle owns the leaf now
+      let probe = Rc::downgrade(&leaf);
+      drop(leaf);
+      assert!(probe.upgrade().is_none());
+    }
   }
-}
\ No newline at end of file
+}
@@ -0,0 +1,98 @@
+/// Rc<T> isn't only for structs: Rc<str> and Rc<[T]> share string and
+/// slice data behind a single allocation. Cloning the handle copies a
+/// pointer and bumps the count, the characters or elements are never copied.
+use std::collections::HashSet;
+use std::rc::Rc;
+
+/// Deduplicates the input so equal strings share one allocation.
+/// The output keeps the input order, one handle per input string.
+pub fn intern(strings: Vec<String>) -> Vec<Rc<str>> {
+  let mut pool: HashSet<Rc<str>> = HashSet::new();
+  strings
+    .into_iter()
+    .map(|s| {
+      // Rc<str> borrows as str, so the lookup needs no allocation
+      if let Some(existing) = pool.get(s.as_str()) {
+        return Rc::clone(existing);
+      }
+      let interned: Rc<str> = Rc::from(s);
+      pool.insert(Rc::clone(&interned));
+      interned
+    })
+    .collect()
+}
+
+/// Two handles to the same, immutable slice.
+pub fn share_slice(v: Vec<i32>) -> (Rc<[i32]>, Rc<[i32]>) {
+  let shared: Rc<[i32]> = Rc::from(v);
+  (Rc::clone(&shared), shared)
+}
+
+/// Clone-on-write: `Rc::make_mut` hands out `&mut` to the data, cloning
+/// it first only when other owners exist, so they keep the old version.
+pub type SharedConfig = Rc<Vec<i32>>;
+
+/// Applies `f` to this owner's config, returning whether a clone happened.
+pub fn update(config: &mut SharedConfig, f: impl FnOnce(&mut Vec<i32>)) -> bool {
+  let before = Rc::as_ptr(config);
+  f(Rc::make_mut(config));
+  before != Rc::as_ptr(config)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn strings(words: &[&str]) -> Vec<String> {
+    words.iter().map(|word| word.to_string()).collect()
+  }
+
+  #[test]
+  fn duplicates_collapse_to_one_allocation() {
+    let interned = intern(strings(&["rc", "arc", "rc", "rc"]));
+    assert_eq!(&*interned[0], "rc");
+    assert!(Rc::ptr_eq(&interned[0], &interned[2]));
+    assert!(Rc::ptr_eq(&interned[0], &interned[3]));
+    // the pool is gone, only the three output handles remain
+    assert_eq!(Rc::strong_count(&interned[0]), 3);
+  }
+
+  #[test]
+  fn distinct_strings_stay_separate() {
+    let interned = intern(strings(&["rc", "arc"]));
+    assert!(!Rc::ptr_eq(&interned[0], &interned[1]));
+    assert_eq!(Rc::strong_count(&interned[0]), 1);
+    assert_eq!(Rc::strong_count(&interned[1]), 1);
+  }
+
+  #[test]
+  fn slice_handles_are_pointer_equal() {
+    let (first, second) = share_slice(vec![1, 2, 3]);
+    assert!(Rc::ptr_eq(&first, &second));
+    assert_eq!(&*first, &[1, 2, 3]);
+    assert_eq!(Rc::strong_count(&first), 2);
+  }
+
+  #[test]
+  fn unique_config_updates_in_place() {
+    let mut config: SharedConfig = Rc::new(vec![1, 2]);
+    let cloned = update(&mut config, |values| values.push(3));
+    assert!(!cloned);
+    assert_eq!(*config, vec![1, 2, 3]);
+  }
+
+  #[test]
+  fn shared_config_clones_on_write() {
+    let mut component_a: SharedConfig = Rc::new(vec![1, 2]);
+    let component_b = Rc::clone(&component_a);
+
+    let cloned = update(&mut component_a, |values| values[0] = 10);
+    assert!(cloned);
+    assert_eq!(*component_a, vec![10, 2]);
+    assert_eq!(*component_b, vec![1, 2]);
+    assert_eq!(Rc::strong_count(&component_b), 1);
+
+    // component_a now owns its copy alone, the next update is in place
+    assert!(!update(&mut component_a, |values| values[1] = 20));
+  }
+}
@@ -0,0 +1,132 @@
+/// The thread-safe sibling of tree::Node: Arc<T> instead of Rc<T>,
+/// sync::Weak<T> instead of rc::Weak<T>, RwLock<T> instead of RefCell<T>.
+/// The ownership story is the same, parents own children and children
+/// only point back weakly, but now several threads may insert at once.
+use std::sync::{Arc, RwLock, Weak};
+use std::thread;
+
+#[derive(Debug)]
+pub struct SyncNode {
+  pub value: i32,
+  pub parent: RwLock<Weak<SyncNode>>,
+  pub children: RwLock<Vec<Arc<SyncNode>>>
+}
+
+impl SyncNode {
+  pub fn new(value: i32) -> Arc<SyncNode> {
+    Arc::new(SyncNode {
+      value,
+      parent: RwLock::new(Weak::new()),
+      children: RwLock::new(vec![])
+    })
+  }
+
+  pub fn parent(&self) -> Option<Arc<SyncNode>> {
+    self.parent.read().unwrap().upgrade()
+  }
+
+  /// Pre-order traversal. Each node's children stay read-locked while its
+  /// subtrees are visited, writers to other nodes carry on meanwhile.
+  pub fn values(&self) -> Vec<i32> {
+    let mut values = vec![self.value];
+    for child in self.children.read().unwrap().iter() {
+      values.extend(child.values());
+    }
+    values
+  }
+
+  pub fn child_count(&self) -> usize {
+    self.children.read().unwrap().len()
+  }
+}
+
+/// Each lock is released before the next is taken, so add_child never
+/// holds two locks at once and can't deadlock against another insert.
+pub fn add_child(parent: &Arc<SyncNode>, child: Arc<SyncNode>) {
+  *child.parent.write().unwrap() = Arc::downgrade(parent);
+  parent.children.write().unwrap().push(child);
+}
+
+/// Splits `values` across `threads` threads, each adding its share as
+/// children of `root`. The order of the children depends on the interleaving.
+pub fn parallel_populate(root: &Arc<SyncNode>, values: Vec<i32>, threads: usize) {
+  let threads = threads.max(1);
+  let mut shares: Vec<Vec<i32>> = vec![vec![]; threads];
+  for (index, value) in values.into_iter().enumerate() {
+    shares[index % threads].push(value);
+  }
+
+  let handles: Vec<_> = shares
+    .into_iter()
+    .map(|share| {
+      let root = Arc::clone(root);
+      thread::spawn(move || {
+        for value in share {
+          add_child(&root, SyncNode::new(value));
+        }
+      })
+    })
+    .collect();
+  for handle in handles {
+    handle.join().unwrap();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::sync::mpsc;
+  use std::time::Duration;
+
+  #[test]
+  fn every_value_becomes_a_child() {
+    for threads in 1..6 {
+      let root = SyncNode::new(0);
+      parallel_populate(&root, (1..=100).collect(), threads);
+      assert_eq!(root.child_count(), 100);
+
+      let mut values = root.values();
+      values.sort();
+      assert_eq!(values, (0..=100).collect::<Vec<i32>>());
+    }
+  }
+
+  #[test]
+  fn reading_while_inserting_finishes() {
+    let root = SyncNode::new(0);
+    let (tx, rx) = mpsc::channel();
+    let reader = {
+      let root = Arc::clone(&root);
+      thread::spawn(move || {
+        let mut seen = vec![];
+        for _ in 0..200 {
+          seen.push(root.values().len());
+        }
+        tx.send(seen).unwrap();
+      })
+    };
+    parallel_populate(&root, (1..=500).collect(), 4);
+
+    // a deadlocked reader would never send
+    let seen = rx.recv_timeout(Duration::from_secs(10)).expect("the reader is stuck");
+    reader.join().unwrap();
+    assert!(seen.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert!(seen.iter().all(|&len| (1..=501).contains(&len)));
+    assert_eq!(root.values().len(), 501);
+  }
+
+  #[test]
+  fn parents_upgrade_like_the_single_threaded_tree() {
+    let branch = SyncNode::new(5);
+    let leaf = SyncNode::new(3);
+    assert!(leaf.parent().is_none());
+
+    add_child(&branch, Arc::clone(&leaf));
+    assert_eq!(leaf.parent().map(|parent| parent.value), Some(5));
+    assert_eq!(Arc::strong_count(&branch), 1);
+    assert_eq!(Arc::weak_count(&branch), 1);
+
+    drop(branch);
+    assert!(leaf.parent().is_none());
+  }
+}
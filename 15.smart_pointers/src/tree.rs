@@ -0,0 +1,119 @@
+/// A tree where parents own their children through Rc<T> and children
+/// only refer back to their parent through Weak<T>. A parent dropping
+/// should free its children, but a child shouldn't keep its parent
+/// alive, so the back pointer can't be an Rc<T> without creating a cycle.
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+#[derive(Debug)]
+pub struct Node {
+  pub value: i32,
+  pub parent: RefCell<Weak<Node>>,
+  pub children: RefCell<Vec<Rc<Node>>>
+}
+
+impl Node {
+  pub fn new(value: i32) -> Rc<Node> {
+    Rc::new(Node {
+      value,
+      parent: RefCell::new(Weak::new()),
+      children: RefCell::new(vec![])
+    })
+  }
+
+  /// None once the parent has been dropped (or if there never was one).
+  pub fn parent(&self) -> Option<Rc<Node>> {
+    self.parent.borrow().upgrade()
+  }
+
+  /// Number of live ancestors, the root has depth 0.
+  pub fn depth(&self) -> usize {
+    let mut depth = 0;
+    let mut current = self.parent();
+    while let Some(node) = current {
+      depth += 1;
+      current = node.parent();
+    }
+    depth
+  }
+
+  /// Walks up the parent pointers to the topmost live ancestor.
+  pub fn root(self: &Rc<Node>) -> Rc<Node> {
+    let mut current = Rc::clone(self);
+    while let Some(parent) = current.parent() {
+      current = parent;
+    }
+    current
+  }
+
+  /// Pre-order traversal: the node first, then each child subtree in order.
+  pub fn values(&self) -> Vec<i32> {
+    let mut values = vec![self.value];
+    for child in self.children.borrow().iter() {
+      values.extend(child.values());
+    }
+    values
+  }
+}
+
+/// Wires both directions: the parent owns the child, the child points back weakly.
+pub fn add_child(parent: &Rc<Node>, child: Rc<Node>) {
+  *child.parent.borrow_mut() = Rc::downgrade(parent);
+  parent.children.borrow_mut().push(child);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn sample() -> (Rc<Node>, Rc<Node>, Rc<Node>) {
+    let root = Node::new(1);
+    let branch = Node::new(2);
+    let leaf = Node::new(3);
+    add_child(&branch, Rc::clone(&leaf));
+    add_child(&root, Rc::clone(&branch));
+    add_child(&root, Node::new(4));
+    (root, branch, leaf)
+  }
+
+  #[test]
+  fn structure_queries() {
+    let (root, branch, leaf) = sample();
+    assert_eq!(root.values(), vec![1, 2, 3, 4]);
+    assert_eq!(branch.values(), vec![2, 3]);
+    assert_eq!(root.depth(), 0);
+    assert_eq!(leaf.depth(), 2);
+    assert!(Rc::ptr_eq(&leaf.root(), &root));
+    assert!(Rc::ptr_eq(&root.root(), &root));
+  }
+
+  #[test]
+  fn parent_upgrade_follows_parent_lifetime() {
+    let leaf = Node::new(3);
+    assert!(leaf.parent().is_none());
+    {
+      let branch = Node::new(5);
+      add_child(&branch, Rc::clone(&leaf));
+      assert_eq!(leaf.parent().map(|parent| parent.value), Some(5));
+      assert_eq!(Rc::strong_count(&branch), 1);
+      assert_eq!(Rc::weak_count(&branch), 1);
+    }
+    assert!(leaf.parent().is_none());
+    assert_eq!(leaf.depth(), 0);
+  }
+
+  #[test]
+  fn dropping_root_frees_all_children() {
+    let (root, branch, leaf) = sample();
+    let probes: Vec<Weak<Node>> = vec![&root, &branch, &leaf]
+      .into_iter()
+      .map(Rc::downgrade)
+      .collect();
+    drop(branch);
+    drop(leaf);
+    assert!(probes.iter().all(|probe| probe.upgrade().is_some()));
+
+    drop(root);
+    assert!(probes.iter().all(|probe| probe.upgrade().is_none()));
+  }
+}
@@ -0,0 +1,123 @@
+/// A cache that hands out Rc<V> but only stores Weak<V> itself. Holding
+/// a Weak<T> doesn't keep the value alive, so the cache never extends
+/// the lifetime of what it caches: once every outside Rc<V> is dropped
+/// the value is freed and the entry goes dead.
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::rc::{Rc, Weak};
+
+pub struct WeakCache<K, V> {
+  entries: RefCell<HashMap<K, Weak<V>>>
+}
+
+impl<K: Eq + Hash, V> WeakCache<K, V> {
+  pub fn new() -> WeakCache<K, V> {
+    WeakCache {
+      entries: RefCell::new(HashMap::new())
+    }
+  }
+
+  /// Returns the live value for `key`, or builds a new one with `make`
+  /// when the key is missing or its value has already been dropped.
+  ///
+  /// No borrow is held while `make` runs, like in `Memo::get`, so `make`
+  /// may use the cache itself. If it stored a live value for the same key
+  /// meanwhile, that one wins and the freshly built value is dropped.
+  pub fn get_or_insert(&self, key: K, make: impl FnOnce() -> V) -> Rc<V> {
+    let cached = self.entries.borrow().get(&key).and_then(Weak::upgrade);
+    if let Some(value) = cached {
+      return value;
+    }
+
+    let value = Rc::new(make());
+    // the RefMut only lives for this block
+    let mut entries = self.entries.borrow_mut();
+    if let Some(existing) = entries.get(&key).and_then(Weak::upgrade) {
+      return existing;
+    }
+    entries.insert(key, Rc::downgrade(&value));
+    value
+  }
+
+  /// Removes entries whose value has been dropped.
+  pub fn purge(&self) {
+    self.entries.borrow_mut().retain(|_, value| value.upgrade().is_some());
+  }
+
+  /// Entries whose value is still alive.
+  pub fn live_len(&self) -> usize {
+    self.entries
+      .borrow()
+      .values()
+      .filter(|value| value.strong_count() > 0)
+      .count()
+  }
+
+  /// All stored entries, dead ones included until the next `purge`.
+  pub fn len(&self) -> usize {
+    self.entries.borrow().len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.entries.borrow().is_empty()
+  }
+}
+
+impl<K: Eq + Hash, V> Default for WeakCache<K, V> {
+  fn default() -> WeakCache<K, V> {
+    WeakCache::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::cell::Cell;
+
+  #[test]
+  fn live_entries_are_shared() {
+    let cache = WeakCache::new();
+    let first = cache.get_or_insert("config", || vec![1, 2]);
+    let second = cache.get_or_insert("config", || vec![3, 4]);
+    assert!(Rc::ptr_eq(&first, &second));
+    assert_eq!(*second, vec![1, 2]);
+    // the cache itself holds no strong reference
+    assert_eq!(Rc::strong_count(&first), 2);
+  }
+
+  #[test]
+  fn dropped_values_are_rebuilt_and_purged() {
+    let built = Cell::new(0);
+    let make = || {
+      built.set(built.get() + 1);
+      String::from("expensive")
+    };
+    let cache = WeakCache::new();
+
+    let value = cache.get_or_insert(1, make);
+    cache.get_or_insert(2, make);
+    assert_eq!(built.get(), 2);
+    // nobody kept the value for key 2
+    assert_eq!(cache.live_len(), 1);
+    assert_eq!(cache.len(), 2);
+
+    drop(value);
+    assert_eq!(cache.live_len(), 0);
+    cache.get_or_insert(1, make);
+    assert_eq!(built.get(), 3);
+
+    cache.purge();
+    assert!(cache.is_empty());
+  }
+
+  #[test]
+  fn make_can_use_the_cache() {
+    let cache = WeakCache::new();
+    let base = cache.get_or_insert("base", || 10);
+    let derived = cache.get_or_insert("derived", || *cache.get_or_insert("base", || 0) * 2);
+    assert_eq!(*derived, 20);
+    assert_eq!(cache.live_len(), 2);
+    assert!(Rc::ptr_eq(&base, &cache.get_or_insert("base", || 0)));
+  }
+}
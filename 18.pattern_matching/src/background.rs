@@ -0,0 +1,91 @@
+/// The `if let` cascade as a function: each test only runs when the ones
+/// before it failed, so the order of the branches is the precedence.
+use std::num::ParseIntError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Background {
+  Favorite(String),
+  Green,
+  Purple,
+  Orange,
+  Blue
+}
+
+pub fn choose_background_color(favorite: Option<&str>, is_tuesday: bool, age: Result<u8, ParseIntError>) -> Background {
+  if let Some(color) = favorite {
+    Background::Favorite(color.to_string())
+  } else if is_tuesday {
+    Background::Green
+  } else if let Ok(age) = age {
+    // `age` here is the u8 inside, shadowing the Result
+    if age > 30 {
+      Background::Purple
+    } else {
+      Background::Orange
+    }
+  } else {
+    Background::Blue
+  }
+}
+
+/// The same rules, one arm each. Let chains (`if let Some(c) = favorite
+/// && ...`) would read this way too but need edition 2024, so here the
+/// three inputs are matched as a tuple, with a guard for the age test.
+pub fn choose_background_color_chained(favorite: Option<&str>, is_tuesday: bool, age: Result<u8, ParseIntError>) -> Background {
+  match (favorite, is_tuesday, age) {
+    (Some(color), _, _) => Background::Favorite(color.to_string()),
+    (None, true, _) => Background::Green,
+    (None, false, Ok(age)) if age > 30 => Background::Purple,
+    (None, false, Ok(_)) => Background::Orange,
+    (None, false, Err(_)) => Background::Blue
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn age(text: &str) -> Result<u8, ParseIntError> {
+    text.parse()
+  }
+
+  #[test]
+  fn favorite_wins_over_everything() {
+    assert_eq!(choose_background_color(Some("yellow"), true, age("34")), Background::Favorite(String::from("yellow")));
+    assert_eq!(choose_background_color(Some("red"), false, age("x")), Background::Favorite(String::from("red")));
+  }
+
+  #[test]
+  fn tuesday_comes_next() {
+    assert_eq!(choose_background_color(None, true, age("34")), Background::Green);
+    assert_eq!(choose_background_color(None, true, age("x")), Background::Green);
+  }
+
+  #[test]
+  fn then_the_age() {
+    assert_eq!(choose_background_color(None, false, age("31")), Background::Purple);
+    assert_eq!(choose_background_color(None, false, age("30")), Background::Orange);
+    assert_eq!(choose_background_color(None, false, age("0")), Background::Orange);
+  }
+
+  #[test]
+  fn blue_when_nothing_applies() {
+    assert_eq!(choose_background_color(None, false, age("thirty")), Background::Blue);
+    assert_eq!(choose_background_color(None, false, age("256")), Background::Blue);
+  }
+
+  #[test]
+  fn chained_agrees_everywhere() {
+    for favorite in [None, Some("yellow")] {
+      for is_tuesday in [true, false] {
+        for text in ["x", "25", "30", "31", "200"] {
+          assert_eq!(
+            choose_background_color_chained(favorite, is_tuesday, age(text)),
+            choose_background_color(favorite, is_tuesday, age(text)),
+            "{:?}, {}, {:?}", favorite, is_tuesday, text
+          );
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,106 @@
+// Binding modes. A pattern binds by value unless told otherwise: `ref`
+// borrows the matched part and `ref mut` borrows it mutably. Matching on
+// a reference does the same without the keywords, "match ergonomics":
+// through a `&` every binding becomes a `ref`, through a `&mut` a
+// `ref mut`.
+
+/// The total length of `values`, which is still whole afterwards since
+/// nothing is moved out of it.
+// takes `&Vec` on purpose, to show the Vec itself survives
+#[allow(clippy::ptr_arg)]
+pub fn sum_without_moving(values: &Vec<String>) -> usize {
+  let mut total = 0;
+  for value in values {
+    // `*value` is a String in place, `ref` borrows it rather than moving it
+    match *value {
+      ref s if s.is_empty() => {},
+      ref s => total += s.len()
+    }
+  }
+  total
+}
+
+fn capitalize(s: &mut String) {
+  if let Some(first) = s.chars().next() {
+    let upper: String = first.to_uppercase().collect();
+    s.replace_range(..first.len_utf8(), &upper);
+  }
+}
+
+/// Uppercases the first letter of the String in place, spelled out with
+/// `ref mut`.
+pub fn capitalize_first(opt: &mut Option<String>) {
+  if let Some(ref mut s) = *opt {
+    capitalize(s);
+  }
+}
+
+/// The same, with match ergonomics: matching `&mut Option<String>`
+/// against `Some(s)` makes `s` a `&mut String`.
+pub fn capitalize_first_ergonomic(opt: &mut Option<String>) {
+  if let Some(s) = opt {
+    capitalize(s);
+  }
+}
+
+/// Takes the String out of `opt` if it has at least `min` chars, leaving
+/// None behind; a shorter one stays where it is.
+pub fn take_if_long(opt: &mut Option<String>, min: usize) -> Option<String> {
+  match opt {
+    // `s` is only borrowed for the guard, so `opt` is free again in the arm
+    Some(s) if s.chars().count() >= min => opt.take(),
+    _ => None
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn the_vec_survives() {
+    let values = vec![String::from("pattern"), String::new(), String::from("ref")];
+    assert_eq!(sum_without_moving(&values), 10);
+    assert_eq!(values, ["pattern", "", "ref"]);
+    assert_eq!(sum_without_moving(&vec![]), 0);
+  }
+
+  #[test]
+  fn capitalized_in_place() {
+    let mut explicit = Some(String::from("élan"));
+    capitalize_first(&mut explicit);
+    assert_eq!(explicit.as_deref(), Some("Élan"));
+
+    let mut ergonomic = Some(String::from("match"));
+    let buffer = ergonomic.as_ref().unwrap().as_ptr();
+    capitalize_first_ergonomic(&mut ergonomic);
+    assert_eq!(ergonomic.as_deref(), Some("Match"));
+    // the same String, changed where it was
+    assert_eq!(ergonomic.as_ref().unwrap().as_ptr(), buffer);
+  }
+
+  #[test]
+  fn capitalizing_nothing() {
+    let mut none = None;
+    capitalize_first(&mut none);
+    capitalize_first_ergonomic(&mut none);
+    assert_eq!(none, None);
+    let mut empty = Some(String::new());
+    capitalize_first(&mut empty);
+    assert_eq!(empty.as_deref(), Some(""));
+  }
+
+  #[test]
+  fn takes_only_long_strings() {
+    let mut short = Some(String::from("abc"));
+    assert_eq!(take_if_long(&mut short, 4), None);
+    assert_eq!(short.as_deref(), Some("abc"));
+
+    let mut long = Some(String::from("abcd"));
+    assert_eq!(take_if_long(&mut long, 4).as_deref(), Some("abcd"));
+    assert_eq!(long, None);
+
+    let mut none: Option<String> = None;
+    assert_eq!(take_if_long(&mut none, 0), None);
+  }
+}
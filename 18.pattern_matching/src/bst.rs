@@ -0,0 +1,123 @@
+/// A binary search tree built like the cons list: a node holds its
+/// children in Boxes, and the empty tree is its own variant. Every
+/// operation is a match on the node, and inserting one on where the new
+/// value goes relative to it.
+use std::cmp::Ordering;
+use std::iter::FromIterator;
+
+#[derive(Debug, PartialEq)]
+pub enum Bst {
+  Leaf,
+  Node { value: i32, left: Box<Bst>, right: Box<Bst> }
+}
+
+use Bst::{Leaf, Node};
+
+impl Bst {
+  /// Takes the tree apart and builds it again with `v` in it; a value
+  /// that's already there leaves the tree as it was.
+  pub fn insert(self, v: i32) -> Bst {
+    match self {
+      Leaf => Node { value: v, left: Box::new(Leaf), right: Box::new(Leaf) },
+      Node { value, left, right } => match v.cmp(&value) {
+        Ordering::Less => Node { value, left: Box::new(left.insert(v)), right },
+        Ordering::Greater => Node { value, left, right: Box::new(right.insert(v)) },
+        Ordering::Equal => Node { value, left, right }
+      }
+    }
+  }
+
+  pub fn contains(&self, v: i32) -> bool {
+    match self {
+      Leaf => false,
+      Node { value, left, right } => match v.cmp(value) {
+        Ordering::Less => left.contains(v),
+        Ordering::Greater => right.contains(v),
+        Ordering::Equal => true
+      }
+    }
+  }
+
+  /// Nodes on the longest path from the root down, 0 for an empty tree.
+  pub fn height(&self) -> usize {
+    match self {
+      Leaf => 0,
+      Node { left, right, .. } => 1 + left.height().max(right.height())
+    }
+  }
+
+  pub fn in_order(&self) -> Vec<i32> {
+    match self {
+      Leaf => vec![],
+      Node { value, left, right } => {
+        let mut values = left.in_order();
+        values.push(*value);
+        values.extend(right.in_order());
+        values
+      }
+    }
+  }
+}
+
+impl FromIterator<i32> for Bst {
+  fn from_iter<I: IntoIterator<Item = i32>>(iter: I) -> Bst {
+    iter.into_iter().fold(Leaf, Bst::insert)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn duplicates_change_nothing() {
+    let once: Bst = vec![5, 3, 8].into_iter().collect();
+    let twice: Bst = vec![5, 3, 8, 3, 5, 8].into_iter().collect();
+    assert_eq!(once, twice);
+    assert_eq!(twice.in_order(), [3, 5, 8]);
+  }
+
+  #[test]
+  fn in_order_is_sorted() {
+    let shuffled = vec![42, 7, 19, -3, 88, 0, 56, 23, 7, 91, -40, 11];
+    let tree: Bst = shuffled.iter().copied().collect();
+    let mut expected = shuffled.clone();
+    expected.sort_unstable();
+    expected.dedup();
+    assert_eq!(tree.in_order(), expected);
+  }
+
+  #[test]
+  fn contains_hits_and_misses() {
+    let tree: Bst = vec![50, 30, 70, 20, 40, 60, 80].into_iter().collect();
+    for v in &[50, 20, 40, 80] {
+      assert!(tree.contains(*v), "{}", v);
+    }
+    for v in &[0, 35, 65, 100] {
+      assert!(!tree.contains(*v), "{}", v);
+    }
+    assert_eq!(tree.height(), 3);
+  }
+
+  #[test]
+  fn sorted_inserts_make_a_list() {
+    let tree: Bst = (1..=20).collect();
+    assert_eq!(tree.height(), 20);
+    // every left child is a Leaf
+    let mut node = &tree;
+    while let Node { left, right, .. } = node {
+      assert_eq!(**left, Leaf);
+      node = right;
+    }
+  }
+
+  #[test]
+  fn empty_tree() {
+    let tree: Bst = std::iter::empty().collect();
+    assert_eq!(tree, Leaf);
+    assert_eq!(tree.height(), 0);
+    assert!(tree.in_order().is_empty());
+    assert!(!tree.contains(0));
+    assert_eq!(tree.insert(1).in_order(), [1]);
+  }
+}
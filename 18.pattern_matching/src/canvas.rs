@@ -0,0 +1,371 @@
+/// Where the messages of the destructuring demo end up: instead of just
+/// printing what each variant holds, a single match turns each one into a
+/// change to the canvas.
+use crate::{Color, Message};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Canvas {
+  pub position: (i32, i32),
+  pub color: Option<Color>,
+  pub log: Vec<String>,
+  pub closed: bool,
+  /// (0, 0) until the first Resize
+  pub size: (u32, u32),
+  /// degrees, always in 0..360
+  pub rotation: i32
+}
+
+impl Canvas {
+  pub fn new() -> Canvas {
+    Canvas::default()
+  }
+
+  /// Moves and rotations are relative, so they add up; a move past the
+  /// edge of i32 stops at the edge. A resize to zero is clamped to 1 in
+  /// that dimension, and colors into their ranges. Once Quit has closed
+  /// the canvas, every message is ignored.
+  pub fn apply(&mut self, msg: Message) {
+    if self.closed {
+      return;
+    }
+    match msg {
+      Message::Quit => self.closed = true,
+      Message::Move { x, y } => {
+        let (at_x, at_y) = self.position;
+        self.position = (at_x.saturating_add(x), at_y.saturating_add(y));
+      },
+      Message::Write(text) => self.log.push(text),
+      Message::ChangeColor(color) => self.color = Some(color.clamped()),
+      Message::Resize { width, height } => self.size = (width.max(1), height.max(1)),
+      Message::Rotate(degrees) => self.rotation = (self.rotation + degrees.rem_euclid(360)) % 360
+    }
+  }
+
+  /// Whether `msg` would move the canvas past the edge of i32.
+  fn moves_too_far(&self, msg: &Message) -> bool {
+    match *msg {
+      Message::Move { x, y } => {
+        let (at_x, at_y) = self.position;
+        at_x.checked_add(x).is_none() || at_y.checked_add(y).is_none()
+      }
+      _ => false
+    }
+  }
+}
+
+pub fn run(messages: Vec<Message>) -> Canvas {
+  let mut canvas = Canvas::new();
+  for msg in messages {
+    canvas.apply(msg);
+  }
+  canvas
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApplyOutcome {
+  Applied,
+  IgnoredAfterQuit,
+  Rejected(String)
+}
+
+/// How many messages of a batch ended up each way.
+#[derive(Debug, Default, PartialEq)]
+pub struct BatchSummary {
+  pub applied: usize,
+  pub ignored: usize,
+  pub rejected: usize
+}
+
+impl BatchSummary {
+  pub fn of(outcomes: &[ApplyOutcome]) -> BatchSummary {
+    let mut summary = BatchSummary::default();
+    for outcome in outcomes {
+      match outcome {
+        ApplyOutcome::Applied => summary.applied += 1,
+        ApplyOutcome::IgnoredAfterQuit => summary.ignored += 1,
+        ApplyOutcome::Rejected(_) => summary.rejected += 1
+      }
+    }
+    summary
+  }
+}
+
+/// Applies every message it can, one outcome per message: nothing gets
+/// through a closed canvas, an empty Write or an out of range color is
+/// rejected, and a rejection doesn't stop the messages after it.
+pub fn process_batch(canvas: &mut Canvas, messages: Vec<Message>) -> Vec<ApplyOutcome> {
+  messages
+    .into_iter()
+    .map(|msg| {
+      let outcome = match &msg {
+        _ if canvas.closed => ApplyOutcome::IgnoredAfterQuit,
+        Message::Write(text) if text.is_empty() => ApplyOutcome::Rejected(String::from("nothing to write")),
+        Message::Move { .. } if canvas.moves_too_far(&msg) => ApplyOutcome::Rejected(String::from("moves off the canvas")),
+        Message::ChangeColor(color) => match color.validate() {
+          Ok(()) => ApplyOutcome::Applied,
+          Err(err) => ApplyOutcome::Rejected(err.to_string())
+        },
+        _ => ApplyOutcome::Applied
+      };
+      if outcome == ApplyOutcome::Applied {
+        canvas.apply(msg);
+      }
+      outcome
+    })
+    .collect()
+}
+
+/// The message that takes back `msg`, when the message alone says how.
+/// Write, ChangeColor, Resize and Quit overwrite state they don't carry,
+/// and a move by `i32::MIN` has no opposite.
+pub fn invert(msg: &Message) -> Option<Message> {
+  match *msg {
+    Message::Move { x, y } => Some(Message::Move { x: x.checked_neg()?, y: y.checked_neg()? }),
+    Message::Rotate(degrees) => Some(Message::Rotate(degrees.checked_neg()?)),
+    Message::Quit | Message::Write(_) | Message::ChangeColor(_) | Message::Resize { .. } => None
+  }
+}
+
+/// A Canvas that remembers what was applied to it.
+#[derive(Debug, Default)]
+pub struct UndoableCanvas {
+  canvas: Canvas,
+  /// Each message, and whether it stopped at the edge, which its inverse
+  /// can't take back
+  history: Vec<(Message, bool)>
+}
+
+impl UndoableCanvas {
+  pub fn new() -> UndoableCanvas {
+    UndoableCanvas::default()
+  }
+
+  pub fn canvas(&self) -> &Canvas {
+    &self.canvas
+  }
+
+  pub fn apply(&mut self, msg: Message) {
+    self.history.push((msg.clone(), self.canvas.moves_too_far(&msg)));
+    self.canvas.apply(msg);
+  }
+
+  /// Takes back the last message, with its inverse when there is one and
+  /// otherwise by replaying everything before it on a fresh canvas.
+  /// Returns false when there is nothing to undo.
+  pub fn undo(&mut self) -> bool {
+    let (last, clamped) = match self.history.pop() {
+      Some(last) => last,
+      None => return false
+    };
+    match invert(&last) {
+      Some(inverse) if !clamped => self.canvas.apply(inverse),
+      _ => self.canvas = run(self.history.iter().map(|(msg, _)| msg.clone()).collect())
+    }
+    true
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn write(text: &str) -> Message {
+    Message::Write(String::from(text))
+  }
+
+  #[test]
+  fn moves_accumulate() {
+    let canvas = run(vec![Message::Move { x: 1, y: 2 }, Message::Move { x: 3, y: -5 }, Message::Move { x: -4, y: 0 }]);
+    assert_eq!(canvas.position, (0, -3));
+  }
+
+  #[test]
+  fn moves_stop_at_the_edge() {
+    let canvas = run(vec![Message::Move { x: i32::MAX, y: i32::MIN }, Message::Move { x: 1, y: -1 }]);
+    assert_eq!(canvas.position, (i32::MAX, i32::MIN));
+    let canvas = run(vec![Message::Move { x: i32::MAX, y: 0 }, Message::Move { x: 1, y: 0 }, Message::Move { x: -1, y: 0 }]);
+    assert_eq!(canvas.position, (i32::MAX - 1, 0));
+
+    let mut batch = Canvas::new();
+    let outcomes = process_batch(&mut batch, vec![Message::Move { x: i32::MAX, y: 0 }, Message::Move { x: 1, y: 0 }]);
+    assert_eq!(outcomes, [ApplyOutcome::Applied, ApplyOutcome::Rejected(String::from("moves off the canvas"))]);
+    assert_eq!(batch.position, (i32::MAX, 0));
+
+    let mut undoable = UndoableCanvas::new();
+    undoable.apply(Message::Move { x: i32::MAX - 1, y: 0 });
+    undoable.apply(Message::Move { x: 5, y: 0 });
+    assert!(undoable.undo());
+    assert_eq!(undoable.canvas().position, (i32::MAX - 1, 0));
+  }
+
+  #[test]
+  fn quit_ignores_the_rest() {
+    let canvas = run(vec![write("kept"), Message::Quit, write("dropped"), Message::Move { x: 1, y: 1 }]);
+    assert_eq!(canvas, Canvas { log: vec![String::from("kept")], closed: true, ..Canvas::new() });
+  }
+
+  #[test]
+  fn writes_keep_their_order() {
+    let canvas = run(vec![write("one"), Message::Move { x: 1, y: 0 }, write("two"), write("three")]);
+    assert_eq!(canvas.log, ["one", "two", "three"]);
+  }
+
+  #[test]
+  fn the_last_color_wins() {
+    let canvas = run(vec![
+      Message::ChangeColor(Color::Rgb(1, 2, 3)),
+      Message::ChangeColor(Color::Hsv(4, 5, 6))
+    ]);
+    assert_eq!(canvas.color, Some(Color::Hsv(4, 5, 6)));
+    assert_eq!(run(vec![]).color, None);
+  }
+
+  #[test]
+  fn rotation_stays_in_a_full_turn() {
+    let rotated = |degrees: &[i32]| run(degrees.iter().map(|&d| Message::Rotate(d)).collect()).rotation;
+    assert_eq!(rotated(&[-90]), 270);
+    assert_eq!(rotated(&[450]), 90);
+    assert_eq!(rotated(&[360]), 0);
+    assert_eq!(rotated(&[270, 180]), 90);
+    assert_eq!(rotated(&[-720, -1]), 359);
+    assert_eq!(rotated(&[i32::MIN, i32::MAX]), (i32::MIN.rem_euclid(360) + i32::MAX.rem_euclid(360)) % 360);
+  }
+
+  #[test]
+  fn resize_clamps_zero_to_one() {
+    assert_eq!(run(vec![Message::Resize { width: 640, height: 480 }]).size, (640, 480));
+    assert_eq!(run(vec![Message::Resize { width: 0, height: 5 }]).size, (1, 5));
+    assert_eq!(run(vec![Message::Resize { width: 0, height: 0 }]).size, (1, 1));
+    assert_eq!(run(vec![]).size, (0, 0));
+  }
+
+  #[test]
+  fn parsed_commands_apply() {
+    let messages = ["resize 0 300", "rotate -90", "rotate 45"].iter().map(|line| line.parse().unwrap()).collect();
+    let canvas = run(messages);
+    assert_eq!((canvas.size, canvas.rotation), ((1, 300), 315));
+  }
+
+  #[test]
+  fn what_inverts() {
+    assert_eq!(invert(&Message::Move { x: 3, y: -4 }), Some(Message::Move { x: -3, y: 4 }));
+    assert_eq!(invert(&Message::Rotate(90)), Some(Message::Rotate(-90)));
+    assert_eq!(invert(&Message::Move { x: i32::MIN, y: 0 }), None);
+    assert_eq!(invert(&write("text")), None);
+    assert_eq!(invert(&Message::ChangeColor(Color::Rgb(1, 2, 3))), None);
+    assert_eq!(invert(&Message::Quit), None);
+  }
+
+  #[test]
+  fn undo_move_and_rotate_restores_the_state() {
+    let mut canvas = UndoableCanvas::new();
+    canvas.apply(Message::Move { x: 2, y: 2 });
+    canvas.apply(Message::Rotate(30));
+    let before = canvas.canvas().clone();
+
+    canvas.apply(Message::Move { x: -7, y: 5 });
+    canvas.apply(Message::Rotate(-100));
+    assert!(canvas.undo());
+    assert!(canvas.undo());
+    assert_eq!(*canvas.canvas(), before);
+  }
+
+  #[test]
+  fn undo_color_replays_the_history() {
+    let mut canvas = UndoableCanvas::new();
+    canvas.apply(Message::ChangeColor(Color::Rgb(1, 2, 3)));
+    canvas.apply(Message::Move { x: 1, y: 0 });
+    canvas.apply(Message::ChangeColor(Color::Hsv(4, 5, 6)));
+    assert!(canvas.undo());
+    assert_eq!(canvas.canvas().color, Some(Color::Rgb(1, 2, 3)));
+    assert_eq!(canvas.canvas().position, (1, 0));
+  }
+
+  #[test]
+  fn nothing_to_undo() {
+    let mut canvas = UndoableCanvas::new();
+    assert!(!canvas.undo());
+    canvas.apply(Message::Quit);
+    assert!(canvas.undo());
+    assert!(!canvas.undo());
+    assert_eq!(*canvas.canvas(), Canvas::new());
+  }
+
+  #[test]
+  fn interleaved_apply_and_undo() {
+    let mut canvas = UndoableCanvas::new();
+    canvas.apply(write("a"));
+    canvas.apply(Message::Move { x: 5, y: 5 });
+    canvas.undo();
+    canvas.apply(write("b"));
+    canvas.apply(Message::Quit);
+    canvas.apply(Message::Move { x: 1, y: 1 });
+    // undoing the move on a closed canvas is as much a no-op as the move was
+    canvas.undo();
+    canvas.undo();
+    canvas.apply(Message::Rotate(-90));
+    canvas.undo();
+    canvas.apply(Message::Resize { width: 2, height: 3 });
+
+    assert_eq!(*canvas.canvas(), Canvas {
+      log: vec![String::from("a"), String::from("b")],
+      size: (2, 3),
+      ..Canvas::new()
+    });
+  }
+
+  #[test]
+  fn batch_outcomes() {
+    let mut canvas = Canvas::new();
+    let outcomes = process_batch(&mut canvas, vec![
+      Message::Move { x: 1, y: 1 },
+      write(""),
+      Message::ChangeColor(Color::Rgb(10, 300, 0)),
+      write("kept"),
+      Message::ChangeColor(Color::Hsv(360, 100, 0)),
+      Message::ChangeColor(Color::Hsv(0, -1, 0)),
+      Message::Quit,
+      Message::Move { x: 5, y: 5 },
+      write("")
+    ]);
+    assert_eq!(outcomes, [
+      ApplyOutcome::Applied,
+      ApplyOutcome::Rejected(String::from("nothing to write")),
+      ApplyOutcome::Rejected(String::from("green is 45 above 255")),
+      ApplyOutcome::Applied,
+      ApplyOutcome::Applied,
+      ApplyOutcome::Rejected(String::from("saturation is 1 below 0")),
+      ApplyOutcome::Applied,
+      ApplyOutcome::IgnoredAfterQuit,
+      ApplyOutcome::IgnoredAfterQuit
+    ]);
+    assert_eq!(canvas, Canvas {
+      position: (1, 1),
+      color: Some(Color::Hsv(360, 100, 0)),
+      log: vec![String::from("kept")],
+      closed: true,
+      ..Canvas::new()
+    });
+    assert_eq!(BatchSummary::of(&outcomes), BatchSummary { applied: 4, ignored: 2, rejected: 3 });
+  }
+
+  #[test]
+  fn summary_counts_add_up() {
+    let mut canvas = Canvas::new();
+    let messages: Vec<Message> = (0..10).map(|i| if i % 3 == 0 { write("") } else { Message::Rotate(i) }).collect();
+    let outcomes = process_batch(&mut canvas, messages);
+    let summary = BatchSummary::of(&outcomes);
+    assert_eq!(summary.applied + summary.ignored + summary.rejected, outcomes.len());
+    assert_eq!(summary, BatchSummary { applied: 6, ignored: 0, rejected: 4 });
+    assert_eq!(canvas.rotation, 1 + 2 + 4 + 5 + 7 + 8);
+    assert_eq!(process_batch(&mut canvas, vec![]), []);
+  }
+
+  #[test]
+  fn out_of_range_colors_are_stored_clamped() {
+    let canvas = run(vec![Message::ChangeColor(Color::Hsv(10, 33, 255))]);
+    assert_eq!(canvas.color, Some(Color::Hsv(10, 33, 100)));
+    let canvas = run(vec![Message::ChangeColor(Color::Rgb(-5, 999, 0))]);
+    assert_eq!(canvas.color, Some(Color::Rgb(0, 255, 0)));
+  }
+}
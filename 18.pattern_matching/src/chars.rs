@@ -0,0 +1,117 @@
+/// Range patterns over char: `'a'..='j'` compares code points, so only
+/// ASCII is covered by the ranges below and everything else, accented
+/// letters and emoji included, falls through to Other.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CharClass {
+  EarlyLowercase,
+  LateLowercase,
+  Uppercase,
+  Digit,
+  Whitespace,
+  Punctuation,
+  Other
+}
+
+pub fn classify_char(c: char) -> CharClass {
+  match c {
+    'a'..='j' => CharClass::EarlyLowercase,
+    'k'..='z' => CharClass::LateLowercase,
+    'A'..='Z' => CharClass::Uppercase,
+    '0'..='9' => CharClass::Digit,
+    ' ' | '\t' | '\n' | '\r' => CharClass::Whitespace,
+    c if c.is_ascii_punctuation() => CharClass::Punctuation,
+    _ => CharClass::Other
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Symbol {
+  Digit(u32),
+  Vowel(char),
+  Consonant(char),
+  Other(char)
+}
+
+/// `@` after a char range or an or-pattern of chars, so the arm gets the
+/// char that matched. The vowels come first and take their letters out
+/// of the consonant ranges.
+pub fn symbol(c: char) -> Symbol {
+  match c {
+    d @ '0'..='9' => Symbol::Digit(d as u32 - '0' as u32),
+    v @ ('a' | 'e' | 'i' | 'o' | 'u' | 'A' | 'E' | 'I' | 'O' | 'U') => Symbol::Vowel(v),
+    l @ ('a'..='z' | 'A'..='Z') => Symbol::Consonant(l),
+    other => Symbol::Other(other)
+  }
+}
+
+pub fn classify_str(s: &str) -> Vec<(char, CharClass)> {
+  s.chars().map(|c| (c, classify_char(c))).collect()
+}
+
+/// How many chars of `s` fell in each class, in declaration order.
+pub fn class_counts(s: &str) -> [usize; 7] {
+  let mut counts = [0; 7];
+  for (_, class) in classify_str(s) {
+    counts[class as usize] += 1;
+  }
+  counts
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn one_of_each() {
+    assert_eq!(classify_str("cqQ7 ,~"), [
+      ('c', CharClass::EarlyLowercase),
+      ('q', CharClass::LateLowercase),
+      ('Q', CharClass::Uppercase),
+      ('7', CharClass::Digit),
+      (' ', CharClass::Whitespace),
+      (',', CharClass::Punctuation),
+      ('~', CharClass::Punctuation)
+    ]);
+    assert_eq!(classify_char('\t'), CharClass::Whitespace);
+    assert_eq!(classify_char('\0'), CharClass::Other);
+  }
+
+  #[test]
+  fn j_and_k_boundary() {
+    assert_eq!(classify_char('j'), CharClass::EarlyLowercase);
+    assert_eq!(classify_char('k'), CharClass::LateLowercase);
+    assert_eq!(classify_char('a'), CharClass::EarlyLowercase);
+    assert_eq!(classify_char('z'), CharClass::LateLowercase);
+  }
+
+  #[test]
+  fn beyond_ascii_is_other() {
+    assert_eq!(classify_char('é'), CharClass::Other);
+    assert_eq!(classify_char('É'), CharClass::Other);
+    assert_eq!(classify_char('🦀'), CharClass::Other);
+    assert_eq!(classify_char('\u{a0}'), CharClass::Other);
+  }
+
+  #[test]
+  fn symbols_keep_the_char() {
+    assert_eq!(symbol('0'), Symbol::Digit(0));
+    assert_eq!(symbol('9'), Symbol::Digit(9));
+    assert_eq!(symbol('a'), Symbol::Vowel('a'));
+    assert_eq!(symbol('U'), Symbol::Vowel('U'));
+    assert_eq!(symbol('b'), Symbol::Consonant('b'));
+    assert_eq!(symbol('z'), Symbol::Consonant('z'));
+    assert_eq!(symbol('A'), Symbol::Vowel('A'));
+    assert_eq!(symbol('Z'), Symbol::Consonant('Z'));
+    assert_eq!(symbol('['), Symbol::Other('['));
+    assert_eq!(symbol('/'), Symbol::Other('/'));
+    assert_eq!(symbol(':'), Symbol::Other(':'));
+    assert_eq!(symbol('é'), Symbol::Other('é'));
+  }
+
+  #[test]
+  fn counts() {
+    assert_eq!(class_counts("Hello, World! 42 🦀"), [2, 6, 2, 2, 3, 2, 1]);
+    assert_eq!(class_counts(""), [0; 7]);
+  }
+}
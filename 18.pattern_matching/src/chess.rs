@@ -0,0 +1,139 @@
+/// Chess squares in algebraic notation, "a1" to "h8". The file and the
+/// rank are each one char, checked against a range pattern and turned
+/// into a 0-based index by subtracting the range's start.
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Square {
+  /// 0 for the a-file through 7 for the h-file
+  pub file: u8,
+  /// 0 for rank 1 through 7 for rank 8
+  pub rank: u8
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SquareColor {
+  Dark,
+  Light
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SquareError {
+  BadFile(char),
+  BadRank(char),
+  /// what stood between the squares of a five-char move instead of a space
+  BadSeparator(char),
+  /// in chars
+  WrongLength(usize)
+}
+
+impl fmt::Display for SquareError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      SquareError::BadFile(c) => write!(f, "{:?} isn't a file, expected a to h", c),
+      SquareError::BadRank(c) => write!(f, "{:?} isn't a rank, expected 1 to 8", c),
+      SquareError::BadSeparator(c) => write!(f, "{:?} can't separate the squares, expected a space", c),
+      SquareError::WrongLength(len) => write!(f, "{} chars is the wrong length", len)
+    }
+  }
+}
+
+impl Square {
+  /// a1 is dark, and the colors alternate along every file and rank.
+  pub fn color(&self) -> SquareColor {
+    match (self.file + self.rank) % 2 {
+      0 => SquareColor::Dark,
+      _ => SquareColor::Light
+    }
+  }
+}
+
+impl fmt::Display for Square {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}{}", (b'a' + self.file) as char, self.rank + 1)
+  }
+}
+
+fn square(file: char, rank: char) -> Result<Square, SquareError> {
+  let file = match file {
+    'a'..='h' => file as u8 - b'a',
+    _ => return Err(SquareError::BadFile(file))
+  };
+  let rank = match rank {
+    '1'..='8' => rank as u8 - b'1',
+    _ => return Err(SquareError::BadRank(rank))
+  };
+  Ok(Square { file, rank })
+}
+
+pub fn parse_square(s: &str) -> Result<Square, SquareError> {
+  match s.chars().collect::<Vec<_>>()[..] {
+    [file, rank] => square(file, rank),
+    ref chars => Err(SquareError::WrongLength(chars.len()))
+  }
+}
+
+/// A move as its two squares, written "e2e4" or "e2 e4".
+pub fn parse_move(s: &str) -> Result<(Square, Square), SquareError> {
+  match s.chars().collect::<Vec<_>>()[..] {
+    [from_file, from_rank, to_file, to_rank] | [from_file, from_rank, ' ', to_file, to_rank] => {
+      Ok((square(from_file, from_rank)?, square(to_file, to_rank)?))
+    }
+    // the right length, so the separator is what's wrong
+    [_, _, separator, _, _] => Err(SquareError::BadSeparator(separator)),
+    ref chars => Err(SquareError::WrongLength(chars.len()))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn corners() {
+    assert_eq!(parse_square("a1"), Ok(Square { file: 0, rank: 0 }));
+    assert_eq!(parse_square("h1"), Ok(Square { file: 7, rank: 0 }));
+    assert_eq!(parse_square("a8"), Ok(Square { file: 0, rank: 7 }));
+    assert_eq!(parse_square("h8"), Ok(Square { file: 7, rank: 7 }));
+    assert_eq!(parse_square("e4").unwrap().to_string(), "e4");
+  }
+
+  #[test]
+  fn bad_characters_are_named() {
+    assert_eq!(parse_square("i4"), Err(SquareError::BadFile('i')));
+    assert_eq!(parse_square("e9"), Err(SquareError::BadRank('9')));
+    assert_eq!(parse_square("E4"), Err(SquareError::BadFile('E')));
+    assert_eq!(parse_square("e0"), Err(SquareError::BadRank('0')));
+    // the file is checked first
+    assert_eq!(parse_square("z9"), Err(SquareError::BadFile('z')));
+    assert_eq!(SquareError::BadRank('9').to_string(), "'9' isn't a rank, expected 1 to 8");
+  }
+
+  #[test]
+  fn wrong_lengths() {
+    assert_eq!(parse_square(""), Err(SquareError::WrongLength(0)));
+    assert_eq!(parse_square("e"), Err(SquareError::WrongLength(1)));
+    assert_eq!(parse_square("e44"), Err(SquareError::WrongLength(3)));
+    assert_eq!(parse_move("e2e"), Err(SquareError::WrongLength(3)));
+    assert_eq!(parse_move("e2  e4"), Err(SquareError::WrongLength(6)));
+  }
+
+  #[test]
+  fn both_move_formats() {
+    let expected = (Square { file: 4, rank: 1 }, Square { file: 4, rank: 3 });
+    assert_eq!(parse_move("e2e4"), Ok(expected));
+    assert_eq!(parse_move("e2 e4"), Ok(expected));
+    assert_eq!(parse_move("e2-e4"), Err(SquareError::BadSeparator('-')));
+    assert_eq!(parse_move("e2xi4"), Err(SquareError::BadSeparator('x')));
+    assert_eq!(SquareError::BadSeparator('-').to_string(), "'-' can't separate the squares, expected a space");
+    assert_eq!(parse_move("e2 i4"), Err(SquareError::BadFile('i')));
+  }
+
+  #[test]
+  fn colors() {
+    assert_eq!(parse_square("a1").unwrap().color(), SquareColor::Dark);
+    assert_eq!(parse_square("h1").unwrap().color(), SquareColor::Light);
+    assert_eq!(parse_square("a8").unwrap().color(), SquareColor::Light);
+    assert_eq!(parse_square("h8").unwrap().color(), SquareColor::Dark);
+  }
+}
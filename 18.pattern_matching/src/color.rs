@@ -0,0 +1,465 @@
+/// The two spaces a Message can carry a color in. Rgb components run
+/// 0..=255; Hsv is a hue in degrees, 0..=360, then saturation and value
+/// as percentages, 0..=100. Conversion clamps components into those
+/// ranges first and rounds to the nearest whole number, so a percentage
+/// step is coarser than an Rgb one and Rgb → Hsv → Rgb can drift further
+/// than the other way around.
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Color {
+  Rgb(i32, i32, i32),
+  Hsv(i32, i32, i32)
+}
+
+#[derive(Debug, PartialEq)]
+pub enum HexError {
+  /// how many digits there were, after any `#`
+  WrongLength(usize),
+  /// `index` counts from the start of the input, `#` included
+  InvalidDigit { index: usize, found: char }
+}
+
+impl fmt::Display for HexError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      HexError::WrongLength(len) => write!(f, "expected 6 hex digits, or 3 after `#`, found {}", len),
+      HexError::InvalidDigit { index, found } => write!(f, "{:?} at {} is not a hex digit", found, index)
+    }
+  }
+}
+
+/// The first component found outside its range, and how far outside.
+#[derive(Debug, PartialEq)]
+pub enum ColorError {
+  Below { component: &'static str, by: u32 },
+  Above { component: &'static str, by: u32, max: i32 }
+}
+
+impl fmt::Display for ColorError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ColorError::Below { component, by } => write!(f, "{} is {} below 0", component, by),
+      ColorError::Above { component, by, max } => write!(f, "{} is {} above {}", component, by, max)
+    }
+  }
+}
+
+fn check(component: &'static str, value: i32, max: i32) -> Result<(), ColorError> {
+  match value {
+    v if v < 0 => Err(ColorError::Below { component, by: v.unsigned_abs() }),
+    v if v > max => Err(ColorError::Above { component, by: (v - max) as u32, max }),
+    _ => Ok(())
+  }
+}
+
+/// The components as stored, in range or not, `rgb(233, 33, 255)` or
+/// `hsv(10, 33%, 100%)`.
+impl fmt::Display for Color {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Color::Rgb(r, g, b) => write!(f, "rgb({}, {}, {})", r, g, b),
+      Color::Hsv(h, s, v) => write!(f, "hsv({}, {}%, {}%)", h, s, v)
+    }
+  }
+}
+
+fn hex_value(digit: u8) -> i32 {
+  match digit {
+    b'0'..=b'9' => i32::from(digit - b'0'),
+    b'a'..=b'f' => i32::from(digit - b'a' + 10),
+    b'A'..=b'F' => i32::from(digit - b'A' + 10),
+    _ => unreachable!("checked by from_hex")
+  }
+}
+
+impl Color {
+  /// Reads `#rrggbb`, `rrggbb` or the short `#rgb`, where each digit
+  /// stands for itself twice, so `#f80` is `#ff8800`.
+  pub fn from_hex(s: &str) -> Result<Color, HexError> {
+    let (digits, offset) = match s.as_bytes() {
+      [b'#', rest @ ..] => (rest, 1),
+      bytes => (bytes, 0)
+    };
+    if let Some(index) = digits.iter().position(|digit| !digit.is_ascii_hexdigit()) {
+      let index = index + offset;
+      // every byte before `index` is ASCII, so it starts a char
+      return Err(HexError::InvalidDigit { index, found: s[index..].chars().next().unwrap() });
+    }
+    let pair = |high: u8, low: u8| hex_value(high) * 16 + hex_value(low);
+    match (offset, digits) {
+      (_, &[r1, r2, g1, g2, b1, b2]) => Ok(Color::Rgb(pair(r1, r2), pair(g1, g2), pair(b1, b2))),
+      (1, &[r, g, b]) => Ok(Color::Rgb(pair(r, r), pair(g, g), pair(b, b))),
+      (_, digits) => Err(HexError::WrongLength(digits.len()))
+    }
+  }
+
+  pub fn validate(&self) -> Result<(), ColorError> {
+    match *self {
+      Color::Rgb(r, g, b) => {
+        check("red", r, 255)?;
+        check("green", g, 255)?;
+        check("blue", b, 255)
+      },
+      Color::Hsv(h, s, v) => {
+        check("hue", h, 360)?;
+        check("saturation", s, 100)?;
+        check("value", v, 100)
+      }
+    }
+  }
+
+  /// The same color with every component pulled into its range.
+  pub fn clamped(&self) -> Color {
+    match *self {
+      Color::Rgb(r, g, b) => Color::Rgb(r.clamp(0, 255), g.clamp(0, 255), b.clamp(0, 255)),
+      Color::Hsv(h, s, v) => Color::Hsv(h.clamp(0, 360), s.clamp(0, 100), v.clamp(0, 100))
+    }
+  }
+
+  /// `#rrggbb` in lowercase, converting an Hsv color first.
+  pub fn to_hex(&self) -> String {
+    match *self {
+      Color::Rgb(r, g, b) => format!("#{:02x}{:02x}{:02x}", r.clamp(0, 255), g.clamp(0, 255), b.clamp(0, 255)),
+      Color::Hsv(..) => self.to_rgb().to_hex()
+    }
+  }
+
+  pub fn to_rgb(&self) -> Color {
+    match *self {
+      Color::Rgb(..) => self.clone(),
+      Color::Hsv(h, s, v) => {
+        let (s, v) = (f64::from(s.clamp(0, 100)) / 100.0, f64::from(v.clamp(0, 100)) / 100.0);
+        let hue = f64::from(h.clamp(0, 360) % 360) / 60.0;
+        let chroma = v * s;
+        let second = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+        // which sixth of the color wheel the hue falls in
+        let (r, g, b) = match hue as i32 {
+          0 => (chroma, second, 0.0),
+          1 => (second, chroma, 0.0),
+          2 => (0.0, chroma, second),
+          3 => (0.0, second, chroma),
+          4 => (second, 0.0, chroma),
+          _ => (chroma, 0.0, second)
+        };
+        let m = v - chroma;
+        let channel = |c: f64| ((c + m) * 255.0).round() as i32;
+        Color::Rgb(channel(r), channel(g), channel(b))
+      }
+    }
+  }
+
+  pub fn to_hsv(&self) -> Color {
+    match *self {
+      Color::Hsv(..) => self.clone(),
+      Color::Rgb(r, g, b) => {
+        let [r, g, b] = [r, g, b].map(|c| f64::from(c.clamp(0, 255)) / 255.0);
+        let max = r.max(g).max(b);
+        let delta = max - r.min(g).min(b);
+        let hue = match max {
+          _ if delta == 0.0 => 0.0,
+          m if m == r => 60.0 * ((g - b) / delta).rem_euclid(6.0),
+          m if m == g => 60.0 * ((b - r) / delta + 2.0),
+          _ => 60.0 * ((r - g) / delta + 4.0)
+        };
+        let saturation = if max == 0.0 { 0.0 } else { delta / max };
+        Color::Hsv(hue.round() as i32 % 360, (saturation * 100.0).round() as i32, (max * 100.0).round() as i32)
+      }
+    }
+  }
+}
+
+/// The named colors' Rgb components, one entry per color under its first
+/// name.
+const NAMED: [(&str, (i32, i32, i32)); 17] = [
+  ("black", (0, 0, 0)),
+  ("white", (255, 255, 255)),
+  ("red", (255, 0, 0)),
+  ("lime", (0, 255, 0)),
+  ("blue", (0, 0, 255)),
+  ("yellow", (255, 255, 0)),
+  ("cyan", (0, 255, 255)),
+  ("magenta", (255, 0, 255)),
+  ("gray", (128, 128, 128)),
+  ("silver", (192, 192, 192)),
+  ("maroon", (128, 0, 0)),
+  ("green", (0, 128, 0)),
+  ("navy", (0, 0, 128)),
+  ("olive", (128, 128, 0)),
+  ("purple", (128, 0, 128)),
+  ("teal", (0, 128, 128)),
+  ("orange", (255, 165, 0))
+];
+
+/// Looks a color up by name, ignoring case. Aliases share an arm.
+pub fn by_name(name: &str) -> Option<Color> {
+  let color = match name.to_lowercase().as_str() {
+    "black" => Color::Rgb(0, 0, 0),
+    "white" => Color::Rgb(255, 255, 255),
+    "red" => Color::Rgb(255, 0, 0),
+    "lime" => Color::Rgb(0, 255, 0),
+    "blue" => Color::Rgb(0, 0, 255),
+    "yellow" => Color::Rgb(255, 255, 0),
+    "cyan" | "aqua" => Color::Rgb(0, 255, 255),
+    "magenta" | "fuchsia" => Color::Rgb(255, 0, 255),
+    "gray" | "grey" => Color::Rgb(128, 128, 128),
+    "silver" | "light gray" | "light grey" => Color::Rgb(192, 192, 192),
+    "maroon" => Color::Rgb(128, 0, 0),
+    "green" => Color::Rgb(0, 128, 0),
+    "navy" | "navy blue" => Color::Rgb(0, 0, 128),
+    "olive" => Color::Rgb(128, 128, 0),
+    "purple" => Color::Rgb(128, 0, 128),
+    "teal" => Color::Rgb(0, 128, 128),
+    "orange" => Color::Rgb(255, 165, 0),
+    _ => return None
+  };
+  Some(color)
+}
+
+/// The name in the table closest to `color`, by squared distance in Rgb.
+pub fn nearest_name(color: &Color) -> &'static str {
+  let (r, g, b) = match color.to_rgb().clamped() {
+    Color::Rgb(r, g, b) => (r, g, b),
+    Color::Hsv(..) => unreachable!("to_rgb always gives Rgb")
+  };
+  let mut nearest = ("", i32::MAX);
+  for &(name, (nr, ng, nb)) in NAMED.iter() {
+    let distance = (r - nr).pow(2) + (g - ng).pow(2) + (b - nb).pow(2);
+    if distance < nearest.1 {
+      nearest = (name, distance);
+    }
+  }
+  nearest.0
+}
+
+/// `a` moved a fraction `t` of the way towards `b`, in `a`'s space. A `b`
+/// from the other space is converted first. Hue is interpolated like any
+/// other number, so it goes the long way round past 180 degrees apart.
+pub fn mix(a: &Color, b: &Color, t: f32) -> Color {
+  let t = t.clamp(0.0, 1.0);
+  // in f64, `to - from` can be twice what fits in an i32; the result lies
+  // between the two, so it fits again
+  let lerp = |from: i32, to: i32| {
+    let (from, to) = (f64::from(from), f64::from(to));
+    (from + (to - from) * f64::from(t)).round() as i32
+  };
+  match (a, b) {
+    (&Color::Rgb(r1, g1, b1), &Color::Rgb(r2, g2, b2)) => Color::Rgb(lerp(r1, r2), lerp(g1, g2), lerp(b1, b2)),
+    (&Color::Hsv(h1, s1, v1), &Color::Hsv(h2, s2, v2)) => Color::Hsv(lerp(h1, h2), lerp(s1, s2), lerp(v1, v2)),
+    (Color::Rgb(..), Color::Hsv(..)) => mix(a, &b.to_rgb(), t),
+    (Color::Hsv(..), Color::Rgb(..)) => mix(a, &b.to_hsv(), t)
+  }
+}
+
+/// All of `colors` mixed in equal parts, in the first one's space.
+pub fn average(colors: &[Color]) -> Option<Color> {
+  let (first, rest) = colors.split_first()?;
+  // the running average of n colors takes 1/(n + 1) of the next one
+  let mixed = rest
+    .iter()
+    .enumerate()
+    .fold(first.clone(), |average, (index, color)| mix(&average, color, 1.0 / (index + 2) as f32));
+  Some(mixed)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn close(a: &Color, b: &Color) -> bool {
+    let parts = |color: &Color| match *color {
+      Color::Rgb(x, y, z) | Color::Hsv(x, y, z) => [x, y, z]
+    };
+    parts(a).iter().zip(&parts(b)).all(|(x, y)| (x - y).abs() <= 1)
+  }
+
+  #[test]
+  fn known_pairs() {
+    let pairs = [
+      (Color::Rgb(255, 0, 0), Color::Hsv(0, 100, 100)),
+      (Color::Rgb(0, 255, 0), Color::Hsv(120, 100, 100)),
+      (Color::Rgb(0, 0, 255), Color::Hsv(240, 100, 100)),
+      (Color::Rgb(255, 255, 255), Color::Hsv(0, 0, 100)),
+      (Color::Rgb(0, 0, 0), Color::Hsv(0, 0, 0)),
+      (Color::Rgb(128, 128, 128), Color::Hsv(0, 0, 50))
+    ];
+    for (rgb, hsv) in &pairs {
+      assert_eq!(rgb.to_hsv(), *hsv);
+      assert!(close(&hsv.to_rgb(), rgb), "{:?} gave {:?}", hsv, hsv.to_rgb());
+    }
+  }
+
+  #[test]
+  fn round_trips_within_one() {
+    let colors = [
+      Color::Rgb(233, 33, 255),
+      Color::Rgb(0, 153, 255),
+      Color::Rgb(255, 128, 0),
+      Color::Rgb(51, 102, 153),
+      Color::Hsv(10, 33, 100),
+      Color::Hsv(300, 80, 60),
+      Color::Hsv(200, 100, 40)
+    ];
+    for color in &colors {
+      let back = match color {
+        Color::Rgb(..) => color.to_hsv().to_rgb(),
+        Color::Hsv(..) => color.to_rgb().to_hsv()
+      };
+      assert!(close(&back, color), "{:?} came back as {:?}", color, back);
+    }
+  }
+
+  #[test]
+  fn already_in_the_space_passes_through() {
+    assert_eq!(Color::Rgb(1, 2, 3).to_rgb(), Color::Rgb(1, 2, 3));
+    assert_eq!(Color::Hsv(10, 33, 255).to_hsv(), Color::Hsv(10, 33, 255));
+  }
+
+  #[test]
+  fn hue_360_is_red_again() {
+    assert_eq!(Color::Hsv(360, 100, 100).to_rgb(), Color::Rgb(255, 0, 0));
+  }
+
+  #[test]
+  fn hex_forms() {
+    assert_eq!(Color::from_hex("#e921ff"), Ok(Color::Rgb(233, 33, 255)));
+    assert_eq!(Color::from_hex("e921ff"), Ok(Color::Rgb(233, 33, 255)));
+    assert_eq!(Color::from_hex("#E921FF"), Ok(Color::Rgb(233, 33, 255)));
+    assert_eq!(Color::from_hex("#f80"), Ok(Color::Rgb(255, 136, 0)));
+    assert_eq!(Color::from_hex("#FfF"), Ok(Color::Rgb(255, 255, 255)));
+  }
+
+  #[test]
+  fn hex_errors() {
+    assert_eq!(Color::from_hex(""), Err(HexError::WrongLength(0)));
+    assert_eq!(Color::from_hex("#"), Err(HexError::WrongLength(0)));
+    assert_eq!(Color::from_hex("f80"), Err(HexError::WrongLength(3)));
+    assert_eq!(Color::from_hex("#12345"), Err(HexError::WrongLength(5)));
+    assert_eq!(Color::from_hex("#1234567"), Err(HexError::WrongLength(7)));
+    assert_eq!(Color::from_hex("#12g456"), Err(HexError::InvalidDigit { index: 3, found: 'g' }));
+    assert_eq!(Color::from_hex("12345z"), Err(HexError::InvalidDigit { index: 5, found: 'z' }));
+    assert_eq!(Color::from_hex("#ab\u{e9}"), Err(HexError::InvalidDigit { index: 3, found: '\u{e9}' }));
+    assert_eq!(Color::from_hex("##fff").unwrap_err().to_string(), "'#' at 1 is not a hex digit");
+  }
+
+  #[test]
+  fn hex_round_trips() {
+    for hex in &["#000000", "#ffffff", "#e921ff", "#0a0b0c"] {
+      assert_eq!(Color::from_hex(hex).unwrap().to_hex(), *hex);
+    }
+    assert_eq!(Color::from_hex("#ABC").unwrap().to_hex(), "#aabbcc");
+    assert_eq!(Color::Hsv(0, 100, 100).to_hex(), "#ff0000");
+  }
+
+  #[test]
+  fn in_range_colors_are_valid() {
+    for color in &[Color::Rgb(0, 0, 0), Color::Rgb(255, 255, 255), Color::Hsv(0, 0, 0), Color::Hsv(360, 100, 100)] {
+      assert_eq!(color.validate(), Ok(()));
+      assert_eq!(color.clamped(), *color);
+    }
+  }
+
+  #[test]
+  fn each_component_out_of_range() {
+    let below = |component| Err(ColorError::Below { component, by: 5 });
+    let above = |component, max| Err(ColorError::Above { component, by: 5, max });
+    assert_eq!(Color::Rgb(-5, 0, 0).validate(), below("red"));
+    assert_eq!(Color::Rgb(0, -5, 0).validate(), below("green"));
+    assert_eq!(Color::Rgb(0, 0, -5).validate(), below("blue"));
+    assert_eq!(Color::Rgb(260, 0, 0).validate(), above("red", 255));
+    assert_eq!(Color::Rgb(0, 260, 0).validate(), above("green", 255));
+    assert_eq!(Color::Rgb(0, 0, 260).validate(), above("blue", 255));
+    assert_eq!(Color::Hsv(-5, 0, 0).validate(), below("hue"));
+    assert_eq!(Color::Hsv(0, -5, 0).validate(), below("saturation"));
+    assert_eq!(Color::Hsv(0, 0, -5).validate(), below("value"));
+    assert_eq!(Color::Hsv(365, 0, 0).validate(), above("hue", 360));
+    assert_eq!(Color::Hsv(0, 105, 0).validate(), above("saturation", 100));
+    assert_eq!(Color::Hsv(0, 0, 105).validate(), above("value", 100));
+    assert_eq!(Color::Rgb(i32::MIN, 0, 0).validate(), Err(ColorError::Below { component: "red", by: 1 << 31 }));
+  }
+
+  #[test]
+  fn clamping() {
+    assert_eq!(Color::Rgb(-5, 999, 128).clamped(), Color::Rgb(0, 255, 128));
+    assert_eq!(Color::Hsv(400, -1, 255).clamped(), Color::Hsv(360, 0, 100));
+  }
+
+  #[test]
+  fn display() {
+    assert_eq!(Color::Rgb(233, 33, 255).to_string(), "rgb(233, 33, 255)");
+    assert_eq!(Color::Hsv(10, 33, 255).to_string(), "hsv(10, 33%, 255%)");
+    assert_eq!(Color::Hsv(10, 33, 255).clamped().to_string(), "hsv(10, 33%, 100%)");
+    assert_eq!(Color::Rgb(0, 300, 0).validate().unwrap_err().to_string(), "green is 45 above 255");
+    assert_eq!(Color::Hsv(0, -2, 0).validate().unwrap_err().to_string(), "saturation is 2 below 0");
+  }
+
+  #[test]
+  fn mix_endpoints() {
+    let (a, b) = (Color::Rgb(10, 20, 30), Color::Rgb(200, 100, 0));
+    assert_eq!(mix(&a, &b, 0.0), a);
+    assert_eq!(mix(&a, &b, 1.0), b);
+    assert_eq!(mix(&a, &b, -3.0), a);
+    assert_eq!(mix(&a, &b, 7.5), b);
+    assert_eq!(mix(&Color::Hsv(0, 0, 0), &Color::Hsv(100, 50, 100), 0.5), Color::Hsv(50, 25, 50));
+  }
+
+  #[test]
+  fn mix_at_the_i32_extremes() {
+    let (low, high) = (Color::Rgb(i32::MIN, 0, i32::MAX), Color::Rgb(i32::MAX, 0, i32::MIN));
+    assert_eq!(mix(&low, &high, 0.0), low);
+    assert_eq!(mix(&low, &high, 1.0), high);
+    // halfway between i32::MIN and i32::MAX is -0.5, which rounds away from zero
+    assert_eq!(mix(&low, &high, 0.5), Color::Rgb(-1, 0, -1));
+    assert_eq!(mix(&Color::Hsv(i32::MAX, 0, 0), &Color::Hsv(i32::MIN, 0, 0), 0.25), Color::Hsv(1073741823, 0, 0));
+  }
+
+  #[test]
+  fn black_and_white_make_grey() {
+    assert_eq!(mix(&Color::Rgb(0, 0, 0), &Color::Rgb(255, 255, 255), 0.5), Color::Rgb(128, 128, 128));
+  }
+
+  #[test]
+  fn mixed_spaces_convert_the_second_operand() {
+    let rgb = Color::Rgb(233, 33, 255);
+    let hsv = Color::Hsv(120, 100, 50);
+    assert_eq!(mix(&rgb, &hsv, 0.25), mix(&rgb, &hsv.to_rgb(), 0.25));
+    assert_eq!(mix(&hsv, &rgb, 0.25), mix(&hsv, &rgb.to_hsv(), 0.25));
+    assert!(matches!(mix(&hsv, &rgb, 0.5), Color::Hsv(..)));
+  }
+
+  #[test]
+  fn averages() {
+    assert_eq!(average(&[]), None);
+    assert_eq!(average(&[Color::Hsv(1, 2, 3)]), Some(Color::Hsv(1, 2, 3)));
+    let colors = [Color::Rgb(255, 0, 0), Color::Rgb(0, 255, 0), Color::Rgb(0, 0, 255)];
+    assert!(close(&average(&colors).unwrap(), &Color::Rgb(85, 85, 85)));
+  }
+
+  #[test]
+  fn lookups_by_name() {
+    assert_eq!(by_name("red"), Some(Color::Rgb(255, 0, 0)));
+    assert_eq!(by_name("Grey"), by_name("gray"));
+    assert_eq!(by_name("AQUA"), Some(Color::Rgb(0, 255, 255)));
+    assert_eq!(by_name("fuchsia"), by_name("magenta"));
+    assert_eq!(by_name("light grey"), by_name("silver"));
+    assert_eq!(by_name("chartreuse"), None);
+    assert_eq!(by_name(""), None);
+  }
+
+  #[test]
+  fn the_table_and_the_match_agree() {
+    for &(name, (r, g, b)) in NAMED.iter() {
+      assert_eq!(by_name(name), Some(Color::Rgb(r, g, b)), "{}", name);
+      assert_eq!(nearest_name(&Color::Rgb(r, g, b)), name);
+    }
+  }
+
+  #[test]
+  fn nearest_names() {
+    assert_eq!(nearest_name(&Color::Rgb(250, 5, 3)), "red");
+    assert_eq!(nearest_name(&Color::Rgb(120, 130, 125)), "gray");
+    assert_eq!(nearest_name(&Color::Rgb(240, 160, 20)), "orange");
+    assert_eq!(nearest_name(&Color::Rgb(-40, 10, 140)), "navy");
+    assert_eq!(nearest_name(&Color::Hsv(240, 100, 98)), "blue");
+  }
+}
@@ -0,0 +1,146 @@
+/// `let` only takes irrefutable patterns, but `let ... else` takes a
+/// refutable one: when the pattern doesn't match, the else block runs and
+/// has to leave the function (or loop), so the bindings after it always
+/// exist. Parsing settings like `port=8080` is a chain of such checks.
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+  Debug,
+  Release
+}
+
+impl Mode {
+  pub fn from_name(name: &str) -> Option<Mode> {
+    match name {
+      "debug" => Some(Mode::Debug),
+      "release" => Some(Mode::Release),
+      _ => None
+    }
+  }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Config {
+  pub host: String,
+  pub port: u16,
+  pub mode: Mode
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ConfigError {
+  NotKeyValue(String),
+  UnknownKey(String),
+  DuplicateKey(String),
+  BadPort(String),
+  BadMode(String),
+  Missing(&'static str)
+}
+
+impl fmt::Display for ConfigError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ConfigError::NotKeyValue(arg) => write!(f, "{:?} isn't key=value", arg),
+      ConfigError::UnknownKey(key) => write!(f, "unknown key {:?}", key),
+      ConfigError::DuplicateKey(key) => write!(f, "{} is set more than once", key),
+      ConfigError::BadPort(value) => write!(f, "port {:?} isn't a number from 0 to 65535", value),
+      ConfigError::BadMode(value) => write!(f, "mode {:?} isn't debug or release", value),
+      ConfigError::Missing(key) => write!(f, "{} isn't set", key)
+    }
+  }
+}
+
+/// Sets `slot` unless it was set already: a key given twice is an error
+/// rather than the last one silently winning.
+fn set_once<T>(slot: &mut Option<T>, key: &str, value: T) -> Result<(), ConfigError> {
+  match slot {
+    Some(_) => Err(ConfigError::DuplicateKey(key.to_string())),
+    None => {
+      *slot = Some(value);
+      Ok(())
+    }
+  }
+}
+
+/// `host=`, `port=` and `mode=` arguments, each exactly once, in any order.
+pub fn parse_config(args: &[String]) -> Result<Config, ConfigError> {
+  let (mut host, mut port, mut mode) = (None, None, None);
+  for arg in args {
+    let parts: Vec<&str> = arg.splitn(2, '=').collect();
+    let [name, value] = parts.as_slice() else {
+      return Err(ConfigError::NotKeyValue(arg.clone()));
+    };
+    match *name {
+      "host" => set_once(&mut host, name, value.to_string())?,
+      "port" => {
+        let Ok(number) = value.parse::<u16>() else {
+          return Err(ConfigError::BadPort(value.to_string()));
+        };
+        set_once(&mut port, name, number)?
+      }
+      "mode" => {
+        let Some(parsed) = Mode::from_name(value) else {
+          return Err(ConfigError::BadMode(value.to_string()));
+        };
+        set_once(&mut mode, name, parsed)?
+      }
+      _ => return Err(ConfigError::UnknownKey(name.to_string()))
+    }
+  }
+  let Some(host) = host else {
+    return Err(ConfigError::Missing("host"));
+  };
+  let Some(port) = port else {
+    return Err(ConfigError::Missing("port"));
+  };
+  let Some(mode) = mode else {
+    return Err(ConfigError::Missing("mode"));
+  };
+  Ok(Config { host, port, mode })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+  }
+
+  #[test]
+  fn valid_config() {
+    assert_eq!(parse_config(&args(&["mode=release", "host=example.com", "port=8080"])), Ok(Config {
+      host: String::from("example.com"),
+      port: 8080,
+      mode: Mode::Release
+    }));
+    // only the first `=` splits
+    assert_eq!(parse_config(&args(&["host=a=b", "port=0", "mode=debug"])).unwrap().host, "a=b");
+  }
+
+  #[test]
+  fn each_field_is_checked() {
+    let err = |list: &[&str]| parse_config(&args(list)).unwrap_err();
+    assert_eq!(err(&["host"]), ConfigError::NotKeyValue(String::from("host")));
+    assert_eq!(err(&["colour=red"]), ConfigError::UnknownKey(String::from("colour")));
+    assert_eq!(err(&["port=80a"]), ConfigError::BadPort(String::from("80a")));
+    assert_eq!(err(&["port=65536"]), ConfigError::BadPort(String::from("65536")));
+    assert_eq!(err(&["mode=fast"]), ConfigError::BadMode(String::from("fast")));
+    assert_eq!(err(&["port=1", "mode=debug"]), ConfigError::Missing("host"));
+    assert_eq!(err(&["host=h", "mode=debug"]), ConfigError::Missing("port"));
+    assert_eq!(err(&["host=h", "port=1"]), ConfigError::Missing("mode"));
+    assert_eq!(err(&["port=x"]).to_string(), "port \"x\" isn't a number from 0 to 65535");
+  }
+
+  #[test]
+  fn duplicate_keys_are_an_error() {
+    let err = parse_config(&args(&["host=a", "port=1", "mode=debug", "port=2"])).unwrap_err();
+    assert_eq!(err, ConfigError::DuplicateKey(String::from("port")));
+    assert_eq!(err.to_string(), "port is set more than once");
+  }
+
+  #[test]
+  fn no_arguments() {
+    assert_eq!(parse_config(&[]), Err(ConfigError::Missing("host")));
+  }
+}
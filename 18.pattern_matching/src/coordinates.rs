@@ -0,0 +1,73 @@
+/// Function parameters are patterns too: `(x, y): &(T, T)` takes the
+/// tuple apart right in the signature, for any T that can be displayed.
+/// T needn't be Copy, since matching through the reference binds x and y
+/// as references as well.
+use std::fmt::Display;
+
+pub fn format_coordinates<T: Display>((x, y): &(T, T)) -> String {
+  format!("Current location ({},{})", x, y)
+}
+
+pub fn format_coordinates3<T: Display>((x, y, z): &(T, T, T)) -> String {
+  format!("Current location ({},{},{})", x, y, z)
+}
+
+/// Both tuple shapes behind one method.
+pub trait Coordinates {
+  fn describe(&self) -> String;
+}
+
+impl<T: Display> Coordinates for (T, T) {
+  fn describe(&self) -> String {
+    format_coordinates(self)
+  }
+}
+
+impl<T: Display> Coordinates for (T, T, T) {
+  fn describe(&self) -> String {
+    format_coordinates3(self)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn describe_all(locations: &[&dyn Coordinates]) -> Vec<String> {
+    locations.iter().map(|location| location.describe()).collect()
+  }
+
+  fn describe_one(location: &impl Coordinates) -> String {
+    location.describe()
+  }
+
+  #[test]
+  fn integers() {
+    assert_eq!(format_coordinates(&(3, 5)), "Current location (3,5)");
+    assert_eq!(format_coordinates(&(-3i64, 0)), "Current location (-3,0)");
+  }
+
+  #[test]
+  fn floats_use_the_default_formatting() {
+    assert_eq!(format_coordinates(&(1.5, -0.25)), "Current location (1.5,-0.25)");
+    // a whole f64 prints without a fraction
+    assert_eq!(format_coordinates(&(2.0, 1e21)), "Current location (2,1000000000000000000000)");
+  }
+
+  #[test]
+  fn three_dimensions() {
+    assert_eq!(format_coordinates3(&(1, 2, 3)), "Current location (1,2,3)");
+    assert_eq!(format_coordinates3(&("x", "y", "z")), "Current location (x,y,z)");
+  }
+
+  #[test]
+  fn through_the_trait() {
+    assert_eq!(describe_one(&(7, 8)), "Current location (7,8)");
+    assert_eq!(describe_one(&(0.5, 0.5, 0.5)), "Current location (0.5,0.5,0.5)");
+    assert_eq!(describe_all(&[&(1, 2), &(1, 2, 3), &(String::from("a"), String::from("b"))]), [
+      "Current location (1,2)",
+      "Current location (1,2,3)",
+      "Current location (a,b)"
+    ]);
+  }
+}
@@ -0,0 +1,234 @@
+/// Arithmetic expressions as a recursive enum: each operand is boxed,
+/// like chapter 15's cons list, so Expr has a known size. Evaluating and
+/// simplifying are both one match over the variants, recursing into the
+/// boxes. There are no box patterns on stable Rust, so to look inside
+/// both operands at once the match is on `(&**l, &**r)`.
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+  Num(i64),
+  Add(Box<Expr>, Box<Expr>),
+  Mul(Box<Expr>, Box<Expr>),
+  Neg(Box<Expr>),
+  Var(String)
+}
+
+#[derive(Debug, PartialEq)]
+pub enum EvalError {
+  UnboundVariable(String),
+  Overflow
+}
+
+impl fmt::Display for EvalError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      EvalError::UnboundVariable(name) => write!(f, "`{}` has no value", name),
+      EvalError::Overflow => write!(f, "the result doesn't fit in an i64")
+    }
+  }
+}
+
+use Expr::*;
+
+pub fn num(n: i64) -> Expr {
+  Num(n)
+}
+
+pub fn var(name: &str) -> Expr {
+  Var(name.to_string())
+}
+
+pub fn add(l: Expr, r: Expr) -> Expr {
+  Add(Box::new(l), Box::new(r))
+}
+
+pub fn mul(l: Expr, r: Expr) -> Expr {
+  Mul(Box::new(l), Box::new(r))
+}
+
+pub fn neg(e: Expr) -> Expr {
+  Neg(Box::new(e))
+}
+
+impl Expr {
+  pub fn eval(&self, env: &HashMap<String, i64>) -> Result<i64, EvalError> {
+    match self {
+      Num(n) => Ok(*n),
+      Add(l, r) => l.eval(env)?.checked_add(r.eval(env)?).ok_or(EvalError::Overflow),
+      Mul(l, r) => l.eval(env)?.checked_mul(r.eval(env)?).ok_or(EvalError::Overflow),
+      Neg(e) => e.eval(env)?.checked_neg().ok_or(EvalError::Overflow),
+      Var(name) => env.get(name).copied().ok_or_else(|| EvalError::UnboundVariable(name.clone()))
+    }
+  }
+
+  /// Folds constants and drops the identities `x + 0`, `x * 1` and
+  /// `- -x`, bottom up, so rules that only apply once the operands are
+  /// simplified still fire. `x * 0` only becomes 0 when `x` is a number
+  /// or a variable: anything bigger could fail to evaluate, by overflowing
+  /// for one, and folding it away would hide that. An unbound `x` still
+  /// goes, so a simplified expression can evaluate where the original
+  /// didn't. Folds that would overflow are left alone.
+  pub fn simplify(&self) -> Expr {
+    match self {
+      Num(_) | Var(_) => self.clone(),
+      Add(l, r) => {
+        let (l, r) = (l.simplify(), r.simplify());
+        match (&l, &r) {
+          (Num(a), Num(b)) if a.checked_add(*b).is_some() => Num(a + b),
+          (Num(0), _) => r,
+          (_, Num(0)) => l,
+          _ => add(l, r)
+        }
+      }
+      Mul(l, r) => {
+        let (l, r) = (l.simplify(), r.simplify());
+        match (&l, &r) {
+          (Num(a), Num(b)) if a.checked_mul(*b).is_some() => Num(a * b),
+          (Num(0), Num(_) | Var(_)) | (Num(_) | Var(_), Num(0)) => Num(0),
+          (Num(1), _) => r,
+          (_, Num(1)) => l,
+          _ => mul(l, r)
+        }
+      }
+      Neg(e) => match e.simplify() {
+        Num(n) if n != i64::MIN => Num(-n),
+        Neg(inner) => *inner,
+        e => neg(e)
+      }
+    }
+  }
+}
+
+impl fmt::Display for Expr {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Num(n) if *n < 0 => write!(f, "({})", n),
+      Num(n) => write!(f, "{}", n),
+      Add(l, r) => write!(f, "{} + {}", l, r),
+      Mul(l, r) => write!(f, "{} * {}", Operand(l), Operand(r)),
+      Neg(e) => match **e {
+        Mul(..) => write!(f, "-({})", e),
+        _ => write!(f, "-{}", Operand(e))
+      },
+      Var(name) => write!(f, "{}", name)
+    }
+  }
+}
+
+// an operand of `*` or `-`, which binds tighter than `+`, so a sum in
+// there needs its parentheses
+struct Operand<'a>(&'a Expr);
+
+impl fmt::Display for Operand<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self.0 {
+      Add(..) => write!(f, "({})", self.0),
+      e => write!(f, "{}", e)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn env(vars: &[(&str, i64)]) -> HashMap<String, i64> {
+    vars.iter().map(|&(name, value)| (name.to_string(), value)).collect()
+  }
+
+  #[test]
+  fn evaluates_arithmetic() {
+    // (2 + 3) * -4
+    let e = mul(add(num(2), num(3)), neg(num(4)));
+    assert_eq!(e.eval(&env(&[])), Ok(-20));
+    let e = add(mul(var("x"), var("x")), var("y"));
+    assert_eq!(e.eval(&env(&[("x", 7), ("y", 1)])), Ok(50));
+    assert_eq!(mul(num(i64::MAX), num(2)).eval(&env(&[])), Err(EvalError::Overflow));
+    assert_eq!(neg(num(i64::MIN)).eval(&env(&[])), Err(EvalError::Overflow));
+  }
+
+  #[test]
+  fn unbound_variable_is_named() {
+    let err = add(var("x"), var("why")).eval(&env(&[("x", 1)])).unwrap_err();
+    assert_eq!(err, EvalError::UnboundVariable(String::from("why")));
+    assert_eq!(err.to_string(), "`why` has no value");
+  }
+
+  #[test]
+  fn simplification_rules() {
+    assert_eq!(add(num(2), num(3)).simplify(), num(5));
+    assert_eq!(add(var("x"), num(0)).simplify(), var("x"));
+    assert_eq!(add(num(0), var("x")).simplify(), var("x"));
+    assert_eq!(mul(var("x"), num(1)).simplify(), var("x"));
+    assert_eq!(mul(num(1), var("x")).simplify(), var("x"));
+    assert_eq!(mul(var("x"), num(0)).simplify(), num(0));
+    assert_eq!(mul(num(0), num(7)).simplify(), num(0));
+    assert_eq!(neg(neg(var("x"))).simplify(), var("x"));
+    assert_eq!(neg(num(3)).simplify(), num(-3));
+    assert_eq!(add(var("x"), var("y")).simplify(), add(var("x"), var("y")));
+    // left alone rather than overflowing
+    assert_eq!(add(num(i64::MAX), num(1)).simplify(), add(num(i64::MAX), num(1)));
+  }
+
+  #[test]
+  fn nested_rules_fire_after_operands_simplify() {
+    // x * (3 + -2) -> x * 1 -> x
+    assert_eq!(mul(var("x"), add(num(3), neg(num(2)))).simplify(), var("x"));
+    // (y * (x + 0)) + (0 * z) -> y * x
+    let e = add(mul(var("y"), add(var("x"), num(0))), mul(num(0), var("z")));
+    assert_eq!(e.simplify(), mul(var("y"), var("x")));
+    // --(1 + 1) -> 2
+    assert_eq!(neg(neg(add(num(1), num(1)))).simplify(), num(2));
+  }
+
+  #[test]
+  fn times_zero_keeps_operands_that_can_fail() {
+    // i64::MAX * 2 overflows, so the product can't be folded to 0
+    let e = mul(mul(num(i64::MAX), num(2)), num(0));
+    assert_eq!(e.simplify(), e);
+    assert_eq!(e.simplify().eval(&env(&[])), Err(EvalError::Overflow));
+    let e = mul(num(0), add(var("x"), num(1)));
+    assert_eq!(e.simplify(), e);
+  }
+
+  /// Small expressions from a linear congruential generator, so the
+  /// "random" ones are the same on every run.
+  fn generate(seed: &mut u64, depth: u32) -> Expr {
+    *seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    let pick = (*seed >> 33) % 6;
+    if depth == 0 {
+      return match pick {
+        0 | 1 => var(["x", "y"][(pick % 2) as usize]),
+        _ => num((pick as i64) - 3)
+      };
+    }
+    match pick {
+      0 => add(generate(seed, depth - 1), generate(seed, depth - 1)),
+      1 => mul(generate(seed, depth - 1), generate(seed, depth - 1)),
+      2 => neg(generate(seed, depth - 1)),
+      _ => generate(seed, 0)
+    }
+  }
+
+  #[test]
+  fn simplify_keeps_the_value() {
+    let env = env(&[("x", 5), ("y", -3)]);
+    let mut seed = 42;
+    for _ in 0..500 {
+      let e = generate(&mut seed, 5);
+      assert_eq!(e.simplify().eval(&env), e.eval(&env), "{}", e);
+    }
+  }
+
+  #[test]
+  fn displays_with_parentheses() {
+    assert_eq!(mul(add(var("x"), num(-1)), neg(var("y"))).to_string(), "(x + (-1)) * -y");
+    assert_eq!(mul(var("x"), add(var("y"), var("z"))).to_string(), "x * (y + z)");
+    assert_eq!(add(var("x"), mul(var("y"), var("z"))).to_string(), "x + y * z");
+    assert_eq!(neg(add(var("x"), num(1))).to_string(), "-(x + 1)");
+    assert_eq!(neg(mul(var("x"), var("y"))).to_string(), "-(x * y)");
+    assert_eq!(add(add(var("x"), var("y")), var("z")).to_string(), "x + y + z");
+  }
+}
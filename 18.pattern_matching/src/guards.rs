@@ -0,0 +1,70 @@
+/// Guards that use more than one binding, and the outer `threshold`,
+/// which a pattern alone can't compare against. Arms are tried in order,
+/// so when several guards would pass, the first one wins.
+///
+/// A guard covers the whole or-pattern before it: `n @ (4 | 5 | 6) if
+/// y < 0` means `(4 | 5 | 6) if y < 0`, never `4 | 5 | (6 if y < 0)`.
+#[derive(Debug, PartialEq)]
+pub enum PairOutcome {
+  Equal(i32),
+  SmallWithNegative { n: i32, y: i32 },
+  AboveThreshold(i32),
+  SumsToThreshold { n: i32, y: i32 },
+  Unmatched { n: i32, y: i32 },
+  Missing { y: i32 }
+}
+
+// `4 | 5 | 6` rather than `4..=6`, to show the guard on an or-pattern
+#[allow(clippy::manual_range_patterns)]
+pub fn compare_pair(pair: (Option<i32>, i32), threshold: i32) -> PairOutcome {
+  match pair {
+    (Some(n), y) if n == y => PairOutcome::Equal(n),
+    (Some(n @ (4 | 5 | 6)), y) if y < 0 => PairOutcome::SmallWithNegative { n, y },
+    (Some(n), _) if n > threshold => PairOutcome::AboveThreshold(n),
+    (Some(n), y) if n.checked_add(y) == Some(threshold) => PairOutcome::SumsToThreshold { n, y },
+    (Some(n), y) => PairOutcome::Unmatched { n, y },
+    (None, y) => PairOutcome::Missing { y }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use PairOutcome::*;
+
+  #[test]
+  fn each_arm() {
+    assert_eq!(compare_pair((Some(3), 3), 10), Equal(3));
+    assert_eq!(compare_pair((Some(5), -1), 10), SmallWithNegative { n: 5, y: -1 });
+    assert_eq!(compare_pair((Some(11), 0), 10), AboveThreshold(11));
+    assert_eq!(compare_pair((Some(7), 3), 10), SumsToThreshold { n: 7, y: 3 });
+    assert_eq!(compare_pair((Some(1), 2), 10), Unmatched { n: 1, y: 2 });
+    assert_eq!(compare_pair((None, 2), 10), Missing { y: 2 });
+  }
+
+  #[test]
+  fn guard_covers_the_whole_or_pattern() {
+    for n in 4..=6 {
+      assert_eq!(compare_pair((Some(n), -2), 10), SmallWithNegative { n, y: -2 });
+      // with y >= 0 the guard fails for every alternative, 4 and 5 included
+      assert_eq!(compare_pair((Some(n), 1), 10), Unmatched { n, y: 1 });
+    }
+    assert_eq!(compare_pair((Some(7), -2), 10), Unmatched { n: 7, y: -2 });
+  }
+
+  #[test]
+  fn first_passing_guard_wins() {
+    // equal, above the threshold and summing to it: Equal comes first
+    assert_eq!(compare_pair((Some(10), 10), 5), Equal(10));
+    // small with a negative y, and summing to the threshold
+    assert_eq!(compare_pair((Some(6), -1), 5), SmallWithNegative { n: 6, y: -1 });
+    // above the threshold, and summing to it
+    assert_eq!(compare_pair((Some(8), -3), 5), AboveThreshold(8));
+  }
+
+  #[test]
+  fn sum_doesnt_overflow() {
+    assert_eq!(compare_pair((Some(i32::MAX), 1), i32::MAX), Unmatched { n: i32::MAX, y: 1 });
+    assert_eq!(compare_pair((Some(i32::MIN), -1), 0), Unmatched { n: i32::MIN, y: -1 });
+  }
+}
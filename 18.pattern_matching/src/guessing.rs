@@ -0,0 +1,118 @@
+/// The guessing game from chapter 2, with the secret and the guesses
+/// passed in so it can run without a terminal. Each guess is matched on
+/// `guess.cmp(&secret)`: the three Ordering variants are the whole game.
+/// `cargo run -- --guess` plays it on stdin.
+use std::cmp::Ordering;
+use std::io::{self, BufRead};
+use rand::{thread_rng, Rng};
+
+#[derive(Debug, PartialEq)]
+pub enum GameResult {
+  Won { guesses: usize },
+  RanOut { guesses: usize }
+}
+
+pub fn play(secret: u32, guesses: impl Iterator<Item = u32>) -> GameResult {
+  play_reporting(secret, guesses, |_, _| {})
+}
+
+/// `play`, calling `report` with every guess and how it compares to the
+/// secret.
+pub fn play_reporting(
+  secret: u32,
+  guesses: impl Iterator<Item = u32>,
+  mut report: impl FnMut(u32, Ordering)
+) -> GameResult {
+  let mut used = 0;
+  for guess in guesses {
+    used += 1;
+    let ordering = guess.cmp(&secret);
+    report(guess, ordering);
+    match ordering {
+      Ordering::Less | Ordering::Greater => continue,
+      Ordering::Equal => return GameResult::Won { guesses: used }
+    }
+  }
+  GameResult::RanOut { guesses: used }
+}
+
+/// A secret between 1 and 100 and guesses read from stdin, one per line.
+/// Lines that aren't numbers are skipped, as in the book.
+pub fn play_interactive() -> GameResult {
+  // rand 0.7 takes the range as two arguments
+  let secret = thread_rng().gen_range(1, 101);
+  println!("Guess the number! Please input your guess.");
+  let stdin = io::stdin();
+  let guesses = stdin.lock().lines().map_while(Result::ok).filter_map(|line| match line.trim().parse() {
+    Ok(num) => Some(num),
+    Err(_) => {
+      println!("Please type a number!");
+      None
+    }
+  });
+  let result = play_reporting(secret, guesses, |guess, ordering| match ordering {
+    Ordering::Less => println!("{} is too small!", guess),
+    Ordering::Greater => println!("{} is too big!", guess),
+    Ordering::Equal => println!("{} it is, you win!", guess)
+  });
+  if let GameResult::RanOut { guesses } = result {
+    println!("Out of input after {} guesses, it was {}", guesses, secret);
+  }
+  result
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Halves the range after every answer, like a careful player.
+  fn binary_search(secret: u32, low: u32, high: u32) -> GameResult {
+    let bounds = std::cell::Cell::new((low, high));
+    let guesses = std::iter::from_fn(|| {
+      let (low, high) = bounds.get();
+      if low > high {
+        return None;
+      }
+      Some(low + (high - low) / 2)
+    });
+    play_reporting(secret, guesses, |guess, ordering| {
+      let (low, high) = bounds.get();
+      match ordering {
+        Ordering::Less => bounds.set((guess + 1, high)),
+        Ordering::Greater => bounds.set((low, guess - 1)),
+        Ordering::Equal => {}
+      }
+    })
+  }
+
+  #[test]
+  fn binary_search_wins_in_log_steps() {
+    // 50, 25, 37, 31, 34, 32, 33
+    assert_eq!(binary_search(33, 1, 100), GameResult::Won { guesses: 7 });
+    assert_eq!(binary_search(50, 1, 100), GameResult::Won { guesses: 1 });
+    for secret in 1..=100 {
+      match binary_search(secret, 1, 100) {
+        GameResult::Won { guesses } => assert!(guesses <= 7, "{} took {}", secret, guesses),
+        result => panic!("{} gave {:?}", secret, result)
+      }
+    }
+  }
+
+  #[test]
+  fn running_out_of_guesses() {
+    assert_eq!(play(7, vec![1, 2, 3].into_iter()), GameResult::RanOut { guesses: 3 });
+    assert_eq!(play(7, std::iter::empty()), GameResult::RanOut { guesses: 0 });
+  }
+
+  #[test]
+  fn first_try() {
+    assert_eq!(play(42, vec![42, 1].into_iter()), GameResult::Won { guesses: 1 });
+  }
+
+  #[test]
+  fn report_sees_every_comparison() {
+    let mut seen = vec![];
+    play_reporting(5, vec![9, 1, 5, 5].into_iter(), |guess, ordering| seen.push((guess, ordering)));
+    assert_eq!(seen, [(9, Ordering::Greater), (1, Ordering::Less), (5, Ordering::Equal)]);
+  }
+}
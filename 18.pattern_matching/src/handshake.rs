@@ -0,0 +1,126 @@
+/// The `@` binding: `id_var @ 3..=8` tests the id against a range and
+/// keeps it in `id_var` at the same time, something a bare range pattern
+/// can't do, since it matches without binding anything.
+///
+/// A handshake built on it: a Hello with an id in 3..=8 is answered with
+/// a Ping, the Ping with Data echoing the nonce, and any other Hello with
+/// a Goodbye saying why.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Msg {
+  Hello { id: i32 },
+  Goodbye { id: i32, reason: String },
+  Ping { nonce: u64 },
+  Data { id: i32, payload: Vec<u8> }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IdClass {
+  Early { id: i32 },
+  InRange { id: i32 },
+  Reserved,
+  Other { id: i32 }
+}
+
+/// Classifies the id of any message that has one; a Ping only has a nonce.
+pub fn classify_id(msg: &Msg) -> Option<IdClass> {
+  let id = match *msg {
+    // the same binding from every alternative of an or-pattern
+    Msg::Hello { id } | Msg::Goodbye { id, .. } | Msg::Data { id, .. } => id,
+    Msg::Ping { .. } => return None
+  };
+  let class = match id {
+    // `@` works with an or-pattern too, binding whichever alternative matched
+    id @ (1 | 2) => IdClass::Early { id },
+    id_var @ 3..=8 => IdClass::InRange { id: id_var },
+    // the range is tested but nothing is bound, the id isn't needed
+    10..=13 => IdClass::Reserved,
+    id => IdClass::Other { id }
+  };
+  Some(class)
+}
+
+// the nonce carries the id in its low half, under a tag
+const PING_TAG: u64 = 0x5049_4e47;
+
+/// The answer to `msg`, or None when the exchange is over.
+pub fn respond(msg: &Msg) -> Option<Msg> {
+  match msg {
+    &Msg::Hello { id: id @ 3..=8 } => Some(Msg::Ping { nonce: PING_TAG << 32 | u64::from(id as u32) }),
+    &Msg::Hello { id } if id < 0 => Some(Msg::Goodbye { id, reason: String::from("ids can't be negative") }),
+    &Msg::Hello { id: id @ 10..=13 } => Some(Msg::Goodbye { id, reason: format!("id {} is reserved", id) }),
+    &Msg::Hello { id } => Some(Msg::Goodbye { id, reason: format!("id {} is outside 3..=8", id) }),
+    &Msg::Ping { nonce } => Some(Msg::Data { id: nonce as u32 as i32, payload: nonce.to_be_bytes().to_vec() }),
+    Msg::Data { .. } | Msg::Goodbye { .. } => None
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn class(id: i32) -> IdClass {
+    classify_id(&Msg::Hello { id }).unwrap()
+  }
+
+  #[test]
+  fn boundaries() {
+    assert_eq!(class(2), IdClass::Early { id: 2 });
+    assert_eq!(class(3), IdClass::InRange { id: 3 });
+    assert_eq!(class(8), IdClass::InRange { id: 8 });
+    assert_eq!(class(9), IdClass::Other { id: 9 });
+    assert_eq!(class(10), IdClass::Reserved);
+    assert_eq!(class(13), IdClass::Reserved);
+    assert_eq!(class(14), IdClass::Other { id: 14 });
+  }
+
+  #[test]
+  fn each_or_pattern_branch_binds_its_value() {
+    assert_eq!(class(1), IdClass::Early { id: 1 });
+    assert_eq!(class(2), IdClass::Early { id: 2 });
+    assert_eq!(class(0), IdClass::Other { id: 0 });
+    assert_eq!(class(-5), IdClass::Other { id: -5 });
+  }
+
+  #[test]
+  fn every_message_with_an_id_is_classified() {
+    let goodbye = Msg::Goodbye { id: 11, reason: String::new() };
+    assert_eq!(classify_id(&goodbye), Some(IdClass::Reserved));
+    assert_eq!(classify_id(&Msg::Data { id: 4, payload: vec![] }), Some(IdClass::InRange { id: 4 }));
+    assert_eq!(classify_id(&Msg::Ping { nonce: 1 }), None);
+  }
+
+  /// Answers each message in turn until nobody has anything left to say.
+  fn exchange(first: Msg) -> Vec<Msg> {
+    let mut messages = vec![first];
+    while let Some(answer) = respond(messages.last().unwrap()) {
+      messages.push(answer);
+    }
+    messages
+  }
+
+  #[test]
+  fn hello_ping_data() {
+    let nonce = PING_TAG << 32 | 5;
+    assert_eq!(exchange(Msg::Hello { id: 5 }), [
+      Msg::Hello { id: 5 },
+      Msg::Ping { nonce },
+      Msg::Data { id: 5, payload: nonce.to_be_bytes().to_vec() }
+    ]);
+  }
+
+  #[test]
+  fn rejected_hellos_get_a_goodbye() {
+    let goodbye = |id, reason: &str| vec![Msg::Hello { id }, Msg::Goodbye { id, reason: String::from(reason) }];
+    assert_eq!(exchange(Msg::Hello { id: 2 }), goodbye(2, "id 2 is outside 3..=8"));
+    assert_eq!(exchange(Msg::Hello { id: 9 }), goodbye(9, "id 9 is outside 3..=8"));
+    assert_eq!(exchange(Msg::Hello { id: 12 }), goodbye(12, "id 12 is reserved"));
+    assert_eq!(exchange(Msg::Hello { id: -1 }), goodbye(-1, "ids can't be negative"));
+  }
+
+  #[test]
+  fn data_and_goodbye_end_the_exchange() {
+    assert_eq!(respond(&Msg::Data { id: 3, payload: vec![1, 2] }), None);
+    assert_eq!(respond(&Msg::Goodbye { id: 3, reason: String::from("done") }), None);
+  }
+}
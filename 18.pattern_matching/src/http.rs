@@ -0,0 +1,101 @@
+/// Sorting an HTTP response by its status line, in one match over
+/// `(status, body)`: ranges for the bands, an or-pattern for the
+/// redirects worth following, `@` to keep the code, and a guard to take a
+/// client error's reason from the body only when there is one. Other 3xx
+/// codes, like 304 Not Modified, aren't redirects to follow, so they end
+/// up Invalid along with anything out of range.
+#[derive(Debug, PartialEq)]
+pub enum ResponseClass {
+  Informational(u16),
+  Success(u16),
+  Redirect(u16),
+  ClientError { status: u16, reason: Option<String> },
+  ServerError(u16),
+  Invalid(u16)
+}
+
+pub fn classify_response(status: u16, body: Option<&str>) -> ResponseClass {
+  match (status, body) {
+    (100..=199, _) => ResponseClass::Informational(status),
+    (200..=299, _) => ResponseClass::Success(status),
+    (code @ (301 | 302 | 307 | 308), _) => ResponseClass::Redirect(code),
+    (code @ 400..=499, Some(body)) if !body.trim().is_empty() => ResponseClass::ClientError {
+      status: code,
+      reason: body.lines().map(str::trim).find(|line| !line.is_empty()).map(String::from)
+    },
+    (code @ 400..=499, _) => ResponseClass::ClientError { status: code, reason: None },
+    (500..=599, _) => ResponseClass::ServerError(status),
+    _ => ResponseClass::Invalid(status)
+  }
+}
+
+/// Whether sending the same request again later could work: a timeout, a
+/// rate limit, or the server's own fault.
+pub fn retryable(class: &ResponseClass) -> bool {
+  match class {
+    ResponseClass::ClientError { status: 408 | 429, .. } | ResponseClass::ServerError(_) => true,
+    ResponseClass::Informational(_)
+    | ResponseClass::Success(_)
+    | ResponseClass::Redirect(_)
+    | ResponseClass::ClientError { .. }
+    | ResponseClass::Invalid(_) => false
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use ResponseClass::*;
+
+  fn class(status: u16) -> ResponseClass {
+    classify_response(status, None)
+  }
+
+  #[test]
+  fn band_boundaries() {
+    assert_eq!(class(99), Invalid(99));
+    assert_eq!(class(100), Informational(100));
+    assert_eq!(class(199), Informational(199));
+    assert_eq!(class(200), Success(200));
+    assert_eq!(class(299), Success(299));
+    assert_eq!(class(300), Invalid(300));
+    assert_eq!(class(399), Invalid(399));
+    assert_eq!(class(400), ClientError { status: 400, reason: None });
+    assert_eq!(class(499), ClientError { status: 499, reason: None });
+    assert_eq!(class(500), ServerError(500));
+    assert_eq!(class(599), ServerError(599));
+    assert_eq!(class(600), Invalid(600));
+  }
+
+  #[test]
+  fn only_followable_redirects() {
+    for code in &[301, 302, 307, 308] {
+      assert_eq!(class(*code), Redirect(*code));
+    }
+    for code in &[303, 304, 305, 306] {
+      assert_eq!(class(*code), Invalid(*code));
+    }
+  }
+
+  #[test]
+  fn not_found_with_and_without_a_body() {
+    assert_eq!(classify_response(404, Some("\n  Not Found \nno page at /x")), ClientError {
+      status: 404,
+      reason: Some(String::from("Not Found"))
+    });
+    assert_eq!(classify_response(404, Some(" \n ")), ClientError { status: 404, reason: None });
+    assert_eq!(classify_response(404, None), ClientError { status: 404, reason: None });
+    // only client errors read the body
+    assert_eq!(classify_response(200, Some("Not Found")), Success(200));
+  }
+
+  #[test]
+  fn rate_limits_are_retryable() {
+    assert!(retryable(&classify_response(429, Some("Too Many Requests"))));
+    assert!(retryable(&class(408)));
+    assert!(!retryable(&class(400)));
+    assert!(retryable(&class(503)));
+    assert!(!retryable(&class(200)));
+    assert!(!retryable(&class(302)));
+  }
+}
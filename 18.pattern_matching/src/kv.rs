@@ -0,0 +1,199 @@
+/// A small `key = value` format, read a char at a time. The loop peeks
+/// at the next char and lets its class decide what comes next: blank
+/// space is skipped, `#` starts a comment that runs to the end of the
+/// line, and an identifier char starts a pair. Values are either bare,
+/// up to the end of the line or a comment, or quoted, where `\"` and `\\`
+/// stand for a quote and a backslash.
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+
+#[derive(Debug, PartialEq)]
+pub enum KvError {
+  MissingEquals { line: usize, column: usize },
+  UnterminatedQuote { line: usize, column: usize },
+  UnexpectedChar { found: char, line: usize, column: usize }
+}
+
+impl fmt::Display for KvError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      KvError::MissingEquals { line, column } => write!(f, "{}:{}: expected `=`", line, column),
+      KvError::UnterminatedQuote { line, column } => write!(f, "{}:{}: this quote is never closed", line, column),
+      KvError::UnexpectedChar { found, line, column } => write!(f, "{}:{}: unexpected {:?}", line, column, found)
+    }
+  }
+}
+
+/// The chars, and where the next one is, as a 1-based line and column.
+struct Cursor<'a> {
+  chars: Peekable<Chars<'a>>,
+  line: usize,
+  column: usize
+}
+
+impl<'a> Cursor<'a> {
+  fn bump(&mut self) -> Option<char> {
+    let c = self.chars.next()?;
+    match c {
+      '\n' => {
+        self.line += 1;
+        self.column = 1;
+      }
+      _ => self.column += 1
+    }
+    Some(c)
+  }
+
+  fn take_while(&mut self, keep: impl Fn(char) -> bool) -> String {
+    let mut taken = String::new();
+    while let Some(&c) = self.chars.peek() {
+      if !keep(c) {
+        break;
+      }
+      taken.push(c);
+      self.bump();
+    }
+    taken
+  }
+
+  fn skip_spaces(&mut self) {
+    self.take_while(|c| c == ' ' || c == '\t');
+  }
+
+  fn unexpected(&self, found: char) -> KvError {
+    KvError::UnexpectedChar { found, line: self.line, column: self.column }
+  }
+
+  /// After the opening quote, which is at `line`:`column`.
+  fn quoted(&mut self, line: usize, column: usize) -> Result<String, KvError> {
+    let mut value = String::new();
+    while let Some(c) = self.bump() {
+      match c {
+        '"' => return Ok(value),
+        '\\' => match self.bump() {
+          Some(escaped @ ('"' | '\\')) => value.push(escaped),
+          Some(other) => {
+            value.push('\\');
+            value.push(other);
+          }
+          None => break
+        },
+        c => value.push(c)
+      }
+    }
+    Err(KvError::UnterminatedQuote { line, column })
+  }
+
+  fn value(&mut self) -> Result<String, KvError> {
+    match self.chars.peek() {
+      Some('"') => {
+        let (line, column) = (self.line, self.column);
+        self.bump();
+        let value = self.quoted(line, column)?;
+        self.skip_spaces();
+        match self.chars.peek() {
+          None | Some('\n' | '\r' | '#') => Ok(value),
+          Some(&c) => Err(self.unexpected(c))
+        }
+      }
+      _ => Ok(self.take_while(|c| c != '\n' && c != '#').trim_end().to_string())
+    }
+  }
+}
+
+fn is_key_char(c: char) -> bool {
+  c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.'
+}
+
+/// Every pair in order, duplicates included.
+pub fn parse_kv(input: &str) -> Result<Vec<(String, String)>, KvError> {
+  let mut cursor = Cursor { chars: input.chars().peekable(), line: 1, column: 1 };
+  let mut pairs = vec![];
+  while let Some(&c) = cursor.chars.peek() {
+    match c {
+      ' ' | '\t' | '\r' | '\n' => {
+        cursor.bump();
+      }
+      '#' => {
+        cursor.take_while(|c| c != '\n');
+      }
+      c if is_key_char(c) => {
+        let key = cursor.take_while(is_key_char);
+        cursor.skip_spaces();
+        match cursor.chars.peek() {
+          Some('=') => cursor.bump(),
+          _ => return Err(KvError::MissingEquals { line: cursor.line, column: cursor.column })
+        };
+        cursor.skip_spaces();
+        pairs.push((key, cursor.value()?));
+      }
+      other => return Err(cursor.unexpected(other))
+    }
+  }
+  Ok(pairs)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn pairs(list: &[(&str, &str)]) -> Vec<(String, String)> {
+    list.iter().map(|&(k, v)| (k.to_string(), v.to_string())).collect()
+  }
+
+  #[test]
+  fn simple_pairs() {
+    assert_eq!(parse_kv("host=localhost\nport = 8080\n  log.level\t=  debug  "), Ok(pairs(&[
+      ("host", "localhost"),
+      ("port", "8080"),
+      ("log.level", "debug")
+    ])));
+    assert_eq!(parse_kv("empty =\r\nnext=1"), Ok(pairs(&[("empty", ""), ("next", "1")])));
+  }
+
+  #[test]
+  fn quoted_values() {
+    let input = r#"greeting = "hello,  world "
+quote = "she said \"hi\""
+path = "C:\\temp\n" # the \n stays as written"#;
+    assert_eq!(parse_kv(input), Ok(pairs(&[
+      ("greeting", "hello,  world "),
+      ("quote", "she said \"hi\""),
+      ("path", "C:\\temp\\n")
+    ])));
+    assert_eq!(parse_kv("a = \"#not a comment\""), Ok(pairs(&[("a", "#not a comment")])));
+  }
+
+  #[test]
+  fn comments_and_blank_lines() {
+    let input = "# settings\n\n   \nname = demo # trailing\n# done\n";
+    assert_eq!(parse_kv(input), Ok(pairs(&[("name", "demo")])));
+    assert_eq!(parse_kv(""), Ok(vec![]));
+    assert_eq!(parse_kv("# only a comment"), Ok(vec![]));
+  }
+
+  #[test]
+  fn missing_equals() {
+    assert_eq!(parse_kv("a = 1\nport 8080"), Err(KvError::MissingEquals { line: 2, column: 6 }));
+    assert_eq!(parse_kv("key"), Err(KvError::MissingEquals { line: 1, column: 4 }));
+    assert_eq!(parse_kv("a = 1\nport 8080").unwrap_err().to_string(), "2:6: expected `=`");
+  }
+
+  #[test]
+  fn unterminated_quote() {
+    assert_eq!(parse_kv("a = 1\nb = \"open\nc = 2"), Err(KvError::UnterminatedQuote { line: 2, column: 5 }));
+    assert_eq!(parse_kv("b = \"ends in \\"), Err(KvError::UnterminatedQuote { line: 1, column: 5 }));
+  }
+
+  #[test]
+  fn unexpected_chars() {
+    assert_eq!(parse_kv("= 1"), Err(KvError::UnexpectedChar { found: '=', line: 1, column: 1 }));
+    assert_eq!(parse_kv("a = \"x\" y"), Err(KvError::UnexpectedChar { found: 'y', line: 1, column: 9 }));
+  }
+
+  #[test]
+  fn duplicates_stay_in_order() {
+    assert_eq!(parse_kv("k = 1\nother = x\nk = 2"), Ok(pairs(&[("k", "1"), ("other", "x"), ("k", "2")])));
+  }
+}
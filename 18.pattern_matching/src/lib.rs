@@ -0,0 +1,4 @@
+// The pattern chapter is a binary, and rustdoc only runs the examples in
+// a library's docs, so the modules with examples worth checking live here
+// and main uses them from `pattern_matching::`.
+pub mod refutability;
@@ -1,27 +1,27 @@
-/// Patterns are a special syntax in Rust for matching 
-/// against the structure of types, both complex and simple. 
-/// Using patterns in conjunction with match expressions 
-/// and other constructs gives you more control over a 
-/// program’s control flow. A pattern consists of 
-/// some combination of the following:
-
-/// *Literals
-/// *Destructured arrays, enums, structs, or tuples
-/// *Variables
-/// *Wildcards
-/// *Placeholders
-
-/// These components describe the shape of the data we’re 
-/// working with, which we then match against values to 
-/// determine whether our program has the correct data 
-/// to continue running a particular piece of code. To use a pattern, 
-/// we compare it to some value. If the pattern matches the 
-/// value, we use the value parts in our code.
-
-/// 1. match arms
-/// One requirement for match expressions is that they need 
-/// to be exhaustive in the sense that all possibilities for 
-/// the value in the match expression must be accounted for
+// Patterns are a special syntax in Rust for matching 
+// against the structure of types, both complex and simple. 
+// Using patterns in conjunction with match expressions 
+// and other constructs gives you more control over a 
+// program’s control flow. A pattern consists of 
+// some combination of the following:
+
+// *Literals
+// *Destructured arrays, enums, structs, or tuples
+// *Variables
+// *Wildcards
+// *Placeholders
+
+// These components describe the shape of the data we’re 
+// working with, which we then match against values to 
+// determine whether our program has the correct data 
+// to continue running a particular piece of code. To use a pattern, 
+// we compare it to some value. If the pattern matches the 
+// value, we use the value parts in our code.
+
+// 1. match arms
+// One requirement for match expressions is that they need 
+// to be exhaustive in the sense that all possibilities for 
+// the value in the match expression must be accounted for
 
 // match VAULE {
 //     PATTERN => expression,
@@ -31,104 +31,166 @@
 // }
 
 // 2. conditional `if let` expression
+mod background;
+
 fn if_let_cases() {
     let favorite_color: Option<&str> = Some("yellow");
     let is_tuesday = true;
     let age: Result<u8, _> = "34".parse();
-
-    if let Some(color) = favorite_color {
-        println!("Using your favorite color, {}, as bg color", color);
-    } else if is_tuesday {
-        println!("Tuesday is green day");
-    } else if let Ok(age) = age {
-        if age > 30 {
-            println!("Using purple as the background color");
-        } else {
-            println!("Using orange as the background color");
-        }
-    } else {
-        println!("Using blue as the bg color");
+    println!("{:?}", background::choose_background_color(favorite_color, is_tuesday, age));
+
+    for &(favorite, is_tuesday, age) in &[(None, true, "34"), (None, false, "34"), (None, false, "30"), (None, false, "old")] {
+        println!(
+            "({:?}, {}, {:?}) -> {:?}",
+            favorite, is_tuesday, age,
+            background::choose_background_color(favorite, is_tuesday, age.parse())
+        );
+    }
+    // the same rules as one match over the three inputs
+    for &(favorite, is_tuesday, age) in &[(Some("red"), false, "34"), (None, false, "31")] {
+        println!(
+            "({:?}, {}, {:?}) -> {:?}",
+            favorite, is_tuesday, age,
+            background::choose_background_color_chained(favorite, is_tuesday, age.parse())
+        );
     }
 }
 
 // 3. `while let` conditional loops
+mod stack;
+mod kv;
+
 /// match a tuple in a function’s arguments to the pattern.
 fn while_let() {
-    let mut stack = vec![1, 2, 3];
+    let mut stack = stack::Stack::new();
+    for n in 1..=3 {
+        stack.push(n);
+    }
+    println!("{} on the stack", stack.len());
     while let Some(top) = stack.pop() {
         println!("{}", top);
     }
+    println!("empty: {}", stack.is_empty());
+
+    let mut stack = stack::Stack::from(vec![4, 9, 2, 8, 7]);
+    println!("drained above 5: {:?}, top is now {:?}", stack.drain_top_while(|&n| n > 5), stack.peek());
+    println!("the rest: {:?}", stack.into_iter().collect::<Vec<_>>());
+
+    // peeking at the next char decides what to read
+    let config = "# demo\nname = \"while let\"\nchapter = 18 # patterns\n";
+    println!("{:?}", kv::parse_kv(config));
+    if let Err(err) = kv::parse_kv("name \"while let\"") {
+        println!("{}", err);
+    }
 }
 
 // 4. for loops
+mod words;
+
 /// In a for loop, the pattern is the value that 
 /// directly follows the keyword for, so in for x 
 /// in y the x is the pattern.
 fn for_loops_pattern() {
-    let v = vec![1, 2, 3];
+    let v = [1, 2, 3];
     for (index, value) in v.iter().enumerate() {
         println!("{} is at index {}", value, index);
     }
+
+    let text = "the quick brown fox jumps over the lazy dog, the fox naps";
+    for (word, count) in words::word_frequencies(text, 3) {
+        println!("{}: {}", word, count);
+    }
+    let merged = words::merge_counts(words::count_words(text), &words::count_words("The dog naps too"));
+    println!("with a second text: {:?}", words::rank(&merged, 3));
 }
 
 // 5. let statments
-/// `let PATTERN = EXPRESSION;`
+// `let PATTERN = EXPRESSION;`
 
 // 6. function parameters
-fn print_coordinates(&(x, y): &(i32, i32)) {
-    println!("Current location ({},{})", x, y);
+mod coordinates;
+use coordinates::Coordinates;
+
+fn print_tuple_coordinates(point: &(i32, i32)) {
+    println!("{}", coordinates::format_coordinates(point));
+}
+
+// the same with a Point, which knows how to display itself
+fn print_coordinates(point: &PointI32) {
+    println!("Current location {}", point);
 }
 
 // 7. Refutability
-/// Patterns come in two forms: refutable and irrefutable. 
-/// Patterns that will match for any possible value passed are irrefutable.
-/// Function parameters, let statements, and for loops can only accept 
-/// irrefutable patterns, because the program cannot do anything meaningful 
-/// when values don’t match. The if let and while let expressions only accept 
-/// refutable patterns, because by definition they’re intended to handle 
-/// possible failure: the functionality of a conditional is in its ability 
-/// to perform differently depending on success or failure.
+// Patterns come in two forms: refutable and irrefutable. 
+// Patterns that will match for any possible value passed are irrefutable.
+// Function parameters, let statements, and for loops can only accept 
+// irrefutable patterns, because the program cannot do anything meaningful 
+// when values don’t match. The if let and while let expressions only accept 
+// refutable patterns, because by definition they’re intended to handle 
+// possible failure: the functionality of a conditional is in its ability 
+// to perform differently depending on success or failure.
+mod config;
+
+fn refutability() {
+    use pattern_matching::refutability::{demo_irrefutable, demo_refutable};
+    println!("{} / {}", demo_refutable(Some(5)), demo_refutable(None));
+    println!("(4, 2) destructures to {}", demo_irrefutable((4, 2)));
+    let argv = ["host=localhost", "port=8080", "mode=debug"].map(String::from);
+    match config::parse_config(&argv) {
+        Ok(config) => println!("{:?}", config),
+        Err(err) => println!("bad config: {}", err)
+    }
+    if let Err(err) = config::parse_config(&[String::from("port=http")]) {
+        println!("bad config: {}", err);
+    }
+}
 
 // 8. All matching cases
 extern crate rand;
 use rand::{thread_rng, Rng};
 
-struct Point {
-    x: i32,
-    y: i32
-}
+mod point;
+use point::{Point, PointI32};
 
-enum Color {
-    Rgb(i32, i32, i32),
-    Hsv(i32, i32, i32)
-}
+mod color;
+use color::Color;
 
+#[derive(Debug, Clone, PartialEq)]
 enum Message {
     Quit,
     Move { x: i32, y:i32 },
     Write(String),
-    ChangeColor(Color)
+    ChangeColor(Color),
+    Resize { width: u32, height: u32 },
+    Rotate(i32)
 }
 
-fn match_cases() {
+mod numbers;
+mod chars;
+mod slices;
+mod readings;
+mod bindings;
+mod underscore;
+
+// the single-arm matches and `if let Some(_)` are the point of the lesson
+#[allow(clippy::match_single_binding, clippy::redundant_pattern_matching)]
+fn match_cases(mut next_number: impl FnMut() -> u32) {
     // 1. matching literals
-    let mut rng = thread_rng();
-    let x = rng.gen_range(0, 10);
-    
-    match x {
-        1 => println!("one"),
-        // Ranges are only allowed with numeric values or char values, 
-        2...5 => println!("two to five"),
-        6 | 7 => println!("six to seven"),
-        _ => println!("large than 5")
+    for (x, category) in numbers::draw_categories(&mut next_number, 3) {
+        println!("{} is {:?}", x, category);
+    }
+    let histogram = numbers::category_histogram((0..100).map(|_| next_number()));
+    println!("100 draws: {:?}", histogram);
+    for x in [None, Some(2), Some(4), Some(77), Some(8)] {
+        println!("{:?} is {:?}", x, numbers::categorize_option(x));
     }
 
-    let y = 'c';
-    match y {
-        'a'...'j' => println!("early ASCII letter"),
-        'k'...'z' => println!("late ASCII letter"),
-        _ => println!("something else"),
+    let sentence = "Pattern #18: é?";
+    for (c, class) in chars::classify_str(sentence) {
+        println!("{:?} is {:?}", c, class);
     }
+    println!("counts: {:?}", chars::class_counts(sentence));
+    println!("{:?}", "R2-D2".chars().map(chars::symbol).collect::<Vec<_>>());
 
     // 2. use patterns to destructure structs,
     // enums, tuples, and references to use different parts of these values
@@ -136,10 +198,16 @@ fn match_cases() {
     // struct pattern rather than creating variables for all the fields. 
     // Doing so allows us to test some of the fields for particular values 
     // while creating variables to destructure the other fields.
-    let p = Point { x: 4, y: 5 };
-    match p {
-        Point { x: 4, y } => println!("on the y axis at {}", y),
-        Point { x, y } => println!("on either axis ({} {})", x, y)
+    let samples: [PointI32; 4] = [Point { x: 4, y: 5 }, Point { x: -2, y: 0 }, Point { x: 0, y: 0 }, Point { x: -1, y: -9 }];
+    for p in &samples {
+        println!("({}, {}) is {:?}", p.x, p.y, p.quadrant());
+    }
+    // the first matching arm wins, so Origin shadows both axes
+    for y in (-1..=1).rev() {
+        let row: Vec<String> = (-2..=2)
+            .map(|x| format!("{:?}", point::region(&Point { x, y })))
+            .collect();
+        println!("y = {:2}: {}", y, row.join(" "));
     }
     println!("\n");
 
@@ -150,61 +218,136 @@ fn match_cases() {
     let msg_move = Message::Move { x: 4, y: 7 };
     let msg_write = Message::Write(String::from("nothing"));
 
-    let message_content = |msg| {
-        match msg {
-            Message::Quit => println!("Quit variant has not data to use"),
-            Message::Move { x, y } => println!("Move to ({} {})", x, y),
-            Message::Write(text) => println!("Write {}", text),
-            // destucting nested enums
-            Message::ChangeColor(Color::Rgb(r, g, b)) => println!("RGB Color red {}, green {}, blue {}", r, g, b),
-            Message::ChangeColor(Color::Hsv(h, s, v)) => println!("HSV Color red {}, green {}, blue {}", h, s, v)
+    // `describe` only borrows, so each message is still ours afterwards
+    // to apply to a canvas
+    let mut canvas = canvas::Canvas::new();
+    let demo = [msg_hsv_color, msg_rgb_color, msg_write, msg_move, msg_quit];
+    println!("{:?}", message::count_by_kind(&demo));
+    println!("{:?}", summary::summarize(&demo));
+    println!("same without match ergonomics: {}", summary::summarize_explicit(&demo) == summary::summarize(&demo));
+    println!("warm: {:?}", palette::warm_colors(&demo));
+    for msg in demo {
+        println!("{}", message::describe(&msg));
+        println!("  quit {}, move {}, write {}, color {}", msg.is_quit(), msg.is_move(), msg.is_write(), msg.is_color_change());
+        canvas.apply(msg);
+    }
+    println!("canvas: {:?}", canvas);
+    println!("{:?} is {:?}", Color::Rgb(233, 33, 255), Color::Rgb(233, 33, 255).to_hsv());
+    println!("{:?} is {:?}", Color::Hsv(10, 33, 100), Color::Hsv(10, 33, 100).to_rgb());
+    let wild = Color::Rgb(-5, 999, 0);
+    println!("{} is invalid, {:?}, clamped to {}", wild, wild.validate().unwrap_err().to_string(), wild.clamped());
+    println!("halfway to white: {}", color::mix(&Color::Rgb(233, 33, 255), &Color::Hsv(0, 0, 100), 0.5));
+    println!("average: {:?}", color::average(&[Color::Rgb(255, 0, 0), Color::Rgb(0, 0, 255)]));
+    println!("grey is {:?}, {} is nearest to {}", color::by_name("grey"), wild, color::nearest_name(&wild));
+    for hex in &["#e921ff", "#f80", "#12345g"] {
+        match Color::from_hex(hex) {
+            Ok(color) => println!("{} is {:?}, back to {}", hex, color, color.to_hex()),
+            Err(err) => println!("{}: {}", hex, err)
         }
-    };
+    }
 
-    message_content(msg_hsv_color);
-    message_content(msg_rgb_color);
-    message_content(msg_write);
-    message_content(msg_move);
-    message_content(msg_quit);
+    // the same messages can be read from commands
+    let mut parsed = vec![];
+    for command in &["move 4 7", "Write two  spaces", "color rgb 233 33 255", "resize 640 480", "rotate -90", "teleport 1 2"] {
+        match command.parse() {
+            Ok(msg) => {
+                println!("{:?} reads as `{}`", command, msg);
+                println!("{}", message::describe(&msg));
+                parsed.push(msg);
+            },
+            Err(err) => println!("{:?}: {}", command, err)
+        }
+    }
+    let (rgb, hsv, _) = palette::split_by_space(parsed.clone());
+    println!("parsed colors: rgb {:?}, hsv {:?}", rgb, hsv);
+    let (texts, others) = message::partition_writes(parsed.clone());
+    println!("parsed {} writes {:?} and {} other messages", texts.len(), texts, others.len());
+    println!("parsed canvas: {:?}", canvas::run(parsed));
+
+    // moves and rotations undo by their inverse, the rest by replaying
+    let mut undoable = canvas::UndoableCanvas::new();
+    undoable.apply(Message::ChangeColor(Color::Rgb(0, 0, 255)));
+    undoable.apply(Message::Move { x: 3, y: 4 });
+    undoable.apply(Message::ChangeColor(Color::Rgb(255, 0, 0)));
+    while undoable.undo() {
+        println!("undone: {:?} at {:?}", undoable.canvas().color, undoable.canvas().position);
+    }
+
+    // a batch keeps going past the messages it rejects
+    let mut batch_canvas = canvas::Canvas::new();
+    let outcomes = canvas::process_batch(&mut batch_canvas, vec![
+        Message::Write(String::new()),
+        Message::ChangeColor(Color::Rgb(233, 33, 256)),
+        Message::Move { x: 1, y: 2 },
+        Message::Quit,
+        Message::Write(String::from("too late"))
+    ]);
+    println!("batch: {:?}", outcomes);
+    println!("summary: {:?}", canvas::BatchSummary::of(&outcomes));
+    println!("canonical: {:?}", message::canonicalize("  COLOR  Hsv 10 33   255"));
 
     // 4. destucting reference
-    let points = vec! [
+    let points = [
         Point { x: 0, y: 0},
         Point { x: 10, y: 10},
         Point { x: 8, y: 23}
     ];
 
-    let sum_of_squares = points
-        .iter()
-        .map(|&Point { x, y }| x * x + y * y)
-        .sum::<i32>();
-    println!("sum of squares: {}", sum_of_squares);
+    println!("sum of squares: {}", point::sum_of_squares(&points));
+    let halves = points.iter().map(|&p| point::map(p, |n| f64::from(n) / 2.0)).collect::<Vec<_>>();
+    println!("sum of squares, halved: {}", point::sum_of_squares(&halves));
+    println!("swapped: {:?}", point::swap(points[2]));
+    let (a, b) = (points[1], points[2]);
+    println!("a + b = {:?}, a - b = {:?}, -a * 3 = {:?}", a + b, a - b, -a * 3);
 
     // 5. destructuring structs and tuples
     // Destructuring with patterns is a convenient way to use pieces of values
     let ((feet, inches), Point { x, y }) = ((2, 12), Point { x: 4, y: 5 });
     println!("feet: {}, inches: {}, Point: ({}, {})", feet, inches, x, y);
 
-    // 6. ignoring values in a pattern
-    let mut setting_value = Some(5);
-    let new_setting_value = Some(10);
-    match (setting_value, new_setting_value) {
-        (Some(_), Some(_)) => {
-            println!("Can't overwrite an existing customized value");
-        },
-        _ => {
-            setting_value = new_setting_value;
-        }
+    // slice patterns: one arm per length, `rest @ ..` for the remainder
+    println!("{:?}", point::from_array([1, 2, 3]));
+    let slices: [&[i32]; 4] = [&[], &[5], &[3, -4], &[1, 2, 3, 4, 5]];
+    for coords in &slices {
+        println!("{:?} is {:?}", coords, point::classify(coords));
     }
-    println!("setting is {:?}", setting_value);
 
-    // use underscores in multiple places within one pattern to ignore particular values
-    let numbers = (1, 2, 3, 4, 5);
-    match numbers {
-        (first, _, third, _, fifth) => {
-            println!("Some numbers: {}, {}, {}", first, third, fifth);
-        }
-    }
+    // 6. ignoring values in a pattern
+    // Setting::try_update matches `(Some(_), Some(_))`: an existing value
+    // can't be overwritten, whatever it is
+    let mut setting = settings::Setting::new();
+    println!("{:?}", setting.try_update(Some(5)));
+    println!("{:?}", setting.try_update(Some(10)));
+    println!("{:?}", setting.try_update(None));
+    println!("setting is {:?}", setting.get());
+    println!("forced, it was {:?}", setting.force_update(None));
+    let mut clearable = settings::Setting::clearable();
+    clearable.try_update(Some("custom"));
+    println!("{:?}, now {:?}", clearable.try_update(None), clearable.get());
+
+    // use underscores in multiple places within one pattern to ignore
+    // particular values, and `..` to ignore however many there are
+    let numbers = [1, 2, 3, 4, 5];
+    println!(
+        "ends: {:?}, first two: {:?}, middle: {:?}",
+        slices::ends(&numbers), slices::first_two(&numbers), slices::middle(&numbers)
+    );
+    println!("middle of [1, 2]: {:?}", slices::middle(&numbers[..2]));
+    let temperatures = [3.5, 21.0, 19.5, 20.5, 40.0];
+    let samples = [4, 4, 4, 7, 4, 9];
+    println!(
+        "trimmed mean: {:?}, delta: {:?}, leading run: {:?}",
+        readings::trimmed_mean(&temperatures), readings::delta_endpoints(&samples), readings::leading_run(&samples)
+    );
+
+    // binding by reference: `ref`, `ref mut` and match ergonomics
+    let words = vec![String::from("match"), String::from("ergonomics")];
+    println!("{} chars, and words is still {:?}", bindings::sum_without_moving(&words), words);
+    let mut word = Some(String::from("binding"));
+    bindings::capitalize_first(&mut word);
+    println!("capitalized in place: {:?}", word);
+    bindings::capitalize_first_ergonomic(&mut word);
+    println!("taken: {:?}, left: {:?}", bindings::take_if_long(&mut word, 5), word);
 
     // ignoring an Unused Variable by Starting Its Name with `_`
     let _x = 5;
@@ -212,6 +355,10 @@ fn match_cases() {
     if let Some(_) = s {
         println!("found a string");
     }
+    println!("and `_` didn't move it: {:?}", underscore::some_underscore_keeps_the_string());
+    println!("Some(_g): {:?}", underscore::underscore_binding());
+    println!("Some(_) and let _: {:?}", underscore::bare_underscore());
+    println!("let _ = first, let _g = second: {:?}", underscore::let_underscore_vs_named());
 
     // Ignoring Remaining Parts of a Value with `..`
     // The .. pattern ignores any parts of a value that we 
@@ -224,6 +371,8 @@ fn match_cases() {
 }
 
 // 7. Extra Conditionals with Match Guards
+mod guards;
+
 /// A match guard is an additional if condition 
 /// specified after the pattern in a match arm 
 /// that must also match, along with the pattern 
@@ -239,26 +388,30 @@ fn match_guard() {
         Some(n) if n == y => println!("matched, n = {:?}", n),
         _ => println!("Default case")
     }
+
+    // guards on two bindings and an outer threshold
+    for pair in [(Some(3), 3), (Some(4), 1), (Some(4), -1), (Some(12), 0), (Some(7), 3), (None, 1)] {
+        println!("{:?} -> {:?}", pair, guards::compare_pair(pair, 10));
+    }
 }
 
 // 8. binding
+mod handshake;
+mod packet;
+
 /// The at operator (@) lets us create a variable that holds a value at 
 /// the same time we’re testing that value to see whether it matches a pattern. 
-enum Msg {
-    Hello { id: i32 }
-}
-
 fn binding_case() {
-    let msg = Msg::Hello { id: 5 };
-    match msg {
-        Msg::Hello { id: id_var @ 3...8 } => {
-            println!("Found an id in range: {}", id_var);
-        },
-        Msg::Hello { id: 10...13 } => {
-            println!("Nothing");
-        },
-        Msg::Hello { id } => {
-            println!("id: {}", id);
+    for id in [1, 2, 5, 9, 10, 14] {
+        let hello = handshake::Msg::Hello { id };
+        println!("id {}: {:?}, answered with {:?}", id, handshake::classify_id(&hello), handshake::respond(&hello));
+    }
+
+    // `@` on a slice pattern: the version it checked, and the rest
+    for bytes in [&[2, packet::COMPRESSED | packet::ACK, 0, 2, 0xbe, 0xef][..], &[1, packet::ENCRYPTED, 0, 0], &[1, 0, 0, 9, 1]] {
+        match packet::decode_header(bytes) {
+            Ok(header) => println!("{:?} decodes to {:?}", bytes, header),
+            Err(err) => println!("{:?}: {}", bytes, err)
         }
     }
 }
@@ -275,15 +428,239 @@ fn foo(_: i32, y: i32) {
     println!("this code only uses the y parameter: {}", y);
 }
 
+// a setting shared between threads behind a RwLock
+mod settings;
+
+fn shared_settings() {
+    let outcome = settings::race_readers_and_writer(8, 5, 10);
+    println!(
+        "writes: {:?}, final value: {:?}, inconsistent reads: {} of {}",
+        outcome.writes, outcome.final_value, outcome.inconsistent_reads, outcome.reads
+    );
+    let setting = settings::Settings::new();
+    setting.try_set(1).unwrap();
+    setting.clear();
+    println!("set again after clear: {:?}", setting.try_set(2));
+    setting.force_set(3);
+    println!("forced to {:?}", setting.get());
+}
+
+// reading Messages from text commands
+mod message;
+// applying them to a canvas
+mod canvas;
+// and summing them up, by reference
+mod summary;
+// or picking their colors out
+mod palette;
+
+// 9. matching on messages sent between threads
+mod worker;
+
+fn channel_worker() {
+    let state = worker::run_pipeline(vec![
+        Message::Move { x: 3, y: 4 },
+        Message::Write(String::from("hello")),
+        Message::ChangeColor(Color::Rgb(0, 160, 255)),
+        Message::Quit,
+        Message::Write(String::from("never applied"))
+    ]);
+    println!("worker state: {:?}", state);
+}
+
+// 10. a two-stage pipeline: parse lines in one thread, apply them in another
+mod pipeline;
+
+fn parse_and_apply() {
+    let lines = vec!["move 2 3", "color hsv 10 33 255", "write done", "quit"];
+    match pipeline::pipeline(lines.into_iter().map(String::from).collect()) {
+        Ok(result) => {
+            for snapshot in &result.snapshots {
+                println!("after line {}: {:?}", snapshot.line, snapshot.state);
+            }
+        },
+        Err(err) => println!("pipeline failed: {}", err)
+    }
+    if let Err(err) = pipeline::pipeline(vec![String::from("move 1 2"), String::from("jump 3")]) {
+        println!("pipeline failed: {}", err);
+    }
+}
+
+// 11. the same kind of hand-off through a bounded queue built on Condvar
+mod queue;
+
+fn bounded_queue() {
+    println!("consumer counted {} writes through a queue of 8", queue::transfer_writes(1000, 8));
+    let small = queue::BoundedQueue::new(1);
+    small.push(Message::Quit).unwrap();
+    println!("try_push on a full queue: {:?}", small.try_push(Message::Move { x: 1, y: 1 }));
+    small.close();
+    println!("after close: {:?}, then {:?}", small.pop(), small.try_pop());
+    println!("{} left, empty = {}", small.len(), small.is_empty());
+}
+
+// 12. the messages typed in: `cargo run -- --repl`
+mod repl;
+
+// 13. the guessing game, on Ordering: `cargo run -- --guess`
+mod guessing;
+
+// 14. a state machine made of matches
+mod traffic;
+
+fn traffic_light() {
+    use traffic::TrafficLight;
+    for light in traffic::simulate(TrafficLight::Red, 4) {
+        println!("{:?} for {:?}, can go: {}", light, light.duration(), light.can_go());
+    }
+    println!("green with a pedestrian waiting: {:?}", TrafficLight::Green.next_with_input(true));
+}
+
+// 15. a recursive enum, with the recursion in Boxes
+mod expr;
+
+fn expressions() {
+    use expr::{add, mul, neg, num, var};
+    let e = add(mul(var("x"), add(num(3), neg(num(2)))), mul(num(0), var("y")));
+    let env = vec![(String::from("x"), 6)].into_iter().collect();
+    println!("{} simplifies to {}", e, e.simplify());
+    match (e.eval(&env), e.simplify().eval(&env)) {
+        (Err(err), Ok(value)) => println!("{}, but simplified it's {}", err, value),
+        results => println!("{:?}", results)
+    }
+}
+
+// 16. another recursive type: a binary search tree
+mod bst;
+
+fn search_tree() {
+    let tree: bst::Bst = vec![8, 3, 10, 1, 6, 14, 4, 7, 13].into_iter().collect();
+    println!("in order {:?}, height {}", tree.in_order(), tree.height());
+    println!("has 6: {}, has 5: {}", tree.contains(6), tree.contains(5));
+}
+
+// 17. Option's combinators are matches too
+mod my_option;
+
+fn hand_rolled_option() {
+    use my_option::{MyOption, MySome, MyNone};
+    for text in &["42", "7", "x"] {
+        let parsed: MyOption<u32> = text.parse().ok().into();
+        let verdict = parsed
+            .filter(|n| n % 2 == 0)
+            .and_then(|n| if n < 100 { MySome(n * 2) } else { MyNone })
+            .map(|n| format!("doubled to {}", n))
+            .unwrap_or_else(|| String::from("not an even number under 100"));
+        println!("{:?}: {}", text, verdict);
+    }
+    let mut slot = MySome(1).zip(MySome('a'));
+    let taken: Option<(i32, char)> = slot.take().into();
+    println!("took {:?}, left {:?}, as a Result {:?}", taken, slot, slot.ok_or("empty"));
+}
+
+// 18. ranges, or-patterns, guards and bindings in one match
+mod http;
+
+fn http_statuses() {
+    let responses = [(204, None), (308, None), (404, Some("Not Found")), (429, None), (503, None), (304, None)];
+    for &(status, body) in responses.iter() {
+        let class = http::classify_response(status, body);
+        println!("{} -> {:?}, retry: {}", status, class, http::retryable(&class));
+    }
+}
+
+// 19. char ranges: chess squares
+mod chess;
+
+fn chess_moves() {
+    for text in &["e2e4", "g1 f3", "e2e9", "e2"] {
+        match chess::parse_move(text) {
+            Ok((from, to)) => println!("{} -> {}, landing on a {:?} square", from, to, to.color()),
+            Err(err) => println!("{:?}: {}", text, err)
+        }
+    }
+    if let Err(err) = chess::parse_square("i1") {
+        println!("{}", err);
+    }
+}
+
+// 20. a #[non_exhaustive] enum needs a wildcard arm
+mod outfit;
+
+fn what_to_wear() {
+    for forecast in ["sunny", "Rainy", "snowy", "hail"] {
+        match outfit::weather::Condition::parse(forecast) {
+            Some(condition) => println!("{:?}: wear {:?}", condition, outfit::outfit_for(&condition)),
+            None => println!("{:?} isn't a forecast", forecast)
+        }
+    }
+    println!("quit needs a canvas: {}", outfit::needs_a_canvas(&Message::Quit));
+}
+
+// 21. a tuple of Results, matched all at once
+mod results;
+
+fn combined_results() {
+    for (a, b) in [("20", "22"), ("20", "x"), ("y", "z")] {
+        println!("{} + {}: {:?}", a, b, results::combine(results::parse(a), results::parse(b)));
+    }
+    match results::sum_parsed(&["1", "two", "3", ""]) {
+        Ok(sum) => println!("sum {}", sum),
+        Err(errors) => {
+            for (index, err) in errors {
+                println!("input {}: {}", index, err);
+            }
+        }
+    }
+}
+
 fn main() {
+    if std::env::args().any(|arg| arg == "--repl") {
+        let stdin = std::io::stdin();
+        if let Err(err) = repl::repl(stdin.lock(), std::io::stdout()) {
+            println!("repl failed: {}", err);
+        }
+        return;
+    }
+    if std::env::args().any(|arg| arg == "--guess") {
+        guessing::play_interactive();
+        return;
+    }
+
     if_let_cases();
     while_let();
     for_loops_pattern();
 
     let point = (3, 5);
-    print_coordinates(&point);
-    match_cases();
+    print_tuple_coordinates(&point);
+    println!("{}", coordinates::format_coordinates(&(1.5, -2.0)));
+    println!("{}", (1, 2, 3).describe());
+    match "(3, 5)".parse::<PointI32>() {
+        Ok(point) => print_coordinates(&point),
+        Err(err) => println!("{}", err)
+    }
+    if let Err(err) = "3; 5".parse::<PointI32>() {
+        println!("{}", err);
+    }
+    let mut rng = thread_rng();
+    // rand 0.7 takes the range as two arguments
+    match_cases(|| rng.gen_range(0, 10));
+    // counting up from 1 always gets there, in `secret` guesses
+    println!("guessing game, counting up: {:?}", guessing::play(rng.gen_range(1, 101), 1..=100));
     foo(3, 4);
     match_guard();
     binding_case();
+    shared_settings();
+    channel_worker();
+    parse_and_apply();
+    bounded_queue();
+    refutability();
+    traffic_light();
+    expressions();
+    search_tree();
+    hand_rolled_option();
+    http_statuses();
+    chess_moves();
+    what_to_wear();
+    combined_results();
 }
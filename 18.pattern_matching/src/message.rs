@@ -0,0 +1,493 @@
+/// Reading a Message back from a command line, the same slice patterns as
+/// the demos but over words: `quit`, `move 4 7`, `write hello world`,
+/// `color rgb 233 33 255`, `color hsv 10 33 255`, `resize 640 480`,
+/// `rotate -90`. Keywords ignore case,
+/// the text of a `write` is kept as typed, or can be quoted, with `\"`
+/// and `\\` escapes, to keep surrounding spaces or an empty text. Color
+/// components and sizes aren't range checked here: any number of the
+/// right type goes through, what it means is up to whoever applies it.
+///
+/// Display writes the same syntax back, so `msg.to_string().parse()`
+/// gives `msg` again.
+use std::fmt;
+use std::str::FromStr;
+use crate::{Color, Message};
+
+const QUIT: &str = "quit";
+const MOVE: &str = "move <x> <y>";
+const WRITE: &str = "write <text>";
+const COLOR: &str = "color rgb|hsv <a> <b> <c>";
+const RESIZE: &str = "resize <width> <height>";
+const ROTATE: &str = "rotate <degrees>";
+const COMMANDS: &str = "quit, move, write, color, resize or rotate";
+
+/// Every variant but Empty carries `at`, the byte offset in the parsed
+/// text where the problem is: the start of the token, or for a missing
+/// argument the end of the text, where the argument should have followed.
+#[derive(Debug, PartialEq)]
+pub enum MessageParseError {
+  Empty,
+  UnknownCommand { token: String, at: usize },
+  /// a word that doesn't fit where it is, like `x` in `move x 7`
+  BadArgument { token: String, expected: &'static str, at: usize },
+  MissingArgument { expected: &'static str, at: usize },
+  ExtraArgument { token: String, expected: &'static str, at: usize }
+}
+
+impl MessageParseError {
+  /// The byte offset of the problem, 0 for an empty command.
+  pub fn offset(&self) -> usize {
+    match *self {
+      MessageParseError::Empty => 0,
+      MessageParseError::UnknownCommand { at, .. }
+      | MessageParseError::BadArgument { at, .. }
+      | MessageParseError::MissingArgument { at, .. }
+      | MessageParseError::ExtraArgument { at, .. } => at
+    }
+  }
+}
+
+impl fmt::Display for MessageParseError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      MessageParseError::Empty => write!(f, "empty command, expected {}", COMMANDS),
+      MessageParseError::UnknownCommand { token, .. } => write!(f, "unknown command {:?}, expected {}", token, COMMANDS),
+      MessageParseError::BadArgument { token, expected, .. } => write!(f, "can't use {:?}, expected `{}`", token, expected),
+      MessageParseError::MissingArgument { expected, .. } => write!(f, "missing argument, expected `{}`", expected),
+      MessageParseError::ExtraArgument { token, expected, .. } => write!(f, "unexpected {:?}, expected `{}`", token, expected)
+    }
+  }
+}
+
+/// A word and the byte offset it starts at.
+type Word<'a> = (usize, &'a str);
+
+/// The words of `s`, split on whitespace like `split_whitespace`.
+fn words(s: &str) -> Vec<Word<'_>> {
+  let mut words = vec![];
+  let mut start = None;
+  for (at, c) in s.char_indices() {
+    match (start, c.is_whitespace()) {
+      (None, false) => start = Some(at),
+      (Some(from), true) => {
+        words.push((from, &s[from..at]));
+        start = None;
+      },
+      _ => {}
+    }
+  }
+  if let Some(from) = start {
+    words.push((from, &s[from..]));
+  }
+  words
+}
+
+/// Exactly N numbers, or an error naming the first word that's wrong.
+/// `end` is where the text ends, for a missing argument.
+fn numbers<T, const N: usize>(args: &[Word], end: usize, expected: &'static str) -> Result<[T; N], MessageParseError>
+where
+  T: FromStr + Default + Copy
+{
+  if args.len() < N {
+    return Err(MessageParseError::MissingArgument { expected, at: end });
+  }
+  if let Some(&(at, extra)) = args.get(N) {
+    return Err(MessageParseError::ExtraArgument { token: extra.to_string(), expected, at });
+  }
+  let mut values = [T::default(); N];
+  for (value, &(at, arg)) in values.iter_mut().zip(args) {
+    *value = arg.parse().map_err(|_| MessageParseError::BadArgument { token: arg.to_string(), expected, at })?;
+  }
+  Ok(values)
+}
+
+/// The text of a quoted `write`, starting at its opening quote, which is
+/// at byte `at` of the whole command.
+fn unquote(payload: &str, at: usize) -> Result<String, MessageParseError> {
+  let bad = || MessageParseError::BadArgument { token: payload.to_string(), expected: WRITE, at };
+  let mut chars = payload[1..].chars();
+  let mut text = String::new();
+  while let Some(c) = chars.next() {
+    match c {
+      '\\' => match chars.next() {
+        Some(escaped @ ('"' | '\\')) => text.push(escaped),
+        _ => return Err(bad())
+      },
+      '"' => {
+        let rest = chars.as_str();
+        return match rest.trim() {
+          "" => Ok(text),
+          extra => Err(MessageParseError::ExtraArgument {
+            token: extra.to_string(),
+            expected: WRITE,
+            at: at + payload.len() - rest.trim_start().len()
+          })
+        }
+      },
+      c => text.push(c)
+    }
+  }
+  // the closing quote never came
+  Err(bad())
+}
+
+impl FromStr for Message {
+  type Err = MessageParseError;
+
+  fn from_str(s: &str) -> Result<Message, MessageParseError> {
+    let words = words(s);
+    let end = s.trim_end().len();
+    let (command, args) = match words.split_first() {
+      Some((&(_, command), args)) => (command.to_lowercase(), args),
+      None => return Err(MessageParseError::Empty)
+    };
+
+    match (command.as_str(), args) {
+      ("quit", []) => Ok(Message::Quit),
+      ("quit", [(at, extra), ..]) => Err(MessageParseError::ExtraArgument { token: extra.to_string(), expected: QUIT, at: *at }),
+      ("move", args) => numbers(args, end, MOVE).map(|[x, y]| Message::Move { x, y }),
+      ("write", []) => Err(MessageParseError::MissingArgument { expected: WRITE, at: end }),
+      // everything after the keyword, inner spacing included
+      ("write", [(at, _), ..]) => match s[*at..].trim_end() {
+        quoted if quoted.starts_with('"') => unquote(quoted, *at).map(Message::Write),
+        text => Ok(Message::Write(text.to_string()))
+      },
+      ("color", []) => Err(MessageParseError::MissingArgument { expected: COLOR, at: end }),
+      ("color", [(at, space), args @ ..]) => match space.to_lowercase().as_str() {
+        "rgb" => numbers(args, end, COLOR).map(|[r, g, b]| Message::ChangeColor(Color::Rgb(r, g, b))),
+        "hsv" => numbers(args, end, COLOR).map(|[h, s, v]| Message::ChangeColor(Color::Hsv(h, s, v))),
+        _ => Err(MessageParseError::BadArgument { token: space.to_string(), expected: COLOR, at: *at })
+      },
+      ("resize", args) => numbers(args, end, RESIZE).map(|[width, height]| Message::Resize { width, height }),
+      ("rotate", args) => numbers(args, end, ROTATE).map(|[degrees]| Message::Rotate(degrees)),
+      _ => Err(MessageParseError::UnknownCommand { token: words[0].1.to_string(), at: words[0].0 })
+    }
+  }
+}
+
+impl fmt::Display for Message {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Message::Quit => write!(f, "quit"),
+      Message::Move { x, y } => write!(f, "move {} {}", x, y),
+      // bare text would lose its outer spaces, or look like a quoted one
+      Message::Write(text) if text.is_empty() || text.contains(char::is_whitespace) || text.starts_with('"') => {
+        write!(f, "write \"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+      },
+      Message::Write(text) => write!(f, "write {}", text),
+      Message::ChangeColor(Color::Rgb(r, g, b)) => write!(f, "color rgb {} {} {}", r, g, b),
+      Message::ChangeColor(Color::Hsv(h, s, v)) => write!(f, "color hsv {} {} {}", h, s, v),
+      Message::Resize { width, height } => write!(f, "resize {} {}", width, height),
+      Message::Rotate(degrees) => write!(f, "rotate {}", degrees)
+    }
+  }
+}
+
+/// Matching on a reference binds the fields by reference too, so `text`
+/// is a `&String` here and nothing is moved out of `msg`.
+pub fn describe(msg: &Message) -> String {
+  match msg {
+    Message::Quit => String::from("Quit variant has not data to use"),
+    Message::Move { x, y } => format!("Move to ({} {})", x, y),
+    Message::Write(text) => format!("Write {}", text),
+    // destucting nested enums
+    Message::ChangeColor(Color::Rgb(r, g, b)) => format!("RGB Color red {}, green {}, blue {}", r, g, b),
+    Message::ChangeColor(Color::Hsv(h, s, v)) => format!("HSV Color hue {}, saturation {}, value {}", h, s, v),
+    Message::Resize { width, height } => format!("Resize to {}x{}", width, height),
+    Message::Rotate(degrees) => format!("Rotate by {} degrees", degrees)
+  }
+}
+
+impl Message {
+  pub fn is_quit(&self) -> bool {
+    matches!(self, Message::Quit)
+  }
+
+  pub fn is_move(&self) -> bool {
+    matches!(self, Message::Move { .. })
+  }
+
+  pub fn is_write(&self) -> bool {
+    matches!(self, Message::Write(_))
+  }
+
+  pub fn is_color_change(&self) -> bool {
+    matches!(self, Message::ChangeColor(_))
+  }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct KindCounts {
+  pub quits: usize,
+  pub moves: usize,
+  pub writes: usize,
+  pub color_changes: usize,
+  /// Resize and Rotate
+  pub other: usize
+}
+
+pub fn count_by_kind(msgs: &[Message]) -> KindCounts {
+  let mut counts = KindCounts::default();
+  for msg in msgs {
+    match msg {
+      Message::Quit => counts.quits += 1,
+      Message::Move { .. } => counts.moves += 1,
+      Message::Write(_) => counts.writes += 1,
+      Message::ChangeColor(_) => counts.color_changes += 1,
+      Message::Resize { .. } | Message::Rotate(_) => counts.other += 1
+    }
+  }
+  counts
+}
+
+/// Moves the text out of every Write, and everything else into the
+/// second Vec, both in their original order.
+pub fn partition_writes(msgs: Vec<Message>) -> (Vec<String>, Vec<Message>) {
+  let mut texts = vec![];
+  let mut rest = vec![];
+  for msg in msgs {
+    // `msg` is only moved from when the pattern matches
+    if let Message::Write(text) = msg {
+      texts.push(text);
+    } else {
+      rest.push(msg);
+    }
+  }
+  (texts, rest)
+}
+
+/// Parses a command and writes it back in the one canonical spelling.
+pub fn canonicalize(input: &str) -> Result<String, MessageParseError> {
+  input.parse::<Message>().map(|msg| msg.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse(s: &str) -> Result<Message, MessageParseError> {
+    s.parse()
+  }
+
+  #[test]
+  fn every_variant() {
+    assert_eq!(parse("quit"), Ok(Message::Quit));
+    assert_eq!(parse("move 4 -7"), Ok(Message::Move { x: 4, y: -7 }));
+    assert_eq!(parse("write hello world"), Ok(Message::Write(String::from("hello world"))));
+    assert_eq!(parse("color rgb 233 33 255"), Ok(Message::ChangeColor(Color::Rgb(233, 33, 255))));
+    assert_eq!(parse("color hsv 10 33 255"), Ok(Message::ChangeColor(Color::Hsv(10, 33, 255))));
+  }
+
+  #[test]
+  fn keywords_ignore_case_but_text_does_not() {
+    assert_eq!(parse("  QUIT "), Ok(Message::Quit));
+    assert_eq!(parse("Color RGB 1 2 3"), Ok(Message::ChangeColor(Color::Rgb(1, 2, 3))));
+    assert_eq!(parse("WRITE  Hello   World  "), Ok(Message::Write(String::from("Hello   World"))));
+  }
+
+  #[test]
+  fn missing_and_extra_arguments() {
+    assert_eq!(parse("move 4"), Err(MessageParseError::MissingArgument { expected: MOVE, at: 6 }));
+    assert_eq!(parse("write"), Err(MessageParseError::MissingArgument { expected: WRITE, at: 5 }));
+    assert_eq!(parse("color"), Err(MessageParseError::MissingArgument { expected: COLOR, at: 5 }));
+    assert_eq!(parse("color hsv 1 2"), Err(MessageParseError::MissingArgument { expected: COLOR, at: 13 }));
+    assert_eq!(parse("move 1 2 3"), Err(MessageParseError::ExtraArgument { token: String::from("3"), expected: MOVE, at: 9 }));
+    assert_eq!(parse("quit now"), Err(MessageParseError::ExtraArgument { token: String::from("now"), expected: QUIT, at: 5 }));
+  }
+
+  #[test]
+  fn bad_arguments_name_the_token() {
+    assert_eq!(parse("move x 7"), Err(MessageParseError::BadArgument { token: String::from("x"), expected: MOVE, at: 5 }));
+    assert_eq!(parse("color cmyk 1 2 3"), Err(MessageParseError::BadArgument { token: String::from("cmyk"), expected: COLOR, at: 6 }));
+    assert_eq!(parse("move 1 2").unwrap(), Message::Move { x: 1, y: 2 });
+    assert_eq!(parse("move 1 y").unwrap_err().to_string(), "can't use \"y\", expected `move <x> <y>`");
+  }
+
+  #[test]
+  fn offsets_point_at_the_right_occurrence() {
+    assert_eq!(parse("color rgb 1 rgb 3"), Err(MessageParseError::BadArgument {
+      token: String::from("rgb"),
+      expected: COLOR,
+      at: 12
+    }));
+    assert_eq!(parse("  move 1 1 1").unwrap_err().offset(), 11);
+    assert_eq!(parse("  jump").unwrap_err().offset(), 2);
+    assert_eq!(parse("rotate  \t").unwrap_err().offset(), 6);
+    assert_eq!(parse("é move").unwrap_err().offset(), 0);
+    assert_eq!(parse("move  é 1").unwrap_err().offset(), 6);
+    assert_eq!(parse("").unwrap_err().offset(), 0);
+  }
+
+  #[test]
+  fn color_ranges_are_left_to_the_color() {
+    assert_eq!(parse("color rgb 300 -1 0"), Ok(Message::ChangeColor(Color::Rgb(300, -1, 0))));
+    assert_eq!(parse("color rgb 99999999999 0 0"), Err(MessageParseError::BadArgument {
+      token: String::from("99999999999"),
+      expected: COLOR,
+      at: 10
+    }));
+  }
+
+  #[test]
+  fn unknown_and_empty() {
+    assert_eq!(parse("jump 3"), Err(MessageParseError::UnknownCommand { token: String::from("jump"), at: 0 }));
+    assert_eq!(parse(""), Err(MessageParseError::Empty));
+    assert_eq!(parse("   "), Err(MessageParseError::Empty));
+    assert_eq!(parse("").unwrap_err().to_string(), "empty command, expected quit, move, write, color, resize or rotate");
+  }
+
+  fn write(text: &str) -> Message {
+    Message::Write(String::from(text))
+  }
+
+  #[test]
+  fn display_round_trips() {
+    let tricky = [
+      Message::Quit,
+      Message::Move { x: -4, y: 0 },
+      Message::Move { x: i32::MIN, y: i32::MAX },
+      write(""),
+      write("plain"),
+      write("two  words"),
+      write("  padded "),
+      write("say \"hi\""),
+      write("\"starts quoted"),
+      write("back\\slash"),
+      write("a \\\" mix"),
+      write("tab\there"),
+      Message::ChangeColor(Color::Rgb(233, 33, 255)),
+      Message::ChangeColor(Color::Hsv(-10, 0, 300)),
+      Message::Resize { width: 0, height: u32::MAX },
+      Message::Rotate(-450)
+    ];
+    for msg in &tricky {
+      assert_eq!(parse(&msg.to_string()).as_ref(), Ok(msg), "through {:?}", msg.to_string());
+    }
+  }
+
+  #[test]
+  fn display_quotes_only_when_needed() {
+    assert_eq!(write("plain").to_string(), "write plain");
+    assert_eq!(write("").to_string(), "write \"\"");
+    assert_eq!(write("say \"hi\"").to_string(), "write \"say \\\"hi\\\"\"");
+    assert_eq!(Message::ChangeColor(Color::Hsv(10, 33, 255)).to_string(), "color hsv 10 33 255");
+  }
+
+  #[test]
+  fn quoted_write_errors() {
+    assert_eq!(parse("write \"open"), Err(MessageParseError::BadArgument { token: String::from("\"open"), expected: WRITE, at: 6 }));
+    assert_eq!(parse("write \"bad \\q\""), Err(MessageParseError::BadArgument {
+      token: String::from("\"bad \\q\""),
+      expected: WRITE,
+      at: 6
+    }));
+    assert_eq!(parse("write \"done\" extra"), Err(MessageParseError::ExtraArgument {
+      token: String::from("extra"),
+      expected: WRITE,
+      at: 13
+    }));
+  }
+
+  #[test]
+  fn canonicalize_normalizes_spacing_and_case() {
+    assert_eq!(canonicalize("  MOVE   4\t7 "), Ok(String::from("move 4 7")));
+    assert_eq!(canonicalize("Color HSV 10  33 255"), Ok(String::from("color hsv 10 33 255")));
+    assert_eq!(canonicalize("Quit"), Ok(String::from("quit")));
+    assert_eq!(canonicalize("WRITE Hello   World"), Ok(String::from("write \"Hello   World\"")));
+    assert_eq!(canonicalize("write \"single\""), Ok(String::from("write single")));
+    assert_eq!(canonicalize("move 1"), Err(MessageParseError::MissingArgument { expected: MOVE, at: 6 }));
+  }
+
+  #[test]
+  fn resize_and_rotate() {
+    assert_eq!(parse("resize 640 480"), Ok(Message::Resize { width: 640, height: 480 }));
+    assert_eq!(parse("RESIZE 0 1"), Ok(Message::Resize { width: 0, height: 1 }));
+    assert_eq!(parse("rotate -90"), Ok(Message::Rotate(-90)));
+    assert_eq!(parse("resize -1 5"), Err(MessageParseError::BadArgument { token: String::from("-1"), expected: RESIZE, at: 7 }));
+    assert_eq!(parse("rotate"), Err(MessageParseError::MissingArgument { expected: ROTATE, at: 6 }));
+    assert_eq!(parse("rotate 90 90"), Err(MessageParseError::ExtraArgument { token: String::from("90"), expected: ROTATE, at: 10 }));
+    assert_eq!(Message::Resize { width: 3, height: 4 }.to_string(), "resize 3 4");
+  }
+
+  #[test]
+  fn describe_every_variant() {
+    let described = [
+      (Message::Quit, "Quit variant has not data to use"),
+      (Message::Move { x: 4, y: -7 }, "Move to (4 -7)"),
+      (write("nothing"), "Write nothing"),
+      (Message::ChangeColor(Color::Rgb(233, 33, 255)), "RGB Color red 233, green 33, blue 255"),
+      (Message::ChangeColor(Color::Hsv(10, 33, 255)), "HSV Color hue 10, saturation 33, value 255"),
+      (Message::Resize { width: 640, height: 480 }, "Resize to 640x480"),
+      (Message::Rotate(-90), "Rotate by -90 degrees")
+    ];
+    for (msg, expected) in &described {
+      assert_eq!(describe(msg), *expected);
+    }
+  }
+
+  #[test]
+  fn a_described_message_is_still_usable() {
+    let msg = write("keep me");
+    assert_eq!(describe(&msg), "Write keep me");
+    assert_eq!(describe(&msg), describe(&msg));
+    // still ours to move
+    let moved = vec![msg];
+    assert_eq!(moved, [write("keep me")]);
+  }
+
+  #[test]
+  fn predicates() {
+    let all = [
+      Message::Quit,
+      Message::Move { x: 1, y: 2 },
+      write("w"),
+      Message::ChangeColor(Color::Rgb(1, 2, 3)),
+      Message::Rotate(5)
+    ];
+    let flags: Vec<[bool; 4]> = all.iter().map(|m| [m.is_quit(), m.is_move(), m.is_write(), m.is_color_change()]).collect();
+    assert_eq!(flags, [
+      [true, false, false, false],
+      [false, true, false, false],
+      [false, false, true, false],
+      [false, false, false, true],
+      [false, false, false, false]
+    ]);
+  }
+
+  #[test]
+  fn counts_over_a_mix() {
+    let msgs = [
+      write("a"),
+      Message::Move { x: 0, y: 0 },
+      write("b"),
+      Message::Resize { width: 1, height: 1 },
+      Message::ChangeColor(Color::Hsv(1, 2, 3)),
+      Message::Quit,
+      Message::Rotate(1)
+    ];
+    assert_eq!(count_by_kind(&msgs), KindCounts { quits: 1, moves: 1, writes: 2, color_changes: 1, other: 2 });
+    assert_eq!(count_by_kind(&[]), KindCounts::default());
+  }
+
+  #[test]
+  fn partition_keeps_both_orders() {
+    let (texts, rest) = partition_writes(vec![
+      write("one"),
+      Message::Rotate(90),
+      write("two"),
+      Message::Quit,
+      Message::Move { x: 1, y: 1 },
+      write("three")
+    ]);
+    assert_eq!(texts, ["one", "two", "three"]);
+    assert_eq!(rest, [Message::Rotate(90), Message::Quit, Message::Move { x: 1, y: 1 }]);
+  }
+
+  #[test]
+  fn partition_all_quits() {
+    let (texts, rest) = partition_writes(vec![Message::Quit; 3]);
+    assert!(texts.is_empty());
+    assert_eq!(rest, vec![Message::Quit; 3]);
+    assert_eq!(count_by_kind(&rest).quits, 3);
+  }
+}
@@ -0,0 +1,151 @@
+/// Option's combinators written out by hand: each one is a single match
+/// on the two variants, which is all the std versions are too. The From
+/// impls go both ways, so a MyOption can stand in for an Option and back.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MyOption<T> {
+  MySome(T),
+  MyNone
+}
+
+pub use MyOption::{MyNone, MySome};
+
+impl<T> MyOption<T> {
+  pub fn map<U>(self, f: impl FnOnce(T) -> U) -> MyOption<U> {
+    match self {
+      MySome(value) => MySome(f(value)),
+      MyNone => MyNone
+    }
+  }
+
+  pub fn and_then<U>(self, f: impl FnOnce(T) -> MyOption<U>) -> MyOption<U> {
+    match self {
+      MySome(value) => f(value),
+      MyNone => MyNone
+    }
+  }
+
+  pub fn unwrap_or(self, default: T) -> T {
+    match self {
+      MySome(value) => value,
+      MyNone => default
+    }
+  }
+
+  pub fn unwrap_or_else(self, f: impl FnOnce() -> T) -> T {
+    match self {
+      MySome(value) => value,
+      MyNone => f()
+    }
+  }
+
+  pub fn filter(self, predicate: impl FnOnce(&T) -> bool) -> MyOption<T> {
+    match self {
+      MySome(value) if predicate(&value) => MySome(value),
+      _ => MyNone
+    }
+  }
+
+  pub fn zip<U>(self, other: MyOption<U>) -> MyOption<(T, U)> {
+    match (self, other) {
+      (MySome(a), MySome(b)) => MySome((a, b)),
+      _ => MyNone
+    }
+  }
+
+  pub fn ok_or<E>(self, err: E) -> Result<T, E> {
+    match self {
+      MySome(value) => Ok(value),
+      MyNone => Err(err)
+    }
+  }
+
+  /// Moves the value out and leaves MyNone in its place.
+  pub fn take(&mut self) -> MyOption<T> {
+    std::mem::replace(self, MyNone)
+  }
+}
+
+impl<T> From<Option<T>> for MyOption<T> {
+  fn from(option: Option<T>) -> MyOption<T> {
+    match option {
+      Some(value) => MySome(value),
+      None => MyNone
+    }
+  }
+}
+
+impl<T> From<MyOption<T>> for Option<T> {
+  fn from(option: MyOption<T>) -> Option<T> {
+    match option {
+      MySome(value) => Some(value),
+      MyNone => None
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::cell::Cell;
+
+  const NONE: MyOption<i32> = MyNone;
+
+  #[test]
+  fn map_and_unwraps() {
+    assert_eq!(MySome(2).map(|n| n * 10), MySome(20));
+    assert_eq!(NONE.map(|n| n * 10), MyNone);
+    assert_eq!(MySome(2).unwrap_or(7), 2);
+    assert_eq!(NONE.unwrap_or(7), 7);
+    assert_eq!(MySome(2).unwrap_or_else(|| panic!("not needed")), 2);
+    assert_eq!(NONE.unwrap_or_else(|| 9), 9);
+  }
+
+  #[test]
+  fn and_then_short_circuits() {
+    let calls = Cell::new(0);
+    let half = |n: i32| {
+      calls.set(calls.get() + 1);
+      if n % 2 == 0 { MySome(n / 2) } else { MyNone }
+    };
+    assert_eq!(MySome(8).and_then(half).and_then(half), MySome(2));
+    assert_eq!(calls.get(), 2);
+    assert_eq!(MySome(6).and_then(half).and_then(half).and_then(half), MyNone);
+    assert_eq!(calls.get(), 4, "the third call never happens");
+    assert_eq!(NONE.and_then(half), MyNone);
+    assert_eq!(calls.get(), 4);
+  }
+
+  #[test]
+  fn filter_and_zip() {
+    assert_eq!(MySome(4).filter(|n| *n > 3), MySome(4));
+    assert_eq!(MySome(2).filter(|n| *n > 3), MyNone);
+    assert_eq!(NONE.filter(|_| true), MyNone);
+    assert_eq!(MySome(1).zip(MySome("a")), MySome((1, "a")));
+    assert_eq!(MySome(1).zip(MyNone::<&str>), MyNone);
+    assert_eq!(NONE.zip(MySome("a")), MyNone);
+  }
+
+  #[test]
+  fn ok_or() {
+    assert_eq!(MySome(1).ok_or("missing"), Ok(1));
+    assert_eq!(NONE.ok_or("missing"), Err("missing"));
+  }
+
+  #[test]
+  fn take_leaves_none() {
+    let mut slot = MySome(String::from("taken"));
+    assert_eq!(slot.take(), MySome(String::from("taken")));
+    assert_eq!(slot, MyNone);
+    assert_eq!(slot.take(), MyNone);
+  }
+
+  #[test]
+  fn converts_both_ways() {
+    assert_eq!(MyOption::from(Some(3)), MySome(3));
+    assert_eq!(MyOption::<i32>::from(None), MyNone);
+    let back: Option<i32> = MySome(3).into();
+    assert_eq!(back, Some(3));
+    let parsed: MyOption<i32> = "12".parse().ok().into();
+    assert_eq!(Option::from(parsed.map(|n| n + 1)), Some(13));
+  }
+}
@@ -0,0 +1,118 @@
+/// Literal, range and or-patterns over a number. The arms are tried in
+/// order and the last one catches whatever is left, 0 included.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumberCategory {
+  One,
+  TwoToFive,
+  SixOrSeven,
+  Large
+}
+
+pub fn categorize_number(x: u32) -> NumberCategory {
+  match x {
+    1 => NumberCategory::One,
+    // ranges are only allowed with numeric values or char values
+    2..=5 => NumberCategory::TwoToFive,
+    6 | 7 => NumberCategory::SixOrSeven,
+    _ => NumberCategory::Large
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OptionCategory {
+  OneOrTwo,
+  Few(u32),
+  Lucky(u32),
+  Other(u32),
+  Nothing
+}
+
+/// The same kind of arms one level down, inside Some: an or-pattern
+/// nested in the variant, and `@` keeping the number a range or an
+/// or-pattern matched.
+pub fn categorize_option(x: Option<u32>) -> OptionCategory {
+  match x {
+    Some(1 | 2) => OptionCategory::OneOrTwo,
+    Some(n @ 3..=5) => OptionCategory::Few(n),
+    Some(n @ (7 | 77 | 777)) => OptionCategory::Lucky(n),
+    Some(n) => OptionCategory::Other(n),
+    None => OptionCategory::Nothing
+  }
+}
+
+/// Draws `count` numbers from `next` and categorizes each one. Taking the
+/// source as a closure lets main pass a random one and tests a script.
+pub fn draw_categories(mut next: impl FnMut() -> u32, count: usize) -> Vec<(u32, NumberCategory)> {
+  (0..count)
+    .map(|_| {
+      let x = next();
+      (x, categorize_number(x))
+    })
+    .collect()
+}
+
+/// How many samples fell in each category, in declaration order.
+pub fn category_histogram(samples: impl Iterator<Item = u32>) -> [usize; 4] {
+  let mut counts = [0; 4];
+  for x in samples {
+    counts[categorize_number(x) as usize] += 1;
+  }
+  counts
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn boundaries() {
+    let expected = [
+      (0, NumberCategory::Large),
+      (1, NumberCategory::One),
+      (2, NumberCategory::TwoToFive),
+      (5, NumberCategory::TwoToFive),
+      (6, NumberCategory::SixOrSeven),
+      (7, NumberCategory::SixOrSeven),
+      (8, NumberCategory::Large),
+      (9, NumberCategory::Large)
+    ];
+    for &(x, category) in &expected {
+      assert_eq!(categorize_number(x), category, "{}", x);
+    }
+  }
+
+  #[test]
+  fn option_boundaries() {
+    let expected = [
+      (None, OptionCategory::Nothing),
+      (Some(0), OptionCategory::Other(0)),
+      (Some(1), OptionCategory::OneOrTwo),
+      (Some(2), OptionCategory::OneOrTwo),
+      (Some(3), OptionCategory::Few(3)),
+      (Some(5), OptionCategory::Few(5)),
+      (Some(6), OptionCategory::Other(6)),
+      (Some(7), OptionCategory::Lucky(7)),
+      (Some(76), OptionCategory::Other(76)),
+      (Some(77), OptionCategory::Lucky(77)),
+      (Some(777), OptionCategory::Lucky(777))
+    ];
+    for &(x, category) in &expected {
+      assert_eq!(categorize_option(x), category, "{:?}", x);
+    }
+  }
+
+  #[test]
+  fn scripted_draws() {
+    let mut script = vec![7, 1, 3].into_iter();
+    let draws = draw_categories(|| script.next().unwrap(), 3);
+    assert_eq!(draws, [(7, NumberCategory::SixOrSeven), (1, NumberCategory::One), (3, NumberCategory::TwoToFive)]);
+  }
+
+  #[test]
+  fn histogram() {
+    let samples = vec![0, 1, 1, 2, 3, 4, 5, 6, 7, 8, 9, 100];
+    assert_eq!(category_histogram(samples.into_iter()), [2, 4, 2, 4]);
+    assert_eq!(category_histogram(std::iter::empty()), [0; 4]);
+  }
+}
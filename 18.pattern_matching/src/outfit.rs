@@ -0,0 +1,75 @@
+/// Two matches side by side: one over the `#[non_exhaustive]` Condition
+/// from the `weather` submodule, which keeps a wildcard arm for variants
+/// added later, and one over Message, which lists every variant so the
+/// compiler flags it when Message grows.
+///
+/// `#[non_exhaustive]` only binds other crates. Inside this one the
+/// compiler still knows every Condition, so the wildcard is unreachable
+/// outside of tests, where the extra `Foggy` variant exists. It's kept
+/// because it's what a consumer in another crate has to write.
+pub mod weather;
+
+use crate::Message;
+use weather::Condition;
+
+#[derive(Debug, PartialEq)]
+pub enum Outfit {
+  TShirt,
+  Raincoat,
+  WinterCoat,
+  /// for weather nobody planned for
+  Layers
+}
+
+#[allow(unreachable_patterns)]
+pub fn outfit_for(c: &Condition) -> Outfit {
+  match c {
+    Condition::Sunny => Outfit::TShirt,
+    Condition::Rainy => Outfit::Raincoat,
+    Condition::Snowy => Outfit::WinterCoat,
+    _ => Outfit::Layers
+  }
+}
+
+/// No wildcard: a new Message variant is a compile error here.
+pub fn needs_a_canvas(msg: &Message) -> bool {
+  match msg {
+    Message::Quit => false,
+    Message::Move { .. } | Message::Write(_) | Message::ChangeColor(_) | Message::Resize { .. } | Message::Rotate(_) => true
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn known_conditions() {
+    assert_eq!(outfit_for(&Condition::Sunny), Outfit::TShirt);
+    assert_eq!(outfit_for(&Condition::Rainy), Outfit::Raincoat);
+    assert_eq!(outfit_for(&Condition::Snowy), Outfit::WinterCoat);
+  }
+
+  #[test]
+  fn parsing() {
+    assert_eq!(Condition::parse(" Sunny"), Some(Condition::Sunny));
+    assert_eq!(Condition::parse("SNOWY"), Some(Condition::Snowy));
+    assert_eq!(Condition::parse(""), None);
+    assert_eq!(Condition::parse("hail"), None);
+    assert_eq!(Condition::parse("rain"), None);
+  }
+
+  #[test]
+  fn a_new_variant_falls_back() {
+    // outfit_for was written without Foggy and still compiles and works
+    let foggy = Condition::parse("foggy").unwrap();
+    assert_eq!(outfit_for(&foggy), Outfit::Layers);
+  }
+
+  #[test]
+  fn only_quit_skips_the_canvas() {
+    assert!(!needs_a_canvas(&Message::Quit));
+    assert!(needs_a_canvas(&Message::Rotate(90)));
+    assert!(needs_a_canvas(&Message::Write(String::new())));
+  }
+}
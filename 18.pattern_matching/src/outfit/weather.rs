@@ -0,0 +1,26 @@
+/// Today's weather. It's `#[non_exhaustive]`: more conditions may be
+/// added, so a match on a Condition outside this crate has to end with a
+/// wildcard arm for the ones it doesn't know about yet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum Condition {
+  Sunny,
+  Rainy,
+  Snowy,
+  /// Only there in tests, standing in for a variant added later
+  #[cfg(test)]
+  Foggy
+}
+
+impl Condition {
+  pub fn parse(s: &str) -> Option<Condition> {
+    match s.trim().to_lowercase().as_str() {
+      "sunny" => Some(Condition::Sunny),
+      "rainy" => Some(Condition::Rainy),
+      "snowy" => Some(Condition::Snowy),
+      #[cfg(test)]
+      "foggy" => Some(Condition::Foggy),
+      _ => None
+    }
+  }
+}
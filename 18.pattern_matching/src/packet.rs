@@ -0,0 +1,134 @@
+/// A made-up packet format: a version byte, a flags byte, the payload
+/// length as two big-endian bytes, then the payload. One slice pattern
+/// takes the header apart, `@` keeps the version it checked and the bytes
+/// after the header, and guards look at the flag bits and the length.
+///
+/// Flags: bit 0 compressed, bit 1 encrypted (version 2 only), bit 2
+/// wants an ack; the other bits are reserved and must be 0.
+use std::fmt;
+
+pub const COMPRESSED: u8 = 0b001;
+pub const ENCRYPTED: u8 = 0b010;
+pub const ACK: u8 = 0b100;
+const RESERVED: u8 = !(COMPRESSED | ENCRYPTED | ACK);
+
+#[derive(Debug, PartialEq)]
+pub struct Header<'a> {
+  pub version: u8,
+  pub compressed: bool,
+  pub encrypted: bool,
+  pub ack: bool,
+  pub payload: &'a [u8]
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DecodeError {
+  Empty,
+  UnsupportedVersion(u8),
+  TruncatedHeader(usize),
+  ReservedFlags(u8),
+  EncryptedV1,
+  TruncatedPayload { declared: usize, available: usize },
+  TrailingBytes(usize)
+}
+
+impl fmt::Display for DecodeError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      DecodeError::Empty => write!(f, "no bytes"),
+      DecodeError::UnsupportedVersion(version) => write!(f, "version {} isn't supported", version),
+      DecodeError::TruncatedHeader(len) => write!(f, "the header is 4 bytes, got {}", len),
+      DecodeError::ReservedFlags(flags) => write!(f, "reserved flag bits set in {:#010b}", flags),
+      DecodeError::EncryptedV1 => write!(f, "version 1 packets can't be encrypted"),
+      DecodeError::TruncatedPayload { declared, available } => {
+        write!(f, "payload of {} bytes declared, {} there", declared, available)
+      }
+      DecodeError::TrailingBytes(extra) => write!(f, "{} bytes after the payload", extra)
+    }
+  }
+}
+
+pub fn decode_header(bytes: &[u8]) -> Result<Header<'_>, DecodeError> {
+  match bytes {
+    [] => Err(DecodeError::Empty),
+    &[version @ 1..=2, flags, len_hi, len_lo, ref rest @ ..] => {
+      match (version, flags) {
+        (_, flags) if flags & RESERVED != 0 => return Err(DecodeError::ReservedFlags(flags)),
+        (1, flags) if flags & ENCRYPTED != 0 => return Err(DecodeError::EncryptedV1),
+        _ => {}
+      }
+      let payload = match usize::from(u16::from_be_bytes([len_hi, len_lo])) {
+        len if len > rest.len() => {
+          return Err(DecodeError::TruncatedPayload { declared: len, available: rest.len() })
+        }
+        len if len < rest.len() => return Err(DecodeError::TrailingBytes(rest.len() - len)),
+        _ => rest
+      };
+      Ok(Header {
+        version,
+        compressed: flags & COMPRESSED != 0,
+        encrypted: flags & ENCRYPTED != 0,
+        ack: flags & ACK != 0,
+        payload
+      })
+    }
+    [1..=2, ..] => Err(DecodeError::TruncatedHeader(bytes.len())),
+    &[version, ..] => Err(DecodeError::UnsupportedVersion(version))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn version_one() {
+    assert_eq!(decode_header(&[1, 0, 0, 3, b'a', b'b', b'c']), Ok(Header {
+      version: 1,
+      compressed: false,
+      encrypted: false,
+      ack: false,
+      payload: b"abc"
+    }));
+    assert_eq!(decode_header(&[1, ACK, 0, 0]).map(|header| (header.ack, header.payload.len())), Ok((true, 0)));
+  }
+
+  #[test]
+  fn version_two() {
+    let mut packet = vec![2, COMPRESSED | ENCRYPTED, 1, 0];
+    packet.extend([7; 256]);
+    let header = decode_header(&packet).unwrap();
+    assert_eq!((header.version, header.compressed, header.encrypted, header.ack), (2, true, true, false));
+    assert_eq!(header.payload.len(), 256);
+  }
+
+  #[test]
+  fn unsupported_versions() {
+    assert_eq!(decode_header(&[0, 0, 0, 0]), Err(DecodeError::UnsupportedVersion(0)));
+    assert_eq!(decode_header(&[3]), Err(DecodeError::UnsupportedVersion(3)));
+    assert_eq!(decode_header(&[255, 0, 0, 0]).unwrap_err().to_string(), "version 255 isn't supported");
+  }
+
+  #[test]
+  fn truncated() {
+    assert_eq!(decode_header(&[]), Err(DecodeError::Empty));
+    assert_eq!(decode_header(&[2, 0, 0]), Err(DecodeError::TruncatedHeader(3)));
+    assert_eq!(decode_header(&[1, 0, 0, 5, 1, 2]), Err(DecodeError::TruncatedPayload { declared: 5, available: 2 }));
+    assert_eq!(decode_header(&[1, 0, 0, 1, 1, 2]), Err(DecodeError::TrailingBytes(1)));
+  }
+
+  #[test]
+  fn flag_combinations() {
+    for flags in 0..=0b111 {
+      let packet = [2, flags, 0, 0];
+      let decoded = decode_header(&packet).unwrap();
+      assert_eq!(
+        (decoded.compressed, decoded.encrypted, decoded.ack),
+        (flags & 1 != 0, flags & 2 != 0, flags & 4 != 0),
+        "{:03b}", flags
+      );
+    }
+    assert_eq!(decode_header(&[1, ENCRYPTED | ACK, 0, 0]), Err(DecodeError::EncryptedV1));
+    assert_eq!(decode_header(&[2, 0b1000, 0, 0]), Err(DecodeError::ReservedFlags(0b1000)));
+  }
+}
@@ -0,0 +1,89 @@
+/// Picking colors out of messages with patterns two levels deep: the
+/// Message variant, then the Color variant inside it, with guards on the
+/// components and `..` for the ones that don't matter.
+use crate::{Color, Message};
+
+/// Colors that lean red: Rgb with more red than blue, or an Hsv hue
+/// within 60 degrees of red. The bounds are exclusive, so hues 60 (yellow)
+/// and 300 (magenta) aren't warm.
+pub fn warm_colors(messages: &[Message]) -> Vec<&Color> {
+  let mut warm = vec![];
+  for msg in messages {
+    match msg {
+      Message::ChangeColor(color @ Color::Rgb(r, _, b)) if r > b => warm.push(color),
+      Message::ChangeColor(color @ Color::Hsv(h, ..)) if *h < 60 || *h > 300 => warm.push(color),
+      _ => {}
+    }
+  }
+  warm
+}
+
+/// Rgb colors, Hsv colors and every other message, each moved out in its
+/// original order.
+pub fn split_by_space(messages: Vec<Message>) -> (Vec<Color>, Vec<Color>, Vec<Message>) {
+  let (mut rgb, mut hsv, mut rest) = (vec![], vec![], vec![]);
+  for msg in messages {
+    match msg {
+      Message::ChangeColor(color @ Color::Rgb(..)) => rgb.push(color),
+      Message::ChangeColor(color @ Color::Hsv(..)) => hsv.push(color),
+      other => rest.push(other)
+    }
+  }
+  (rgb, hsv, rest)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn color(color: Color) -> Message {
+    Message::ChangeColor(color)
+  }
+
+  #[test]
+  fn warm_from_a_mix() {
+    let messages = vec![
+      color(Color::Rgb(255, 0, 0)),
+      Message::Write(String::from("red")),
+      color(Color::Rgb(0, 0, 255)),
+      color(Color::Hsv(20, 100, 100)),
+      color(Color::Hsv(200, 100, 100)),
+      Message::Quit,
+      color(Color::Hsv(330, 50, 50)),
+      color(Color::Rgb(200, 255, 120))
+    ];
+    let warm = warm_colors(&messages);
+    assert_eq!(warm, [&Color::Rgb(255, 0, 0), &Color::Hsv(20, 100, 100), &Color::Hsv(330, 50, 50), &Color::Rgb(200, 255, 120)]);
+    assert!(warm_colors(&[]).is_empty());
+  }
+
+  #[test]
+  fn borderline() {
+    let hues: Vec<Message> = [0, 59, 60, 300, 301, 359].iter().map(|&h| color(Color::Hsv(h, 100, 100))).collect();
+    let warm: Vec<i32> = warm_colors(&hues)
+      .into_iter()
+      .map(|color| match color {
+        Color::Hsv(h, ..) => *h,
+        Color::Rgb(..) => unreachable!()
+      })
+      .collect();
+    assert_eq!(warm, [0, 59, 301, 359]);
+    // equal red and blue isn't warm either
+    assert!(warm_colors(&[color(Color::Rgb(100, 0, 100))]).is_empty());
+  }
+
+  #[test]
+  fn split_keeps_order() {
+    let (rgb, hsv, rest) = split_by_space(vec![
+      color(Color::Hsv(1, 2, 3)),
+      color(Color::Rgb(1, 2, 3)),
+      Message::Move { x: 1, y: 1 },
+      color(Color::Rgb(4, 5, 6)),
+      Message::Quit,
+      color(Color::Hsv(4, 5, 6))
+    ]);
+    assert_eq!(rgb, [Color::Rgb(1, 2, 3), Color::Rgb(4, 5, 6)]);
+    assert_eq!(hsv, [Color::Hsv(1, 2, 3), Color::Hsv(4, 5, 6)]);
+    assert_eq!(rest, [Message::Move { x: 1, y: 1 }, Message::Quit]);
+  }
+}
@@ -0,0 +1,166 @@
+/// Two stages, two channels: a parser thread turns text lines into
+/// Message values for an applier thread, which folds them into a
+/// WorkerState and reports a snapshot after each one. When a stage stops,
+/// early or not, it drops its Sender, and the stage after it sees its
+/// `recv` fail and stops too, so nothing is left waiting. The parser
+/// stops at Quit itself, so lines after it are never looked at.
+use std::fmt;
+use std::sync::mpsc;
+use std::thread;
+use crate::message::MessageParseError;
+use crate::worker::WorkerState;
+use crate::Message;
+
+#[derive(Debug, PartialEq)]
+pub struct PipelineError {
+  /// 1-based, like an editor shows it
+  pub line: usize,
+  pub text: String,
+  /// why the line didn't parse
+  pub error: MessageParseError
+}
+
+impl fmt::Display for PipelineError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "line {}: can't parse {:?}: {}", self.line, self.text, self.error)
+  }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Snapshot {
+  pub line: usize,
+  pub state: WorkerState
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FinalState {
+  pub state: WorkerState,
+  pub snapshots: Vec<Snapshot>
+}
+
+pub fn pipeline(lines: Vec<String>) -> Result<FinalState, PipelineError> {
+  let (message_tx, message_rx) = mpsc::channel();
+  let (snapshot_tx, snapshot_rx) = mpsc::channel();
+
+  let parser = thread::spawn(move || {
+    for (index, text) in lines.into_iter().enumerate() {
+      let msg = match text.parse::<Message>() {
+        Ok(msg) => msg,
+        Err(error) => return Err(PipelineError { line: index + 1, text, error })
+      };
+      let quit = msg.is_quit();
+      // the applier stopped early, the rest would go nowhere
+      if message_tx.send((index + 1, msg)).is_err() {
+        break;
+      }
+      // the applier stops at Quit, so whatever follows is never parsed
+      if quit {
+        break;
+      }
+    }
+    Ok(())
+  });
+
+  let applier = thread::spawn(move || {
+    let mut state = WorkerState::default();
+    while let Ok((line, msg)) = message_rx.recv() {
+      let keep_going = state.apply(msg);
+      // the caller only goes away once the pipeline is over
+      snapshot_tx.send(Snapshot { line, state: state.clone() }).ok();
+      if !keep_going {
+        break;
+      }
+    }
+    state
+  });
+
+  let mut snapshots = vec![];
+  while let Ok(snapshot) = snapshot_rx.recv() {
+    snapshots.push(snapshot);
+  }
+
+  let parsed = parser.join().unwrap();
+  let state = applier.join().unwrap();
+  parsed.map(|()| FinalState { state, snapshots })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::Color;
+
+  fn lines(lines: &[&str]) -> Vec<String> {
+    lines.iter().map(|line| line.to_string()).collect()
+  }
+
+  #[test]
+  fn clean_run_has_one_snapshot_per_message() {
+    let result = pipeline(lines(&["move 1 2", "write hello  world", "color hsv 1 2 3", "move -3 4"])).unwrap();
+    assert_eq!(result.snapshots.len(), 4);
+    assert_eq!(result.snapshots[0].state.position, (1, 2));
+    assert_eq!(result.snapshots[1].state.last_text, "hello  world");
+    assert_eq!(result.snapshots.iter().map(|snapshot| snapshot.line).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+    assert_eq!(result.state, WorkerState {
+      position: (-2, 6),
+      last_text: String::from("hello  world"),
+      color: Some(Color::Hsv(1, 2, 3)),
+      quit: false
+    });
+  }
+
+  #[test]
+  fn malformed_line_stops_everything() {
+    let result = pipeline(lines(&["move 1 2", "quit-ish", "move 1", "move 5 5"]));
+    assert_eq!(result, Err(PipelineError {
+      line: 2,
+      text: String::from("quit-ish"),
+      error: MessageParseError::UnknownCommand { token: String::from("quit-ish"), at: 0 }
+    }));
+
+    let result = pipeline(lines(&["move 1 2", "write ok", "color cmyk 1 2 3", "quit"]));
+    let err = result.unwrap_err();
+    assert_eq!(err.line, 3);
+    assert_eq!(err.error.offset(), 6);
+    assert_eq!(
+      err.to_string(),
+      "line 3: can't parse \"color cmyk 1 2 3\": can't use \"cmyk\", expected `color rgb|hsv <a> <b> <c>`"
+    );
+  }
+
+  #[test]
+  fn quit_ends_the_applier() {
+    let result = pipeline(lines(&["move 1 1", "quit", "move 9 9"])).unwrap();
+    assert_eq!(result.snapshots.len(), 2);
+    assert!(result.state.quit);
+    assert_eq!(result.state.position, (1, 1));
+  }
+
+  #[test]
+  fn lines_after_quit_are_not_parsed() {
+    // "bad" used to fail the run whenever the parser got to it before
+    // the applier had hung up
+    let result = pipeline(lines(&["quit", "move 1 1", "move 1 1", "bad"])).unwrap();
+    assert_eq!(result.snapshots.len(), 1);
+    assert!(result.state.quit);
+    assert_eq!(result.state.position, (0, 0));
+  }
+
+  #[test]
+  fn empty_input() {
+    let result = pipeline(vec![]).unwrap();
+    assert!(result.snapshots.is_empty());
+    assert_eq!(result.state, WorkerState::default());
+  }
+
+  #[test]
+  fn lines_are_parsed_as_messages() {
+    let result = pipeline(lines(&["QUIT"])).unwrap();
+    assert!(result.state.quit);
+    for (bad, offset) in &[("move 1 2 3", 9), ("write", 5), ("", 0)] {
+      let err = pipeline(lines(&[bad])).unwrap_err();
+      assert_eq!((err.line, err.text.as_str()), (1, *bad));
+      assert_eq!(err.error, bad.parse::<Message>().unwrap_err());
+      assert_eq!(err.error.offset(), *offset);
+    }
+  }
+}
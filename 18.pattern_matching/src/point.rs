@@ -0,0 +1,419 @@
+/// A point on the plane, and where it falls: struct patterns can test a
+/// field against a literal (`x: 0`) while binding the others, and a guard
+/// covers what a pattern alone can't say, like "x is negative".
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point<T> {
+  pub x: T,
+  pub y: T
+}
+
+/// The chapter's integer points.
+pub type PointI32 = Point<i32>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Quadrant {
+  First,
+  Second,
+  Third,
+  Fourth,
+  OnXAxis,
+  OnYAxis,
+  Origin
+}
+
+impl PointI32 {
+  pub fn quadrant(&self) -> Quadrant {
+    match *self {
+      Point { x: 0, y: 0 } => Quadrant::Origin,
+      Point { x: 0, .. } => Quadrant::OnYAxis,
+      Point { y: 0, .. } => Quadrant::OnXAxis,
+      Point { x, y } if x > 0 && y > 0 => Quadrant::First,
+      Point { x, y } if x < 0 && y > 0 => Quadrant::Second,
+      Point { x, y } if x < 0 && y < 0 => Quadrant::Third,
+      // both are non-zero, and no other sign combination is left
+      Point { .. } => Quadrant::Fourth
+    }
+  }
+}
+
+impl<T: fmt::Display> fmt::Display for Point<T> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let Point { x, y } = self;
+    write!(f, "({}, {})", x, y)
+  }
+}
+
+/// Each variant carries the part of the input it gave up on.
+#[derive(Debug, PartialEq)]
+pub enum PointParseError {
+  MissingSeparator(String),
+  NotANumber(String),
+  Unclosed(String),
+  TrailingGarbage(String)
+}
+
+impl fmt::Display for PointParseError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      PointParseError::MissingSeparator(input) => write!(f, "missing `,` between the coordinates in {:?}", input),
+      PointParseError::NotANumber(part) => write!(f, "{:?} is not a number", part),
+      PointParseError::Unclosed(input) => write!(f, "missing `)` in {:?}", input),
+      PointParseError::TrailingGarbage(rest) => write!(f, "unexpected {:?} after the point", rest)
+    }
+  }
+}
+
+/// Reads `(3, 5)` or `3,5`, with any whitespace around the parts.
+impl FromStr for PointI32 {
+  type Err = PointParseError;
+
+  fn from_str(s: &str) -> Result<PointI32, PointParseError> {
+    let input = s.trim();
+    let (body, rest) = match input.strip_prefix('(') {
+      Some(inner) => match inner.split_once(')') {
+        Some(split) => split,
+        None => return Err(PointParseError::Unclosed(input.to_string()))
+      },
+      // without parentheses the point ends at the first space after y
+      None => match input.find(',') {
+        Some(comma) => {
+          let after = &input[comma + 1..];
+          let y_start = comma + 1 + after.len() - after.trim_start().len();
+          match input[y_start..].find(char::is_whitespace) {
+            Some(y_len) => input.split_at(y_start + y_len),
+            None => (input, "")
+          }
+        },
+        None => (input, "")
+      }
+    };
+    let rest = rest.trim();
+    if !rest.is_empty() {
+      return Err(PointParseError::TrailingGarbage(rest.to_string()));
+    }
+
+    let number = |part: &str| part.trim().parse().map_err(|_| PointParseError::NotANumber(part.trim().to_string()));
+    match body.split_once(',') {
+      Some((x, y)) => Ok(Point { x: number(x)?, y: number(y)? }),
+      None => Err(PointParseError::MissingSeparator(input.to_string()))
+    }
+  }
+}
+
+impl<T: Copy + Add<Output = T> + Mul<Output = T>> Point<T> {
+  pub fn dot(&self, other: &Point<T>) -> T {
+    let Point { x: x1, y: y1 } = *self;
+    let Point { x: x2, y: y2 } = *other;
+    x1 * x2 + y1 * y2
+  }
+}
+
+// Each operator pulls both sides apart with a let pattern and builds the
+// result field by field.
+impl<T: Add<Output = T>> Add for Point<T> {
+  type Output = Point<T>;
+
+  fn add(self, other: Point<T>) -> Point<T> {
+    let Point { x: x1, y: y1 } = self;
+    let Point { x: x2, y: y2 } = other;
+    Point { x: x1 + x2, y: y1 + y2 }
+  }
+}
+
+impl<T: Sub<Output = T>> Sub for Point<T> {
+  type Output = Point<T>;
+
+  fn sub(self, other: Point<T>) -> Point<T> {
+    let Point { x: x1, y: y1 } = self;
+    let Point { x: x2, y: y2 } = other;
+    Point { x: x1 - x2, y: y1 - y2 }
+  }
+}
+
+impl<T: Neg<Output = T>> Neg for Point<T> {
+  type Output = Point<T>;
+
+  fn neg(self) -> Point<T> {
+    let Point { x, y } = self;
+    Point { x: -x, y: -y }
+  }
+}
+
+/// Scaling by a plain number, `Point { x: 1, y: 2 } * 3`.
+impl<T: Copy + Mul<Output = T>> Mul<T> for Point<T> {
+  type Output = Point<T>;
+
+  fn mul(self, factor: T) -> Point<T> {
+    let Point { x, y } = self;
+    Point { x: x * factor, y: y * factor }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Region {
+  Origin,
+  OnXAxis,
+  OnYAxis,
+  InsideUnitSquare,
+  Elsewhere
+}
+
+/// Arms are tried top to bottom and the first match wins, so the order is
+/// part of the meaning: the origin is on both axes but only reaches the
+/// first arm, and `(1, 0)` is inside the unit square but is reported as
+/// on the x axis because that arm comes first.
+pub fn region(p: &PointI32) -> Region {
+  match *p {
+    Point { x: 0, y: 0 } => Region::Origin,
+    Point { y: 0, .. } => Region::OnXAxis,
+    Point { x: 0, .. } => Region::OnYAxis,
+    Point { x, y } if x.unsigned_abs() <= 1 && y.unsigned_abs() <= 1 => Region::InsideUnitSquare,
+    Point { .. } => Region::Elsewhere
+  }
+}
+
+/// Destructures in the parameter itself, so the fields move out by value.
+pub fn swap<T>(Point { x, y }: Point<T>) -> Point<T> {
+  Point { x: y, y: x }
+}
+
+pub fn map<T, U>(p: Point<T>, f: impl Fn(T) -> U) -> Point<U> {
+  let Point { x, y } = p;
+  Point { x: f(x), y: f(y) }
+}
+
+/// `x * x + y * y`, each point dotted with itself, summed over `points`.
+pub fn sum_of_squares<T>(points: &[Point<T>]) -> T
+where
+  T: Copy + Add<Output = T> + Mul<Output = T> + Sum
+{
+  points.iter().map(|p| p.dot(p)).sum()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point3 {
+  pub x: i32,
+  pub y: i32,
+  pub z: i32
+}
+
+/// An array's length is part of its type, so `[x, y, z]` can't fail here.
+pub fn from_array(coords: [i32; 3]) -> Point3 {
+  let [x, y, z] = coords;
+  Point3 { x, y, z }
+}
+
+/// What a slice of coordinates describes, by how many there are.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CoordShape {
+  Empty,
+  Scalar(i32),
+  Pair(PointI32),
+  Triple(Point3),
+  /// the first three as a point, plus how many coordinates follow them
+  Higher { first: Point3, extra: usize }
+}
+
+/// A slice's length is only known at run time, so every length needs an
+/// arm; `rest @ ..` binds whatever is left after the first three.
+pub fn classify(coords: &[i32]) -> CoordShape {
+  match *coords {
+    [] => CoordShape::Empty,
+    [x] => CoordShape::Scalar(x),
+    [x, y] => CoordShape::Pair(Point { x, y }),
+    [x, y, z] => CoordShape::Triple(Point3 { x, y, z }),
+    [x, y, z, ref rest @ ..] => CoordShape::Higher { first: Point3 { x, y, z }, extra: rest.len() }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn quadrant(x: i32, y: i32) -> Quadrant {
+    PointI32 { x, y }.quadrant()
+  }
+
+  #[test]
+  fn the_four_quadrants() {
+    assert_eq!(quadrant(3, 4), Quadrant::First);
+    assert_eq!(quadrant(-3, 4), Quadrant::Second);
+    assert_eq!(quadrant(-3, -4), Quadrant::Third);
+    assert_eq!(quadrant(3, -4), Quadrant::Fourth);
+  }
+
+  #[test]
+  fn axes_on_either_side_of_the_origin() {
+    assert_eq!(quadrant(5, 0), Quadrant::OnXAxis);
+    assert_eq!(quadrant(-5, 0), Quadrant::OnXAxis);
+    assert_eq!(quadrant(0, 7), Quadrant::OnYAxis);
+    assert_eq!(quadrant(0, -7), Quadrant::OnYAxis);
+  }
+
+  #[test]
+  fn origin() {
+    assert_eq!(quadrant(0, 0), Quadrant::Origin);
+  }
+
+  #[test]
+  fn extremes_keep_their_signs() {
+    assert_eq!(quadrant(i32::MIN, i32::MAX), Quadrant::Second);
+    assert_eq!(quadrant(i32::MAX, i32::MIN), Quadrant::Fourth);
+  }
+
+  #[test]
+  fn swap_and_map_over_numbers() {
+    assert_eq!(swap(Point { x: 1, y: -2 }), Point { x: -2, y: 1 });
+    assert_eq!(swap(Point { x: 0.5, y: 1.5 }), Point { x: 1.5, y: 0.5 });
+    assert_eq!(map(Point { x: 3, y: -4 }, |n| f64::from(n) / 2.0), Point { x: 1.5, y: -2.0 });
+  }
+
+  #[test]
+  fn swap_and_map_move_non_copy_fields() {
+    let p = Point { x: String::from("left"), y: String::from("right") };
+    let swapped = swap(p);
+    assert_eq!(swapped, Point { x: String::from("right"), y: String::from("left") });
+    assert_eq!(map(swapped, |s| s.len()), Point { x: 5, y: 4 });
+  }
+
+  #[test]
+  fn sum_of_squares_for_ints_and_floats() {
+    let ints = [Point { x: 0, y: 0 }, Point { x: 10, y: 10 }, Point { x: 8, y: 23 }];
+    assert_eq!(sum_of_squares(&ints), 793);
+    let floats = [Point { x: 0.5, y: 1.0 }, Point { x: -2.0, y: 0.0 }];
+    assert_eq!(sum_of_squares(&floats), 5.25);
+    assert_eq!(sum_of_squares::<i32>(&[]), 0);
+  }
+
+  #[test]
+  fn arrays_become_points() {
+    assert_eq!(from_array([1, -2, 3]), Point3 { x: 1, y: -2, z: 3 });
+  }
+
+  #[test]
+  fn classify_every_length() {
+    assert_eq!(classify(&[]), CoordShape::Empty);
+    assert_eq!(classify(&[7]), CoordShape::Scalar(7));
+    assert_eq!(classify(&[1, -2]), CoordShape::Pair(Point { x: 1, y: -2 }));
+    assert_eq!(classify(&[1, 2, 3]), CoordShape::Triple(Point3 { x: 1, y: 2, z: 3 }));
+  }
+
+  #[test]
+  fn rest_binding_counts_what_follows() {
+    let first = Point3 { x: 1, y: 2, z: 3 };
+    assert_eq!(classify(&[1, 2, 3, 4]), CoordShape::Higher { first, extra: 1 });
+    assert_eq!(classify(&[1, 2, 3, 4, 5, 6, 7]), CoordShape::Higher { first, extra: 4 });
+  }
+
+  #[test]
+  fn add_sub_and_neg() {
+    let a = Point { x: 3, y: -4 };
+    let b = Point { x: -1, y: 2 };
+    assert_eq!(a + b, Point { x: 2, y: -2 });
+    assert_eq!(a - b, Point { x: 4, y: -6 });
+    assert_eq!(-a, Point { x: -3, y: 4 });
+    assert_eq!(-(-b), b);
+  }
+
+  #[test]
+  fn operators_chain_left_to_right() {
+    let a = Point { x: 1, y: 1 };
+    let b = Point { x: 2, y: -3 };
+    let c = Point { x: -5, y: 4 };
+    assert_eq!(a + b - c, Point { x: 8, y: -6 });
+    assert_eq!((a - c) * 2, Point { x: 12, y: -6 });
+  }
+
+  #[test]
+  #[allow(clippy::erasing_op)] // scaling by zero is the case under test
+  fn scaling() {
+    assert_eq!(Point { x: -3, y: 7 } * 0, Point { x: 0, y: 0 });
+    assert_eq!(Point { x: -3, y: 7 } * -2, Point { x: 6, y: -14 });
+    assert_eq!(Point { x: 0.5, y: 1.5 } * 2.0, Point { x: 1.0, y: 3.0 });
+  }
+
+  #[test]
+  fn dot_products() {
+    assert_eq!(Point { x: 2, y: 3 }.dot(&Point { x: -4, y: 5 }), 7);
+    let p = Point { x: 8, y: 23 };
+    assert_eq!(p.dot(&p), 8 * 8 + 23 * 23);
+  }
+
+  #[test]
+  fn sum_of_squares_matches_the_original_closure() {
+    let points = [Point { x: 0, y: 0 }, Point { x: 10, y: 10 }, Point { x: 8, y: -23 }];
+    let by_pattern = points.iter().map(|&Point { x, y }| x * x + y * y).sum::<i32>();
+    assert_eq!(sum_of_squares(&points), by_pattern);
+  }
+
+  #[test]
+  fn display_then_parse_round_trips() {
+    for p in &[Point { x: 3, y: 5 }, Point { x: -12, y: 0 }, Point { x: i32::MIN, y: i32::MAX }] {
+      assert_eq!(p.to_string().parse::<PointI32>(), Ok(*p));
+    }
+    assert_eq!(Point { x: -1, y: 2 }.to_string(), "(-1, 2)");
+  }
+
+  #[test]
+  fn accepted_spellings() {
+    let expected = Ok(Point { x: 3, y: -5 });
+    assert_eq!("(3, -5)".parse(), expected);
+    assert_eq!("3,-5".parse(), expected);
+    assert_eq!("  ( 3 ,-5 )  ".parse(), expected);
+    assert_eq!(" 3 , -5 ".parse(), expected);
+  }
+
+  fn error_text(input: &str) -> String {
+    input.parse::<PointI32>().unwrap_err().to_string()
+  }
+
+  #[test]
+  fn each_error_echoes_its_input() {
+    assert_eq!("3 5".parse::<PointI32>(), Err(PointParseError::MissingSeparator(String::from("3 5"))));
+    assert_eq!(error_text(" (3 5) "), "missing `,` between the coordinates in \"(3 5)\"");
+
+    assert_eq!("(3, five)".parse::<PointI32>(), Err(PointParseError::NotANumber(String::from("five"))));
+    assert_eq!(error_text("1.5,2"), "\"1.5\" is not a number");
+
+    assert_eq!("(3, 5".parse::<PointI32>(), Err(PointParseError::Unclosed(String::from("(3, 5"))));
+    assert_eq!(error_text("(3, 5"), "missing `)` in \"(3, 5\"");
+
+    assert_eq!("(3, 5) and more".parse::<PointI32>(), Err(PointParseError::TrailingGarbage(String::from("and more"))));
+    assert_eq!(error_text("3, 5 6"), "unexpected \"6\" after the point");
+  }
+
+  #[test]
+  fn region_matrix() {
+    let region_at = |x, y| region(&Point { x, y });
+    let expected = [
+      // y = 1
+      [Region::Elsewhere, Region::InsideUnitSquare, Region::OnYAxis, Region::InsideUnitSquare, Region::Elsewhere],
+      // y = 0
+      [Region::OnXAxis, Region::OnXAxis, Region::Origin, Region::OnXAxis, Region::OnXAxis],
+      // y = -1
+      [Region::Elsewhere, Region::InsideUnitSquare, Region::OnYAxis, Region::InsideUnitSquare, Region::Elsewhere]
+    ];
+    for (row, y) in expected.iter().zip(&[1, 0, -1]) {
+      for (&want, x) in row.iter().zip(-2..=2) {
+        assert_eq!(region_at(x, *y), want, "at ({}, {})", x, y);
+      }
+    }
+    assert_eq!(region_at(0, -9), Region::OnYAxis);
+    assert_eq!(region_at(3, 9), Region::Elsewhere);
+    assert_eq!(region_at(i32::MIN, 1), Region::Elsewhere);
+  }
+
+  #[test]
+  fn earlier_arms_win() {
+    // on both axes, and within the unit square too
+    assert_eq!(region(&Point { x: 0, y: 0 }), Region::Origin);
+    // on an axis and within the unit square
+    assert_eq!(region(&Point { x: 1, y: 0 }), Region::OnXAxis);
+    assert_eq!(region(&Point { x: 0, y: -1 }), Region::OnYAxis);
+  }
+}
@@ -0,0 +1,205 @@
+/// A fixed-capacity FIFO shared between threads. Producers wait on
+/// `not_full` while it is full, consumers wait on `not_empty` while it is
+/// empty, and each side wakes the other after changing the queue.
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use crate::Message;
+
+struct State<T> {
+  items: VecDeque<T>,
+  closed: bool
+}
+
+pub struct BoundedQueue<T> {
+  state: Mutex<State<T>>,
+  capacity: usize,
+  not_empty: Condvar,
+  not_full: Condvar
+}
+
+impl<T> BoundedQueue<T> {
+  /// Panics if `capacity` is zero, no item could ever get through.
+  pub fn new(capacity: usize) -> BoundedQueue<T> {
+    assert!(capacity > 0, "a bounded queue needs room for at least one item");
+    BoundedQueue {
+      state: Mutex::new(State { items: VecDeque::with_capacity(capacity), closed: false }),
+      capacity,
+      not_empty: Condvar::new(),
+      not_full: Condvar::new()
+    }
+  }
+
+  /// Waits for space. Hands the item back if the queue is closed.
+  pub fn push(&self, item: T) -> Result<(), T> {
+    let mut state = self.state.lock().unwrap();
+    // `wait` releases the lock while asleep; wakeups can be spurious, so recheck
+    while state.items.len() == self.capacity && !state.closed {
+      state = self.not_full.wait(state).unwrap();
+    }
+    if state.closed {
+      return Err(item);
+    }
+    state.items.push_back(item);
+    self.not_empty.notify_one();
+    Ok(())
+  }
+
+  /// Hands the item back at once if the queue is full or closed.
+  pub fn try_push(&self, item: T) -> Result<(), T> {
+    let mut state = self.state.lock().unwrap();
+    if state.closed || state.items.len() == self.capacity {
+      return Err(item);
+    }
+    state.items.push_back(item);
+    self.not_empty.notify_one();
+    Ok(())
+  }
+
+  /// Waits for an item. None once the queue is closed and drained.
+  pub fn pop(&self) -> Option<T> {
+    let mut state = self.state.lock().unwrap();
+    while state.items.is_empty() && !state.closed {
+      state = self.not_empty.wait(state).unwrap();
+    }
+    let item = state.items.pop_front();
+    if item.is_some() {
+      self.not_full.notify_one();
+    }
+    item
+  }
+
+  pub fn try_pop(&self) -> Option<T> {
+    let item = self.state.lock().unwrap().items.pop_front();
+    if item.is_some() {
+      self.not_full.notify_one();
+    }
+    item
+  }
+
+  /// Refuses new items; items already queued can still be popped.
+  /// Everyone waiting is woken up to notice.
+  pub fn close(&self) {
+    self.state.lock().unwrap().closed = true;
+    self.not_empty.notify_all();
+    self.not_full.notify_all();
+  }
+
+  pub fn len(&self) -> usize {
+    self.state.lock().unwrap().items.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+}
+
+/// A producer pushes `count` Write messages through a queue of
+/// `capacity`, a consumer counts them until the queue is closed.
+pub fn transfer_writes(count: usize, capacity: usize) -> usize {
+  let queue = Arc::new(BoundedQueue::new(capacity));
+  let producer = {
+    let queue = Arc::clone(&queue);
+    thread::spawn(move || {
+      for n in 0..count {
+        queue.push(Message::Write(format!("message {}", n))).ok();
+      }
+      queue.close();
+    })
+  };
+  let consumer = {
+    let queue = Arc::clone(&queue);
+    thread::spawn(move || {
+      let mut received = 0;
+      while let Some(msg) = queue.pop() {
+        if let Message::Write(_) = msg {
+          received += 1;
+        }
+      }
+      received
+    })
+  };
+  producer.join().unwrap();
+  consumer.join().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::sync::mpsc;
+  use std::time::Duration;
+
+  const TIMEOUT: Duration = Duration::from_secs(5);
+
+  #[test]
+  fn first_in_first_out() {
+    let queue = BoundedQueue::new(3);
+    for n in 1..=3 {
+      queue.push(n).unwrap();
+    }
+    assert_eq!(queue.len(), 3);
+    assert_eq!((queue.pop(), queue.try_pop(), queue.pop()), (Some(1), Some(2), Some(3)));
+    assert_eq!(queue.try_pop(), None);
+    assert!(queue.is_empty());
+  }
+
+  #[test]
+  fn push_waits_for_space() {
+    let queue = Arc::new(BoundedQueue::new(1));
+    queue.push(1).unwrap();
+    assert_eq!(queue.try_push(2), Err(2));
+
+    let (done_tx, done_rx) = mpsc::channel();
+    let pusher = {
+      let queue = Arc::clone(&queue);
+      thread::spawn(move || {
+        queue.push(2).unwrap();
+        done_tx.send(()).unwrap();
+      })
+    };
+    // still blocked: nothing was popped yet
+    assert!(done_rx.recv_timeout(Duration::from_millis(50)).is_err());
+    assert_eq!(queue.pop(), Some(1));
+    done_rx.recv_timeout(TIMEOUT).expect("push never woke up");
+    pusher.join().unwrap();
+    assert_eq!(queue.pop(), Some(2));
+  }
+
+  #[test]
+  fn close_wakes_blocked_poppers() {
+    let queue: Arc<BoundedQueue<i32>> = Arc::new(BoundedQueue::new(2));
+    let (tx, rx) = mpsc::channel();
+    let poppers: Vec<_> = (0..3)
+      .map(|_| {
+        let (queue, tx) = (Arc::clone(&queue), tx.clone());
+        thread::spawn(move || tx.send(queue.pop()).unwrap())
+      })
+      .collect();
+
+    thread::sleep(Duration::from_millis(20));
+    queue.close();
+    for _ in 0..3 {
+      assert_eq!(rx.recv_timeout(TIMEOUT).expect("a popper stayed asleep"), None);
+    }
+    for popper in poppers {
+      popper.join().unwrap();
+    }
+  }
+
+  #[test]
+  fn closed_queue_drains_then_refuses() {
+    let queue = BoundedQueue::new(2);
+    queue.push("kept").unwrap();
+    queue.close();
+    assert_eq!(queue.push("late"), Err("late"));
+    assert_eq!(queue.try_push("late"), Err("late"));
+    assert_eq!(queue.pop(), Some("kept"));
+    assert_eq!(queue.pop(), None);
+  }
+
+  #[test]
+  fn producer_consumer_moves_every_message() {
+    assert_eq!(transfer_writes(1000, 8), 1000);
+    assert_eq!(transfer_writes(0, 8), 0);
+  }
+}
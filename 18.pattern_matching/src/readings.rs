@@ -0,0 +1,86 @@
+// Statistics over a series of readings, each one a slice pattern with
+// `name @ ..` binding the part of the slice the `..` skipped.
+
+/// The mean with the lowest and highest readings left out, None below
+/// three readings. Readings are assumed not to be NaN: the sort uses
+/// `total_cmp`, which puts a NaN after every number, so a single NaN is
+/// dropped as the highest reading but any more end up in the mean.
+pub fn trimmed_mean(readings: &[f64]) -> Option<f64> {
+  let mut sorted = readings.to_vec();
+  sorted.sort_by(f64::total_cmp);
+  match sorted.as_slice() {
+    [_, middle @ .., _] if !middle.is_empty() => Some(middle.iter().sum::<f64>() / middle.len() as f64),
+    _ => None
+  }
+}
+
+/// How far the last reading is from the first, None below two readings
+/// or when the difference doesn't fit in an i32.
+pub fn delta_endpoints(readings: &[i32]) -> Option<i32> {
+  match readings {
+    [first, .., last] => last.checked_sub(*first),
+    _ => None
+  }
+}
+
+/// How many elements at the start equal the first one, and what follows
+/// them; `(0, [])` for an empty slice.
+pub fn leading_run(xs: &[i32]) -> (usize, &[i32]) {
+  match xs {
+    [] => (0, xs),
+    [head, rest @ ..] => {
+      let repeats = rest.iter().take_while(|&next| next == head).count();
+      (repeats + 1, &rest[repeats..])
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn trimmed_mean_by_length() {
+    assert_eq!(trimmed_mean(&[]), None);
+    assert_eq!(trimmed_mean(&[1.0]), None);
+    assert_eq!(trimmed_mean(&[1.0, 2.0]), None);
+    assert_eq!(trimmed_mean(&[9.0, 1.0, 4.0]), Some(4.0));
+    assert_eq!(trimmed_mean(&[100.0, 2.0, 3.0, -50.0, 4.0]), Some(3.0));
+    assert_eq!(trimmed_mean(&[2.5; 6]), Some(2.5));
+  }
+
+  #[test]
+  fn trimmed_mean_and_nan() {
+    // one NaN sorts last and is trimmed like any other high reading
+    assert_eq!(trimmed_mean(&[f64::NAN, 1.0, 2.0, 3.0]), Some(2.5));
+    // a second one is kept, and poisons the mean
+    assert!(trimmed_mean(&[f64::NAN, 1.0, f64::NAN, 3.0]).unwrap().is_nan());
+  }
+
+  #[test]
+  fn delta_by_length() {
+    assert_eq!(delta_endpoints(&[]), None);
+    assert_eq!(delta_endpoints(&[7]), None);
+    assert_eq!(delta_endpoints(&[7, 3]), Some(-4));
+    assert_eq!(delta_endpoints(&[1, 100, 5]), Some(4));
+    assert_eq!(delta_endpoints(&[10, 1, 2, 3, 30]), Some(20));
+    assert_eq!(delta_endpoints(&[i32::MIN, i32::MAX]), None);
+  }
+
+  #[test]
+  fn leading_run_by_length() {
+    assert_eq!(leading_run(&[]), (0, &[] as &[i32]));
+    assert_eq!(leading_run(&[5]), (1, &[] as &[i32]));
+    assert_eq!(leading_run(&[5, 6]), (1, &[6][..]));
+    assert_eq!(leading_run(&[5, 5, 6]), (2, &[6][..]));
+    assert_eq!(leading_run(&[3, 3, 3, 1, 3, 3]), (3, &[1, 3, 3][..]));
+  }
+
+  #[test]
+  fn leading_run_all_equal() {
+    assert_eq!(leading_run(&[4; 3]), (3, &[] as &[i32]));
+    // one frame however long the run is
+    let long = vec![9; 1_000_000];
+    assert_eq!(leading_run(&long), (1_000_000, &[] as &[i32]));
+  }
+}
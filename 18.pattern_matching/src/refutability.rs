@@ -0,0 +1,72 @@
+//! Refutability, checked by the compiler. A `let` needs a pattern that
+//! matches every value, so a refutable one doesn't compile:
+//!
+//! ```compile_fail,E0005
+//! let maybe: Option<i32> = Some(3);
+//! let Some(x) = maybe;
+//! ```
+//!
+//! Giving it somewhere to go when the pattern fails, with `let ... else`
+//! or `if let`, does:
+//!
+//! ```
+//! let maybe: Option<i32> = Some(3);
+//! let Some(x) = maybe else {
+//!   panic!("no value");
+//! };
+//! assert_eq!(x, 3);
+//!
+//! if let Some(y) = maybe {
+//!   assert_eq!(y, 3);
+//! }
+//! ```
+//!
+//! The other way round, `if let` with a pattern that always matches only
+//! gets a warning, `irrefutable_let_patterns`, here made an error:
+//!
+//! ```compile_fail
+//! #![deny(irrefutable_let_patterns)]
+//! if let x = 5 {
+//!   println!("{}", x);
+//! }
+//! ```
+//!
+//! and a plain `let` is what it should have been:
+//!
+//! ```
+//! let x = 5;
+//! assert_eq!(x, 5);
+//! ```
+
+/// A refutable pattern, with the else block for when it doesn't match.
+pub fn demo_refutable(input: Option<i32>) -> String {
+  let Some(x) = input else {
+    return String::from("None, so `Some(x)` didn't match");
+  };
+  format!("Some({}) matched, x = {}", x, x)
+}
+
+/// An irrefutable pattern: every pair destructures into two numbers.
+pub fn demo_irrefutable(pair: (i32, i32)) -> i32 {
+  let (x, y) = pair;
+  x * 10 + y
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn refutable_either_way() {
+    assert_eq!(demo_refutable(Some(7)), "Some(7) matched, x = 7");
+    assert_eq!(demo_refutable(Some(-1)), "Some(-1) matched, x = -1");
+    assert_eq!(demo_refutable(None), "None, so `Some(x)` didn't match");
+  }
+
+  #[test]
+  fn irrefutable_keeps_the_order() {
+    assert_eq!(demo_irrefutable((4, 2)), 42);
+    assert_eq!(demo_irrefutable((2, 4)), 24);
+    assert_eq!(demo_irrefutable((0, 0)), 0);
+  }
+}
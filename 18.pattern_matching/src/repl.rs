@@ -0,0 +1,145 @@
+/// A line-at-a-time session over a canvas: every line is parsed as a
+/// Message and applied, until `quit` or the end of the input. A line that
+/// doesn't parse is reported, with the column where the problem starts,
+/// and the session goes on. `cargo run -- --repl` runs it on stdin.
+use std::io::{self, BufRead, Write};
+use crate::canvas::Canvas;
+use crate::message::MessageParseError;
+use crate::Message;
+
+const HELP: &str = "\
+commands:
+  quit
+  move <x> <y>
+  write <text>            quote the text to keep outer spaces: write \"  hi \"
+  color rgb|hsv <a> <b> <c>
+  resize <width> <height>
+  rotate <degrees>
+  help";
+
+fn summary(canvas: &Canvas) -> String {
+  let color = match &canvas.color {
+    Some(color) => color.to_string(),
+    None => String::from("none")
+  };
+  let (width, height) = canvas.size;
+  format!(
+    "at {:?}, color {}, size {}x{}, rotated {}, {} written",
+    canvas.position, color, width, height, canvas.rotation, canvas.log.len()
+  )
+}
+
+/// The 1-based column, in chars, where `err` starts in `line`.
+fn column(line: &str, err: &MessageParseError) -> usize {
+  let column = line[..err.offset()].chars().count() + 1;
+  match err {
+    // where the next word would go, after a space
+    MessageParseError::MissingArgument { .. } => column + 1,
+    _ => column
+  }
+}
+
+pub fn repl(input: impl BufRead, mut output: impl Write) -> io::Result<Canvas> {
+  let mut canvas = Canvas::new();
+  for line in input.lines() {
+    let line = line?;
+    match line.trim() {
+      "" => continue,
+      "help" => {
+        writeln!(output, "{}", HELP)?;
+        continue;
+      },
+      _ => {}
+    }
+    match line.parse::<Message>() {
+      Ok(Message::Quit) => {
+        canvas.apply(Message::Quit);
+        writeln!(output, "bye")?;
+        break;
+      },
+      Ok(msg) => {
+        canvas.apply(msg);
+        writeln!(output, "{}", summary(&canvas))?;
+      },
+      Err(err) => writeln!(output, "error at column {}: {}", column(&line, &err), err)?
+    }
+  }
+  Ok(canvas)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn session(script: &str) -> (Canvas, String) {
+    let mut output = vec![];
+    let canvas = repl(script.as_bytes(), &mut output).unwrap();
+    (canvas, String::from_utf8(output).unwrap())
+  }
+
+  #[test]
+  fn commands_update_the_canvas() {
+    let (canvas, output) = session("move 1 2\ncolor rgb 1 2 3\nwrite hi\nquit\nmove 9 9\n");
+    assert_eq!(output.lines().collect::<Vec<_>>(), [
+      "at (1, 2), color none, size 0x0, rotated 0, 0 written",
+      "at (1, 2), color rgb(1, 2, 3), size 0x0, rotated 0, 0 written",
+      "at (1, 2), color rgb(1, 2, 3), size 0x0, rotated 0, 1 written",
+      "bye"
+    ]);
+    assert!(canvas.closed);
+    assert_eq!(canvas.position, (1, 2));
+  }
+
+  #[test]
+  fn parse_errors_point_at_the_column_and_go_on() {
+    let (canvas, output) = session("move 1 x\n  jump\nmove 1 1 1\nrotate\nmove 3 4\n");
+    assert_eq!(output.lines().collect::<Vec<_>>(), [
+      "error at column 8: can't use \"x\", expected `move <x> <y>`",
+      "error at column 3: unknown command \"jump\", expected quit, move, write, color, resize or rotate",
+      "error at column 10: unexpected \"1\", expected `move <x> <y>`",
+      "error at column 8: missing argument, expected `rotate <degrees>`",
+      "at (3, 4), color none, size 0x0, rotated 0, 0 written"
+    ]);
+    assert_eq!(canvas.position, (3, 4));
+  }
+
+  #[test]
+  fn help_lists_the_syntax() {
+    let (canvas, output) = session("help\n");
+    assert_eq!(output.trim_end(), HELP);
+    assert!(output.contains("move <x> <y>"));
+    assert_eq!(canvas, Canvas::new());
+  }
+
+  #[test]
+  fn eof_without_quit_keeps_the_canvas_open() {
+    let (canvas, output) = session("\nrotate -90\n\n");
+    assert_eq!(output, "at (0, 0), color none, size 0x0, rotated 270, 0 written\n");
+    assert!(!canvas.closed);
+    assert_eq!(session("").0, Canvas::new());
+  }
+
+  #[test]
+  fn columns_for_quoted_writes() {
+    let line = "write \"done\" extra";
+    let err = line.parse::<Message>().unwrap_err();
+    assert_eq!(column(line, &err), 14);
+    let line = "write \"open";
+    assert_eq!(column(line, &line.parse::<Message>().unwrap_err()), 7);
+  }
+
+  #[test]
+  fn columns_for_repeated_tokens() {
+    let (_, output) = session("color rgb 1 rgb 3
+move 1 1 1
+write \"1\" 1
+");
+    assert_eq!(output.lines().map(|line| &line[..18]).collect::<Vec<_>>(), [
+      "error at column 13",
+      "error at column 10",
+      "error at column 11"
+    ]);
+    let line = "é é move";
+    assert_eq!(column(line, &line.parse::<Message>().unwrap_err()), 1);
+  }
+}
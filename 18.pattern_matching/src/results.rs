@@ -0,0 +1,111 @@
+/// Two Results matched as one tuple: both Ok, exactly one Err, or both.
+/// The middle arm is an or-pattern whose alternatives bind the same name,
+/// which works because `e` has the same type on both sides.
+use std::fmt;
+use std::num::IntErrorKind;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+  Empty,
+  NotANumber(String),
+  OutOfRange(String)
+}
+
+impl fmt::Display for ParseError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ParseError::Empty => write!(f, "nothing to parse"),
+      ParseError::NotANumber(text) => write!(f, "{:?} isn't a number", text),
+      ParseError::OutOfRange(text) => write!(f, "{:?} doesn't fit in an i32", text)
+    }
+  }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CombineError {
+  One(ParseError),
+  Both(ParseError, ParseError),
+  /// both parsed, but the sum doesn't fit
+  Overflow
+}
+
+pub fn parse(text: &str) -> Result<i32, ParseError> {
+  text.trim().parse().map_err(|err: std::num::ParseIntError| match err.kind() {
+    IntErrorKind::Empty => ParseError::Empty,
+    IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => ParseError::OutOfRange(text.to_string()),
+    _ => ParseError::NotANumber(text.to_string())
+  })
+}
+
+/// The sum of both, or every error there was.
+pub fn combine(a: Result<i32, ParseError>, b: Result<i32, ParseError>) -> Result<i32, CombineError> {
+  match (a, b) {
+    (Ok(x), Ok(y)) => x.checked_add(y).ok_or(CombineError::Overflow),
+    (Err(e), Ok(_)) | (Ok(_), Err(e)) => Err(CombineError::One(e)),
+    (Err(e1), Err(e2)) => Err(CombineError::Both(e1, e2))
+  }
+}
+
+/// Parses every input, rather than stopping at the first failure, and
+/// sums them; the errors come back with their 0-based index. A number
+/// that takes the running sum out of range counts as OutOfRange.
+pub fn sum_parsed(inputs: &[&str]) -> Result<i32, Vec<(usize, ParseError)>> {
+  let mut sum: i32 = 0;
+  let mut errors = vec![];
+  for (index, text) in inputs.iter().enumerate() {
+    match parse(text) {
+      Ok(n) => match sum.checked_add(n) {
+        Some(total) => sum = total,
+        None => errors.push((index, ParseError::OutOfRange(text.to_string())))
+      },
+      Err(err) => errors.push((index, err))
+    }
+  }
+  if errors.is_empty() {
+    Ok(sum)
+  } else {
+    Err(errors)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn bad(text: &str) -> ParseError {
+    ParseError::NotANumber(text.to_string())
+  }
+
+  #[test]
+  fn both_ok() {
+    assert_eq!(combine(parse("2"), parse(" -5 ")), Ok(-3));
+    assert_eq!(combine(Ok(i32::MAX), Ok(1)), Err(CombineError::Overflow));
+  }
+
+  #[test]
+  fn one_side_fails() {
+    assert_eq!(combine(parse("x"), parse("1")), Err(CombineError::One(bad("x"))));
+    assert_eq!(combine(parse("1"), parse("")), Err(CombineError::One(ParseError::Empty)));
+    assert_eq!(combine(Ok(1), parse("99999999999")), Err(CombineError::One(ParseError::OutOfRange(String::from("99999999999")))));
+  }
+
+  #[test]
+  fn both_fail() {
+    assert_eq!(combine(parse("a"), parse("b")), Err(CombineError::Both(bad("a"), bad("b"))));
+    assert_eq!(combine(parse(""), parse("1.5")), Err(CombineError::Both(ParseError::Empty, bad("1.5"))));
+  }
+
+  #[test]
+  fn every_failure_is_reported_with_its_index() {
+    assert_eq!(sum_parsed(&["1", "2", "3"]), Ok(6));
+    assert_eq!(sum_parsed(&["1", "two", "3", "", "x5"]), Err(vec![(1, bad("two")), (3, ParseError::Empty), (4, bad("x5"))]));
+    let max = i32::MAX.to_string();
+    assert_eq!(sum_parsed(&[&max, "1", "-1"]), Err(vec![(1, ParseError::OutOfRange(String::from("1")))]));
+    assert_eq!(bad("two").to_string(), "\"two\" isn't a number");
+  }
+
+  #[test]
+  fn empty_input_sums_to_zero() {
+    assert_eq!(sum_parsed(&[]), Ok(0));
+  }
+}
@@ -0,0 +1,260 @@
+/// The "can't overwrite an existing customized value" rule as a shared
+/// setting: a value that may be set once, read from any thread. RwLock<T>
+/// lets any number of readers in at the same time, or one writer alone.
+use std::fmt;
+use std::sync::{Arc, RwLock};
+use std::thread;
+
+#[derive(Debug, PartialEq)]
+pub struct AlreadySet;
+
+impl fmt::Display for AlreadySet {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "Can't overwrite an existing customized value")
+  }
+}
+
+/// Cloning shares the same setting, like cloning the Arc inside.
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+  value: Arc<RwLock<Option<u32>>>
+}
+
+impl Settings {
+  pub fn new() -> Settings {
+    Settings::default()
+  }
+
+  /// Sets the value unless one is already there.
+  pub fn try_set(&self, new: u32) -> Result<(), AlreadySet> {
+    let mut value = self.value.write().unwrap();
+    match *value {
+      // the current value doesn't matter, only that there is one
+      Some(_) => Err(AlreadySet),
+      None => {
+        *value = Some(new);
+        Ok(())
+      }
+    }
+  }
+
+  pub fn force_set(&self, new: u32) {
+    *self.value.write().unwrap() = Some(new);
+  }
+
+  pub fn get(&self) -> Option<u32> {
+    *self.value.read().unwrap()
+  }
+
+  pub fn clear(&self) {
+    *self.value.write().unwrap() = None;
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UpdateResult {
+  KeptExisting,
+  Updated,
+  Cleared,
+  NoChange
+}
+
+/// The same rule for a single owner and any T: one value, set once,
+/// optionally clearable back to nothing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Setting<T> {
+  value: Option<T>,
+  clearable: bool
+}
+
+impl<T> Setting<T> {
+  pub fn new() -> Setting<T> {
+    Setting { value: None, clearable: false }
+  }
+
+  /// A setting that `try_update(None)` empties again.
+  pub fn clearable() -> Setting<T> {
+    Setting { value: None, clearable: true }
+  }
+
+  /// The old `(setting_value, new_setting_value)` match: with both
+  /// present, the existing value stays and `new` is dropped.
+  pub fn try_update(&mut self, new: Option<T>) -> UpdateResult {
+    match (&self.value, new) {
+      (Some(_), Some(_)) => UpdateResult::KeptExisting,
+      (None, Some(new)) => {
+        self.value = Some(new);
+        UpdateResult::Updated
+      },
+      (Some(_), None) if self.clearable => {
+        self.value = None;
+        UpdateResult::Cleared
+      },
+      (_, None) => UpdateResult::NoChange
+    }
+  }
+
+  /// Replaces the value whatever it was, handing back the old one.
+  pub fn force_update(&mut self, new: Option<T>) -> Option<T> {
+    std::mem::replace(&mut self.value, new)
+  }
+
+  pub fn get(&self) -> Option<&T> {
+    self.value.as_ref()
+  }
+}
+
+impl<T> Default for Setting<T> {
+  fn default() -> Setting<T> {
+    Setting::new()
+  }
+}
+
+#[derive(Debug)]
+pub struct RaceOutcome {
+  /// what the writer's two `try_set` calls returned
+  pub writes: Vec<Result<(), AlreadySet>>,
+  pub final_value: Option<u32>,
+  pub reads: usize,
+  /// reads that saw something other than None or the first write
+  pub inconsistent_reads: usize
+}
+
+/// `readers` threads keep reading while one writer tries to set
+/// `first` and then `second`. Only `first` may ever be observed.
+pub fn race_readers_and_writer(readers: usize, first: u32, second: u32) -> RaceOutcome {
+  const READS_PER_THREAD: usize = 1000;
+  let settings = Settings::new();
+
+  let reader_handles: Vec<_> = (0..readers)
+    .map(|_| {
+      let settings = settings.clone();
+      thread::spawn(move || {
+        (0..READS_PER_THREAD)
+          .filter(|_| match settings.get() {
+            None => false,
+            Some(value) => value != first
+          })
+          .count()
+      })
+    })
+    .collect();
+
+  let writer = {
+    let settings = settings.clone();
+    thread::spawn(move || vec![settings.try_set(first), settings.try_set(second)])
+  };
+
+  let writes = writer.join().unwrap();
+  let inconsistent_reads = reader_handles.into_iter().map(|handle| handle.join().unwrap()).sum();
+  RaceOutcome {
+    writes,
+    final_value: settings.get(),
+    reads: readers * READS_PER_THREAD,
+    inconsistent_reads
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn matches_the_old_two_option_logic() {
+    // (Some(_), Some(_)) refused the new value, anything else took it
+    let settings = Settings::new();
+    settings.force_set(5);
+    assert_eq!(settings.try_set(10), Err(AlreadySet));
+    assert_eq!(settings.get(), Some(5));
+
+    let unset = Settings::new();
+    assert_eq!(unset.try_set(10), Ok(()));
+    assert_eq!(unset.get(), Some(10));
+  }
+
+  #[test]
+  fn already_set_error() {
+    let settings = Settings::new();
+    settings.try_set(1).unwrap();
+    let err = settings.try_set(2).unwrap_err();
+    assert_eq!(err.to_string(), "Can't overwrite an existing customized value");
+  }
+
+  #[test]
+  fn clear_then_set() {
+    let settings = Settings::new();
+    settings.try_set(1).unwrap();
+    settings.clear();
+    assert_eq!(settings.get(), None);
+    assert_eq!(settings.try_set(2), Ok(()));
+    assert_eq!(settings.get(), Some(2));
+  }
+
+  #[test]
+  fn clones_share_the_value() {
+    let settings = Settings::new();
+    let shared = settings.clone();
+    settings.force_set(7);
+    assert_eq!(shared.get(), Some(7));
+  }
+
+  #[test]
+  fn concurrent_readers_see_only_the_first_write() {
+    let outcome = race_readers_and_writer(8, 5, 10);
+    assert_eq!(outcome.writes, vec![Ok(()), Err(AlreadySet)]);
+    assert_eq!(outcome.final_value, Some(5));
+    assert_eq!(outcome.reads, 8000);
+    assert_eq!(outcome.inconsistent_reads, 0);
+  }
+
+  #[test]
+  fn existing_value_is_kept() {
+    let mut setting = Setting::new();
+    assert_eq!(setting.try_update(Some(5)), UpdateResult::Updated);
+    assert_eq!(setting.try_update(Some(10)), UpdateResult::KeptExisting);
+    assert_eq!(setting.get(), Some(&5));
+  }
+
+  #[test]
+  fn nothing_in_nothing_out() {
+    let mut setting: Setting<u32> = Setting::new();
+    assert_eq!(setting.try_update(None), UpdateResult::NoChange);
+    assert_eq!(setting.get(), None);
+    assert_eq!(Setting::<u32>::default(), setting);
+  }
+
+  #[test]
+  fn clearing_is_opt_in() {
+    let mut fixed = Setting::new();
+    fixed.try_update(Some(1));
+    assert_eq!(fixed.try_update(None), UpdateResult::NoChange);
+    assert_eq!(fixed.get(), Some(&1));
+
+    let mut clearable = Setting::clearable();
+    clearable.try_update(Some(1));
+    assert_eq!(clearable.try_update(None), UpdateResult::Cleared);
+    assert_eq!(clearable.get(), None);
+    assert_eq!(clearable.try_update(None), UpdateResult::NoChange);
+    assert_eq!(clearable.try_update(Some(2)), UpdateResult::Updated);
+  }
+
+  #[test]
+  fn force_update_returns_the_old_value() {
+    let mut setting = Setting::new();
+    assert_eq!(setting.force_update(Some(3)), None);
+    assert_eq!(setting.force_update(Some(4)), Some(3));
+    assert_eq!(setting.force_update(None), Some(4));
+  }
+
+  #[test]
+  fn strings_move_in_and_out_without_clones() {
+    let mut setting = Setting::new();
+    let name = String::from("dark");
+    let address = name.as_ptr();
+    setting.try_update(Some(name));
+    // the same heap buffer, moved rather than copied
+    assert_eq!(setting.get().map(|name| name.as_ptr()), Some(address));
+    assert_eq!(setting.try_update(Some(String::from("light"))), UpdateResult::KeptExisting);
+    assert_eq!(setting.force_update(None).map(|name| name.as_ptr()), Some(address));
+  }
+}
@@ -0,0 +1,59 @@
+// `_` skips one element and `..` any number of them, so slice patterns
+// can pick out the ends or the start of a slice of any length.
+
+/// The first and last elements; a single element is both.
+pub fn ends(xs: &[i32]) -> Option<(i32, i32)> {
+  match *xs {
+    [] => None,
+    [only] => Some((only, only)),
+    [first, .., last] => Some((first, last))
+  }
+}
+
+pub fn first_two(xs: &[i32]) -> Option<(i32, i32)> {
+  match *xs {
+    [a, b, ..] => Some((a, b)),
+    _ => None
+  }
+}
+
+/// Everything but the first and last elements, empty when there are
+/// fewer than three.
+pub fn middle(xs: &[i32]) -> &[i32] {
+  match xs {
+    [_, mid @ .., _] => mid,
+    _ => &[]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn ends_by_length() {
+    assert_eq!(ends(&[]), None);
+    assert_eq!(ends(&[4]), Some((4, 4)));
+    assert_eq!(ends(&[4, 5]), Some((4, 5)));
+    assert_eq!(ends(&[4, 5, 6]), Some((4, 6)));
+    assert_eq!(ends(&[1, 2, 3, 4, 5, 6]), Some((1, 6)));
+  }
+
+  #[test]
+  fn first_two_by_length() {
+    assert_eq!(first_two(&[]), None);
+    assert_eq!(first_two(&[4]), None);
+    assert_eq!(first_two(&[4, 5]), Some((4, 5)));
+    assert_eq!(first_two(&[4, 5, 6]), Some((4, 5)));
+    assert_eq!(first_two(&[1, 2, 3, 4, 5, 6]), Some((1, 2)));
+  }
+
+  #[test]
+  fn middle_by_length() {
+    assert_eq!(middle(&[]), &[] as &[i32]);
+    assert_eq!(middle(&[4]), &[] as &[i32]);
+    assert_eq!(middle(&[4, 5]), &[] as &[i32]);
+    assert_eq!(middle(&[4, 5, 6]), &[5]);
+    assert_eq!(middle(&[1, 2, 3, 4, 5, 6]), &[2, 3, 4, 5]);
+  }
+}
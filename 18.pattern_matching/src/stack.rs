@@ -0,0 +1,133 @@
+/// The `while let Some(top) = stack.pop()` loop as a type: a last in,
+/// first out stack whose draining and iteration are that same loop.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stack<T> {
+  items: Vec<T>
+}
+
+impl<T> Stack<T> {
+  pub fn new() -> Stack<T> {
+    Stack { items: vec![] }
+  }
+
+  pub fn push(&mut self, item: T) {
+    self.items.push(item);
+  }
+
+  pub fn pop(&mut self) -> Option<T> {
+    self.items.pop()
+  }
+
+  pub fn peek(&self) -> Option<&T> {
+    self.items.last()
+  }
+
+  pub fn len(&self) -> usize {
+    self.items.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.items.is_empty()
+  }
+
+  /// Pops items for as long as `pred` holds for the top one, and returns
+  /// them in the order they were popped. The first item that fails stays.
+  pub fn drain_top_while(&mut self, mut pred: impl FnMut(&T) -> bool) -> Vec<T> {
+    let mut drained = vec![];
+    while let Some(top) = self.items.pop() {
+      if !pred(&top) {
+        self.items.push(top);
+        break;
+      }
+      drained.push(top);
+    }
+    drained
+  }
+}
+
+impl<T> Default for Stack<T> {
+  fn default() -> Stack<T> {
+    Stack::new()
+  }
+}
+
+impl<T> From<Vec<T>> for Stack<T> {
+  /// The last element of `items` ends up on top.
+  fn from(items: Vec<T>) -> Stack<T> {
+    Stack { items }
+  }
+}
+
+/// Yields the items in pop order, top first.
+pub struct IntoIter<T>(Stack<T>);
+
+impl<T> Iterator for IntoIter<T> {
+  type Item = T;
+
+  fn next(&mut self) -> Option<T> {
+    self.0.pop()
+  }
+}
+
+impl<T> IntoIterator for Stack<T> {
+  type Item = T;
+  type IntoIter = IntoIter<T>;
+
+  fn into_iter(self) -> IntoIter<T> {
+    IntoIter(self)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn last_in_first_out() {
+    let mut stack = Stack::new();
+    stack.push(1);
+    stack.push(2);
+    stack.push(3);
+    assert_eq!(stack.len(), 3);
+    let mut popped = vec![];
+    while let Some(top) = stack.pop() {
+      popped.push(top);
+    }
+    assert_eq!(popped, [3, 2, 1]);
+  }
+
+  #[test]
+  fn peek_leaves_the_top() {
+    let mut stack = Stack::from(vec![String::from("bottom"), String::from("top")]);
+    assert_eq!(stack.peek().map(String::as_str), Some("top"));
+    assert_eq!(stack.peek().map(String::as_str), Some("top"));
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack.pop().as_deref(), Some("top"));
+  }
+
+  #[test]
+  fn drain_stops_at_the_first_miss() {
+    let mut stack = Stack::from(vec![9, 1, 7, 8, 6]);
+    assert_eq!(stack.drain_top_while(|&n| n > 5), [6, 8, 7]);
+    assert_eq!(stack.peek(), Some(&1));
+    assert_eq!(stack.drain_top_while(|&n| n > 5), Vec::<i32>::new());
+    assert_eq!(stack.len(), 2);
+  }
+
+  #[test]
+  fn iterates_in_pop_order() {
+    let stack = Stack::from(vec!['a', 'b', 'c']);
+    assert_eq!(stack.into_iter().collect::<String>(), "cba");
+  }
+
+  #[test]
+  fn empty_stack() {
+    let mut stack: Stack<u8> = Stack::default();
+    assert!(stack.is_empty());
+    assert_eq!(stack.peek(), None);
+    assert_eq!(stack.pop(), None);
+    assert!(stack.drain_top_while(|_| true).is_empty());
+    assert_eq!(stack.into_iter().next(), None);
+  }
+}
@@ -0,0 +1,120 @@
+/// Reading a list of messages without taking any of them. Matching a
+/// `&Message` against non-reference patterns switches the bindings to
+/// references (the default binding modes, or "match ergonomics"), so
+/// `summarize` needs no `ref` and no clones, except to keep a copy of the
+/// last color. `summarize_explicit` is the same function written the way
+/// it was before Rust 2018, with the `&` and the `ref`s spelled out.
+use crate::message::KindCounts;
+use crate::{Color, Message};
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Summary {
+  pub counts: KindCounts,
+  /// Moves are relative, counted as |x| + |y| each
+  pub distance: u64,
+  /// in chars, over every Write
+  pub text_len: usize,
+  pub last_color: Option<Color>
+}
+
+pub fn summarize(messages: &[Message]) -> Summary {
+  let mut summary = Summary::default();
+  for msg in messages {
+    match msg {
+      Message::Quit => summary.counts.quits += 1,
+      Message::Move { x, y } => {
+        summary.counts.moves += 1;
+        // x and y are `&i32` here
+        summary.distance += u64::from(x.unsigned_abs()) + u64::from(y.unsigned_abs());
+      }
+      Message::Write(text) => {
+        summary.counts.writes += 1;
+        summary.text_len += text.chars().count();
+      }
+      Message::ChangeColor(color) => {
+        summary.counts.color_changes += 1;
+        summary.last_color = Some(color.clone());
+      }
+      Message::Resize { .. } | Message::Rotate(_) => summary.counts.other += 1
+    }
+  }
+  summary
+}
+
+// written out in full on purpose, to compare with `summarize`
+#[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference)]
+pub fn summarize_explicit(messages: &[Message]) -> Summary {
+  let mut summary = Summary::default();
+  for msg in messages {
+    match msg {
+      &Message::Quit => summary.counts.quits += 1,
+      &Message::Move { x, y } => {
+        summary.counts.moves += 1;
+        summary.distance += u64::from(x.unsigned_abs()) + u64::from(y.unsigned_abs());
+      }
+      &Message::Write(ref text) => {
+        summary.counts.writes += 1;
+        summary.text_len += text.chars().count();
+      }
+      &Message::ChangeColor(ref color) => {
+        summary.counts.color_changes += 1;
+        summary.last_color = Some(color.clone());
+      }
+      &Message::Resize { .. } | &Message::Rotate(_) => summary.counts.other += 1
+    }
+  }
+  summary
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn demo() -> Vec<Message> {
+    vec![
+      Message::ChangeColor(Color::Hsv(10, 33, 255)),
+      Message::ChangeColor(Color::Rgb(233, 33, 255)),
+      Message::Write(String::from("nothing")),
+      Message::Move { x: 4, y: -7 },
+      Message::Quit
+    ]
+  }
+
+  #[test]
+  fn demo_messages() {
+    let messages = demo();
+    let summary = summarize(&messages);
+    assert_eq!(summary, Summary {
+      counts: KindCounts { quits: 1, moves: 1, writes: 1, color_changes: 2, other: 0 },
+      distance: 11,
+      text_len: 7,
+      last_color: Some(Color::Rgb(233, 33, 255))
+    });
+    assert_eq!(summarize_explicit(&messages), summary);
+  }
+
+  #[test]
+  fn empty_slice() {
+    assert_eq!(summarize(&[]), Summary::default());
+    assert_eq!(summarize_explicit(&[]), Summary::default());
+  }
+
+  #[test]
+  fn messages_are_still_ours() {
+    let mut messages = demo();
+    messages.push(Message::Write(String::from("né")));
+    messages.push(Message::Rotate(90));
+    messages.push(Message::Move { x: i32::MIN, y: 0 });
+    let first = summarize(&messages);
+    // none of this would compile if summarize had moved out of `messages`
+    assert_eq!(summarize_explicit(&messages), first);
+    assert_eq!(first.text_len, 9);
+    assert_eq!(first.distance, 11 + 2_147_483_648);
+    assert_eq!(first.counts.other, 1);
+    let texts: Vec<String> = messages
+      .into_iter()
+      .filter_map(|msg| if let Message::Write(text) = msg { Some(text) } else { None })
+      .collect();
+    assert_eq!(texts, ["nothing", "né"]);
+  }
+}
@@ -0,0 +1,101 @@
+/// A UK-style traffic light as a state machine: every question about a
+/// state is one exhaustive match, so adding a state means the compiler
+/// points at everything that needs to know about it.
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrafficLight {
+  Red,
+  RedAmber,
+  Green,
+  Amber
+}
+
+/// How long Green lasts when a pedestrian is waiting to cross.
+pub const PEDESTRIAN_GREEN: Duration = Duration::from_secs(10);
+
+impl TrafficLight {
+  pub fn next(self) -> TrafficLight {
+    match self {
+      TrafficLight::Red => TrafficLight::RedAmber,
+      TrafficLight::RedAmber => TrafficLight::Green,
+      TrafficLight::Green => TrafficLight::Amber,
+      TrafficLight::Amber => TrafficLight::Red
+    }
+  }
+
+  pub fn duration(&self) -> Duration {
+    match self {
+      TrafficLight::Red => Duration::from_secs(30),
+      TrafficLight::RedAmber => Duration::from_secs(2),
+      TrafficLight::Green => Duration::from_secs(25),
+      TrafficLight::Amber => Duration::from_secs(3)
+    }
+  }
+
+  pub fn can_go(&self) -> bool {
+    match self {
+      TrafficLight::Green => true,
+      TrafficLight::Red | TrafficLight::RedAmber | TrafficLight::Amber => false
+    }
+  }
+
+  /// The next light, and how long this one is held before it, when
+  /// `request` says whether a pedestrian has pressed the button. A request
+  /// cuts a go light short; every other light keeps its usual time.
+  pub fn next_with_input(self, request: bool) -> (TrafficLight, Duration) {
+    match (self, request) {
+      (light, true) if light.can_go() => (light.next(), PEDESTRIAN_GREEN),
+      // Red included: pedestrians are already crossing, the request changes nothing
+      (light, _) => (light.next(), light.duration())
+    }
+  }
+}
+
+/// `start` and the `steps` lights after it.
+pub fn simulate(start: TrafficLight, steps: usize) -> Vec<TrafficLight> {
+  let mut lights = vec![start];
+  let mut light = start;
+  for _ in 0..steps {
+    light = light.next();
+    lights.push(light);
+  }
+  lights
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use TrafficLight::*;
+
+  #[test]
+  fn cycle_has_period_four() {
+    let lights = simulate(Red, 12);
+    assert_eq!(&lights[..5], [Red, RedAmber, Green, Amber, Red]);
+    for (i, light) in lights.iter().enumerate().skip(4) {
+      assert_eq!(*light, lights[i - 4]);
+    }
+    assert_eq!(simulate(Amber, 0), [Amber]);
+  }
+
+  #[test]
+  fn durations() {
+    let secs: Vec<u64> = [Red, RedAmber, Green, Amber].iter().map(|light| light.duration().as_secs()).collect();
+    assert_eq!(secs, [30, 2, 25, 3]);
+  }
+
+  #[test]
+  fn only_green_can_go() {
+    let go: Vec<TrafficLight> = simulate(Red, 3).into_iter().filter(TrafficLight::can_go).collect();
+    assert_eq!(go, [Green]);
+  }
+
+  #[test]
+  fn pedestrian_request_shortens_green_only() {
+    assert_eq!(Green.next_with_input(true), (Amber, PEDESTRIAN_GREEN));
+    assert_eq!(Green.next_with_input(false), (Amber, Green.duration()));
+    assert_eq!(Red.next_with_input(true), (RedAmber, Red.duration()));
+    assert_eq!(Red.next_with_input(true), Red.next_with_input(false));
+    assert_eq!(Amber.next_with_input(true), (Red, Amber.duration()));
+  }
+}
@@ -0,0 +1,132 @@
+// `_x` and `_` both keep the compiler quiet about an unused value, but
+// only `_x` is a binding. A value bound to `_x` is moved into it and
+// lives until the end of its scope; `_` binds nothing, so it neither
+// moves the value nor keeps it alive. A Guard that logs its own drop
+// makes the difference visible.
+use std::cell::RefCell;
+use std::rc::Rc;
+
+type Log = Rc<RefCell<Vec<String>>>;
+
+pub struct Guard {
+  name: &'static str,
+  log: Log
+}
+
+impl Guard {
+  fn new(name: &'static str, log: &Log) -> Guard {
+    Guard { name, log: Rc::clone(log) }
+  }
+}
+
+impl Drop for Guard {
+  fn drop(&mut self) {
+    self.log.borrow_mut().push(format!("drop {}", self.name));
+  }
+}
+
+fn note(log: &Log, marker: &str) {
+  log.borrow_mut().push(marker.to_string());
+}
+
+fn entries(log: Log) -> Vec<String> {
+  log.borrow().clone()
+}
+
+/// `Some(_g)` moves the guard into `_g`, which lasts to the end of the
+/// if-let block.
+pub fn underscore_binding() -> Vec<String> {
+  let log = Log::default();
+  let guarded = Some(Guard::new("guard", &log));
+  if let Some(_g) = guarded {
+    note(&log, "inside if-let");
+  }
+  note(&log, "after if-let");
+  entries(log)
+}
+
+/// `Some(_)` matches without moving, so the guard stays in `guarded` and
+/// goes when `guarded` does. `let _ = Guard::new(..)` on the other hand
+/// leaves the new guard with no owner at all, and it's dropped on the
+/// spot.
+pub fn bare_underscore() -> Vec<String> {
+  let log = Log::default();
+  {
+    let guarded = Some(Guard::new("kept", &log));
+    #[allow(clippy::redundant_pattern_matching)]
+    if let Some(_) = guarded {
+      note(&log, "inside if-let");
+    }
+    note(&log, "after if-let");
+  }
+  note(&log, "after the block");
+
+  let _ = Guard::new("temporary", &log);
+  note(&log, "after let _");
+  entries(log)
+}
+
+/// `let _ = guard;` is a no-op, `guard` keeps the value; `let _g = guard;`
+/// moves it into `_g`. Both are dropped at the end of the block, the
+/// later declared first.
+pub fn let_underscore_vs_named() -> Vec<String> {
+  let log = Log::default();
+  {
+    let first = Guard::new("first", &log);
+    let _ = first;
+    note(&log, "after let _ = first");
+    let second = Guard::new("second", &log);
+    let _g = second;
+    note(&log, "after let _g = second");
+  }
+  note(&log, "after the block");
+  entries(log)
+}
+
+/// The chapter's `if let Some(_) = s`: the String is still in `s` after.
+pub fn some_underscore_keeps_the_string() -> Option<String> {
+  let s = Some(String::from("Next"));
+  #[allow(clippy::redundant_pattern_matching)]
+  if let Some(_) = s {
+    // `s` hasn't been moved from, it could be used here too
+  }
+  s
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn named_binding_lives_to_the_end_of_the_block() {
+    assert_eq!(underscore_binding(), ["inside if-let", "drop guard", "after if-let"]);
+  }
+
+  #[test]
+  fn bare_underscore_doesnt_take_the_value() {
+    assert_eq!(bare_underscore(), [
+      "inside if-let",
+      "after if-let",
+      "drop kept",
+      "after the block",
+      "drop temporary",
+      "after let _"
+    ]);
+  }
+
+  #[test]
+  fn let_underscore_keeps_the_original_owner() {
+    assert_eq!(let_underscore_vs_named(), [
+      "after let _ = first",
+      "after let _g = second",
+      "drop second",
+      "drop first",
+      "after the block"
+    ]);
+  }
+
+  #[test]
+  fn string_is_still_there() {
+    assert_eq!(some_underscore_keeps_the_string().as_deref(), Some("Next"));
+  }
+}
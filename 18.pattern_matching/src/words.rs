@@ -0,0 +1,80 @@
+/// Counting words in a HashMap, with the `(key, value)` tuples it
+/// iterates over taken apart right in the for loop's pattern.
+use std::collections::HashMap;
+
+/// Words are runs of letters, digits and apostrophes, lowercased, so
+/// "The" and "the" count as one word and "don't" stays whole.
+pub fn count_words(text: &str) -> HashMap<String, usize> {
+  let mut counts = HashMap::new();
+  for word in text.split(|c: char| !(c.is_alphanumeric() || c == '\'')).filter(|word| !word.is_empty()) {
+    *counts.entry(word.to_lowercase()).or_insert(0) += 1;
+  }
+  counts
+}
+
+/// The `top` most frequent words in `text`.
+pub fn word_frequencies(text: &str, top: usize) -> Vec<(String, usize)> {
+  rank(&count_words(text), top)
+}
+
+/// The `top` most frequent words, most frequent first, and words with the
+/// same count in alphabetical order.
+pub fn rank(counts: &HashMap<String, usize>, top: usize) -> Vec<(String, usize)> {
+  let mut ranked = Vec::with_capacity(counts.len());
+  for (word, &count) in counts {
+    ranked.push((word.clone(), count));
+  }
+  ranked.sort_by(|(word_a, count_a), (word_b, count_b)| count_b.cmp(count_a).then_with(|| word_a.cmp(word_b)));
+  ranked.truncate(top);
+  ranked
+}
+
+/// `a` with every count from `b` added in.
+pub fn merge_counts(mut a: HashMap<String, usize>, b: &HashMap<String, usize>) -> HashMap<String, usize> {
+  for (word, count) in b {
+    *a.entry(word.clone()).or_insert(0) += count;
+  }
+  a
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn counts(list: &[(&str, usize)]) -> HashMap<String, usize> {
+    list.iter().map(|&(word, count)| (word.to_string(), count)).collect()
+  }
+
+  fn ranked(list: &[(&str, usize)]) -> Vec<(String, usize)> {
+    list.iter().map(|&(word, count)| (word.to_string(), count)).collect()
+  }
+
+  #[test]
+  fn ties_are_alphabetical() {
+    let text = "the cat and the dog and the bird";
+    assert_eq!(word_frequencies(text, 4), ranked(&[("the", 3), ("and", 2), ("bird", 1), ("cat", 1)]));
+    assert_eq!(word_frequencies(text, 1), ranked(&[("the", 3)]));
+    assert_eq!(word_frequencies(text, 100).len(), 5);
+  }
+
+  #[test]
+  fn case_and_punctuation() {
+    assert_eq!(word_frequencies("The THE the. Don't, don't!", 5), ranked(&[("the", 3), ("don't", 2)]));
+    assert_eq!(word_frequencies("Élan élan", 5), ranked(&[("élan", 2)]));
+  }
+
+  #[test]
+  fn nothing_to_count() {
+    assert!(word_frequencies("", 3).is_empty());
+    assert!(word_frequencies(" ,.; ", 3).is_empty());
+    assert!(word_frequencies("word", 0).is_empty());
+  }
+
+  #[test]
+  fn merging() {
+    let merged = merge_counts(counts(&[("a", 1), ("b", 2)]), &counts(&[("b", 3), ("c", 4)]));
+    assert_eq!(merged, counts(&[("a", 1), ("b", 5), ("c", 4)]));
+    assert_eq!(merge_counts(HashMap::new(), &counts(&[("x", 1)])), counts(&[("x", 1)]));
+    assert_eq!(merge_counts(count_words("one two"), &count_words("Two three")), counts(&[("one", 1), ("two", 2), ("three", 1)]));
+  }
+}
@@ -0,0 +1,137 @@
+/// A producer thread sends Message values over an mpsc channel and a
+/// consumer thread matches on each variant to update its state. Every
+/// arm of the match is one kind of work, and Quit ends the loop early.
+use std::sync::mpsc::{self, Receiver};
+use std::thread::{self, JoinHandle};
+use crate::{Color, Message};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorkerState {
+  /// the sum of every Move so far, like Canvas, stopping at the i32 edge
+  pub position: (i32, i32),
+  pub last_text: String,
+  pub color: Option<Color>,
+  pub quit: bool
+}
+
+impl WorkerState {
+  /// Applies one message, returning false once the worker should stop.
+  pub fn apply(&mut self, msg: Message) -> bool {
+    match msg {
+      Message::Quit => {
+        self.quit = true;
+        return false;
+      },
+      Message::Move { x, y } => {
+        let (at_x, at_y) = self.position;
+        self.position = (at_x.saturating_add(x), at_y.saturating_add(y));
+      },
+      Message::Write(text) => self.last_text = text,
+      Message::ChangeColor(color) => self.color = Some(color),
+      // the worker doesn't track the canvas geometry
+      Message::Resize { .. } | Message::Rotate(_) => {}
+    }
+    true
+  }
+}
+
+/// Consumes messages until Quit arrives or every sender is dropped.
+pub fn spawn_worker(rx: Receiver<Message>) -> JoinHandle<WorkerState> {
+  thread::spawn(move || {
+    let mut state = WorkerState::default();
+    while let Ok(msg) = rx.recv() {
+      if !state.apply(msg) {
+        break;
+      }
+    }
+    state
+  })
+}
+
+/// Sends `messages` from a producer thread to a worker and returns the
+/// worker's final state once both threads have finished.
+pub fn run_pipeline(messages: Vec<Message>) -> WorkerState {
+  let (tx, rx) = mpsc::channel();
+  let worker = spawn_worker(rx);
+  let producer = thread::spawn(move || {
+    for msg in messages {
+      // the worker hung up after Quit, nobody is listening anymore
+      if tx.send(msg).is_err() {
+        break;
+      }
+    }
+  });
+
+  producer.join().unwrap();
+  worker.join().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn write(text: &str) -> Message {
+    Message::Write(String::from(text))
+  }
+
+  #[test]
+  fn scripted_sequence() {
+    let state = run_pipeline(vec![
+      Message::Move { x: 1, y: 2 },
+      write("first"),
+      Message::ChangeColor(Color::Hsv(10, 20, 30)),
+      Message::Move { x: -5, y: 8 },
+      write("second"),
+      Message::Quit
+    ]);
+    assert_eq!(state, WorkerState {
+      position: (-4, 10),
+      last_text: String::from("second"),
+      color: Some(Color::Hsv(10, 20, 30)),
+      quit: true
+    });
+  }
+
+  #[test]
+  fn messages_after_quit_are_ignored() {
+    let state = run_pipeline(vec![write("kept"), Message::Quit, write("ignored"), Message::Move { x: 9, y: 9 }]);
+    assert_eq!(state.last_text, "kept");
+    assert_eq!(state.position, (0, 0));
+    assert!(state.quit);
+  }
+
+  #[test]
+  fn dropping_the_sender_ends_the_loop() {
+    let (tx, rx) = mpsc::channel();
+    let worker = spawn_worker(rx);
+    tx.send(Message::ChangeColor(Color::Rgb(1, 2, 3))).unwrap();
+    drop(tx);
+
+    let state = worker.join().unwrap();
+    assert_eq!(state.color, Some(Color::Rgb(1, 2, 3)));
+    assert!(!state.quit);
+  }
+
+  #[test]
+  fn moves_add_up_like_on_the_canvas() {
+    let moves = vec![Message::Move { x: 3, y: -1 }, Message::Move { x: 3, y: -1 }, Message::Move { x: i32::MAX, y: 0 }];
+    let mut canvas = crate::canvas::Canvas::new();
+    for msg in moves.clone() {
+      canvas.apply(msg);
+    }
+    let state = run_pipeline(moves);
+    assert_eq!(state.position, (i32::MAX, -2));
+    assert_eq!(state.position, canvas.position);
+  }
+
+  #[test]
+  fn empty_script_keeps_the_default_state() {
+    assert_eq!(run_pipeline(vec![]), WorkerState::default());
+  }
+
+  #[test]
+  fn geometry_messages_leave_the_state_alone() {
+    let state = run_pipeline(vec![Message::Resize { width: 4, height: 3 }, Message::Rotate(90)]);
+    assert_eq!(state, WorkerState::default());
+  }
+}